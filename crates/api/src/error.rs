@@ -12,6 +12,9 @@ pub enum Error {
     #[error(transparent)]
     FromUtf8(#[from] std::string::FromUtf8Error),
 
+    #[error(transparent)]
+    Lua(#[from] luajit::Error),
+
     #[error(transparent)]
     Nvim(#[from] types::Error),
 
@@ -23,6 +26,15 @@ pub enum Error {
 }
 
 impl Error {
+    /// Returns the stack traceback of the error, if it was raised by Lua
+    /// code called from Rust.
+    pub fn traceback(&self) -> Option<&luajit::Traceback> {
+        match self {
+            Self::Lua(err) => err.traceback(),
+            _ => None,
+        }
+    }
+
     pub(crate) fn custom<M: Display>(msg: M) -> Self {
         Self::Other(msg.to_string())
     }
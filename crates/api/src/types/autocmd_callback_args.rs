@@ -1,16 +1,13 @@
 use std::path::PathBuf;
 
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::{Object, conversion::FromObject};
 
 use crate::Buffer;
 
 #[non_exhaustive]
-#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, macros::FromObject)]
+#[object(serde, nvim_oxi = types)]
 pub struct AutocmdCallbackArgs {
     /// The `Buffer` specified by `<abuf>`.
     #[serde(rename = "buf")]
@@ -39,12 +36,6 @@ pub struct AutocmdCallbackArgs {
     pub r#match: String,
 }
 
-impl FromObject for AutocmdCallbackArgs {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
-
 impl luajit::Poppable for AutocmdCallbackArgs {
     unsafe fn pop(
         lstate: *mut luajit::ffi::State,
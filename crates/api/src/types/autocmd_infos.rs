@@ -1,15 +1,13 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
 
 use crate::Buffer;
 
 /// Informations related to an autocommand.
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Hash, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct AutocmdInfos {
     /// The `Buffer` associated to the autocommand. Only present if `buflocal`
     /// is `true`.
@@ -48,9 +46,3 @@ pub struct AutocmdInfos {
     /// The autocommand's pattern.
     pub pattern: String,
 }
-
-impl FromObject for AutocmdInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
@@ -1,15 +1,11 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
 
 use super::ClientInfos;
 use crate::Buffer;
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, macros::FromObject)]
+#[object(serde, nvim_oxi = types)]
 pub struct ChannelInfos {
     /// Job arguments list.
     pub argv: Option<Vec<String>>,
@@ -55,9 +51,3 @@ pub enum ChannelMode {
     Rpc,
     Terminal,
 }
-
-impl FromObject for ChannelInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
@@ -2,7 +2,7 @@ use serde::Deserialize;
 use types::{
     Array,
     Object,
-    conversion::{self, FromObject, ToObject},
+    conversion::{self, ToObject},
     serde::Deserializer,
 };
 use types::{Boolean, Dictionary, Integer, String as NvimString};
@@ -11,7 +11,8 @@ use super::{CmdMagic, CmdRange, CommandAddr, CommandModifiers, CommandNArgs};
 use crate::serde_utils as utils;
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, macros::FromObject)]
+#[object(serde, nvim_oxi = types)]
 pub struct CmdInfos {
     /// Value of `:command-addr`. Uses short name.
     #[serde(default, deserialize_with = "utils::none_literal_is_none")]
@@ -156,13 +157,6 @@ impl CmdInfosBuilder {
     }
 }
 
-impl FromObject for CmdInfos {
-    #[inline]
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
-
 #[derive(Default, Debug, Clone, macros::OptsBuilder)]
 #[repr(C)]
 pub(crate) struct ParseCmdOutput {
@@ -1,13 +1,19 @@
 use serde::{Deserialize, Serialize};
-use types::{
-    Object,
-    conversion::{self, ToObject},
-    serde::Serializer,
-};
 
 /// See `:h command-addr` for details.
 #[non_exhaustive]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    Hash,
+    Serialize,
+    Deserialize,
+    macros::ToObject,
+)]
+#[object(serde, nvim_oxi = types)]
 #[serde(rename_all = "snake_case")]
 pub enum CommandAddr {
     Lines,
@@ -34,9 +40,3 @@ impl CommandAddr {
         }
     }
 }
-
-impl ToObject for CommandAddr {
-    fn to_object(self) -> Result<Object, conversion::Error> {
-        self.serialize(Serializer::new()).map_err(Into::into)
-    }
-}
@@ -1,9 +1,5 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::{Object, conversion::FromObject};
 
 use crate::serde_utils as utils;
 
@@ -13,7 +9,10 @@ use crate::serde_utils as utils;
 /// create a buffer-local command or
 /// [`create_user_command`](crate::create_user_command) to create a global one.
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Hash, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct CommandArgs {
     /// The arguments passed to the command, if any.
     #[serde(deserialize_with = "utils::empty_string_is_none")]
@@ -50,12 +49,6 @@ pub struct CommandArgs {
     pub smods: super::CommandModifiers,
 }
 
-impl FromObject for CommandArgs {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
-
 impl luajit::Poppable for CommandArgs {
     unsafe fn pop(
         lstate: *mut luajit::ffi::State,
@@ -1,14 +1,10 @@
 use serde::Serialize;
-use types::{
-    Function,
-    Object,
-    conversion::{self, ToObject},
-    serde::Serializer,
-};
+use types::Function;
 
 /// See `:h command-complete` for details.
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, macros::ToObject)]
+#[object(serde, nvim_oxi = types)]
 #[serde(rename_all = "snake_case")]
 pub enum CommandComplete {
     Arglist,
@@ -50,9 +46,3 @@ pub enum CommandComplete {
     /// See `:h command-completion-customlist` for details.
     CustomList(Function<(String, String, usize), Vec<String>>),
 }
-
-impl ToObject for CommandComplete {
-    fn to_object(self) -> Result<Object, conversion::Error> {
-        self.serialize(Serializer::new()).map_err(Into::into)
-    }
-}
@@ -2,17 +2,15 @@ use serde::{
     Deserialize,
     de::{self, Error},
 };
-use types::{
-    Function,
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::Function;
 
 use super::{CommandAddr, CommandArgs, CommandNArgs, CommandRange};
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Hash, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct CommandInfos {
     /// TODO: docs
     pub addr: Option<CommandAddr>,
@@ -73,9 +71,3 @@ where
         })
         .transpose()
 }
-
-impl FromObject for CommandInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
@@ -1,10 +1,5 @@
 use serde::{Deserialize, Serialize};
-use types::{
-    Dictionary,
-    Object,
-    conversion::{self, ToObject},
-    serde::Serializer,
-};
+use types::{Dictionary, conversion::ToObject};
 
 use super::SplitModifier;
 use crate::serde_utils as utils;
@@ -12,8 +7,18 @@ use crate::serde_utils as utils;
 /// See `:h command-modifiers` for more infos.
 #[non_exhaustive]
 #[derive(
-    Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Hash,
+    Deserialize,
+    Serialize,
+    macros::ToObject,
 )]
+#[object(serde, nvim_oxi = types)]
 pub struct CommandModifiers {
     pub browse: bool,
     pub confirm: bool,
@@ -35,12 +40,6 @@ pub struct CommandModifiers {
     pub vertical: bool,
 }
 
-impl ToObject for CommandModifiers {
-    fn to_object(self) -> Result<Object, conversion::Error> {
-        self.serialize(Serializer::new()).map_err(Into::into)
-    }
-}
-
 impl From<CommandModifiers> for Dictionary {
     fn from(mods: CommandModifiers) -> Self {
         let obj = mods.to_object().unwrap();
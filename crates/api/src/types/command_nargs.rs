@@ -1,13 +1,19 @@
 use serde::{Deserialize, Serialize, ser};
-use types::{
-    Object,
-    conversion::{self, ToObject},
-    serde::Serializer,
-};
 
 /// Number of arguments accepted by a command.
 #[non_exhaustive]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Hash,
+    Deserialize,
+    macros::ToObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub enum CommandNArgs {
     #[default]
     #[serde(rename = "0")]
@@ -41,9 +47,3 @@ impl Serialize for CommandNArgs {
         }
     }
 }
-
-impl ToObject for CommandNArgs {
-    fn to_object(self) -> Result<Object, conversion::Error> {
-        self.serialize(Serializer::new()).map_err(Into::into)
-    }
-}
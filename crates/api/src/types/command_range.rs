@@ -1,17 +1,15 @@
 use std::fmt;
 
 use serde::{Serialize, de, ser};
-use types::{
-    Object,
-    conversion::{self, ToObject},
-    serde::Serializer,
-};
 
 // use crate::object::{self, ToObject};
 
 /// See `:h command-range` for details.
 #[non_exhaustive]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize)]
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, macros::ToObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub enum CommandRange {
     #[serde(serialize_with = "serialize_as_true")]
     CurrentLine,
@@ -22,12 +20,6 @@ pub enum CommandRange {
     Count(u32),
 }
 
-impl ToObject for CommandRange {
-    fn to_object(self) -> Result<Object, conversion::Error> {
-        self.serialize(Serializer::new()).map_err(Into::into)
-    }
-}
-
 impl<'de> de::Deserialize<'de> for CommandRange {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -1,14 +1,11 @@
 use serde::Deserialize;
-use types::{
-    Array,
-    Dictionary,
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::{Array, Dictionary};
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct EditorContext {
     #[serde(default, rename = "bufs")]
     pub bufferlist: Vec<types::String>,
@@ -129,9 +126,3 @@ impl From<EditorContext> for Dictionary {
         ])
     }
 }
-
-impl FromObject for EditorContext {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
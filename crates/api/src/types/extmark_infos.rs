@@ -1,15 +1,13 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
 
 use super::{ExtmarkHlMode, ExtmarkVirtTextChunk, ExtmarkVirtTextPosition};
 
 /// Extmark infos returned by `Buffer::get_extmark_by_id`.
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Hash, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct ExtmarkInfos {
     #[serde(default)]
     pub end_col: Option<usize>,
@@ -65,9 +63,3 @@ pub struct ExtmarkInfos {
     #[serde(default)]
     pub virt_text_win_col: Option<i64>,
 }
-
-impl FromObject for ExtmarkInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
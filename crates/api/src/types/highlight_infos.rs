@@ -1,13 +1,11 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
 
 /// Attributes related to a highlight group.
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Default)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Deserialize, Default, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct HighlightInfos {
     pub altfont: Option<bool>,
     pub background: Option<u32>,
@@ -30,9 +28,3 @@ pub struct HighlightInfos {
     pub underline: Option<bool>,
     pub underlineline: Option<bool>,
 }
-
-impl FromObject for HighlightInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
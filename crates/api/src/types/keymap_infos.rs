@@ -1,16 +1,14 @@
 use serde::Deserialize;
-use types::{
-    Function,
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::Function;
 
 use super::Mode;
 use crate::{Buffer, serde_utils as utils};
 
 #[non_exhaustive]
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Debug, Clone, Eq, PartialEq, Hash, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct KeymapInfos {
     /// When the [`KeymapInfos`] are returned from [`Buffer::get_keymap()`],
     /// this will contain the [`Buffer`] it was called on. `None` when returned
@@ -60,9 +58,3 @@ pub struct KeymapInfos {
     #[serde(deserialize_with = "utils::bool_from_int")]
     pub silent: bool,
 }
-
-impl FromObject for KeymapInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
@@ -1,14 +1,11 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::Object;
 
 /// Informations related to an option. Unlike in the Lua API, the `type` field
 /// is omitted because it's included in the definition of `default`.
 #[non_exhaustive]
-#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, macros::FromObject)]
+#[object(serde, nvim_oxi = types)]
 pub struct OptionInfos {
     /// TODO: docs
     pub allows_duplicates: bool,
@@ -47,12 +44,6 @@ pub struct OptionInfos {
     pub was_set: bool,
 }
 
-impl FromObject for OptionInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
-
 #[non_exhaustive]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
 pub enum OptionScope {
@@ -3,18 +3,15 @@ use core::mem::transmute;
 use std::collections::BTreeSet;
 
 use serde::Deserialize;
-use types::{
-    Float,
-    Integer,
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::{Float, Integer};
 
 use super::viml_ast_node::*;
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 /// Informations about a parsed VimL expression returned by
 /// [`parse_expression`](crate::parse_expression).
 pub struct ParsedVimLExpression {
@@ -228,9 +225,3 @@ impl PartialOrd for VimLExpressionAst {
         Some(self.cmp(other))
     }
 }
-
-impl FromObject for ParsedVimLExpression {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
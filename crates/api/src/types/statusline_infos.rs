@@ -1,16 +1,14 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
 
 use super::StatuslineHighlightInfos;
 
 /// Statusline informations returned by
 /// [`eval_statusline`](crate::eval_statusline).
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Hash, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct StatuslineInfos {
     /// Vector of highlight informations for the statusline populated if the
     /// [`highlights`](crate::opts::EvalStatuslineOptsBuilder::highlights)
@@ -24,9 +22,3 @@ pub struct StatuslineInfos {
     /// Display width of the statusline.
     pub width: u32,
 }
-
-impl FromObject for StatuslineInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
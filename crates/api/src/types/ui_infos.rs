@@ -1,15 +1,11 @@
 use serde::Deserialize;
-use types::{
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
 
 use crate::serde_utils as utils;
 
 /// Informations about an attached UI.
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, macros::FromObject)]
+#[object(serde, nvim_oxi = types)]
 pub struct UiInfos {
     /// Channel id or remote UI (not present for TUI).
     #[serde(rename = "chan", deserialize_with = "utils::zero_is_none")]
@@ -36,9 +32,3 @@ pub struct UiInfos {
     /// Requested height of the UI.
     pub width: usize,
 }
-
-impl FromObject for UiInfos {
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
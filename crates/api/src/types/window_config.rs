@@ -1,12 +1,5 @@
 use serde::Deserialize;
-use types::{
-    Array,
-    Float,
-    Integer,
-    Object,
-    conversion::{self, FromObject},
-    serde::Deserializer,
-};
+use types::{Array, Float, Integer, Object, conversion, serde::Deserializer};
 use types::{Boolean, String as NvimString, WinHandle};
 
 use super::{WindowAnchor, WindowBorder, WindowRelativeTo, WindowStyle};
@@ -14,7 +7,10 @@ use crate::Window;
 use crate::serde_utils as utils;
 
 #[non_exhaustive]
-#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[derive(
+    Clone, Debug, Default, PartialEq, Deserialize, macros::FromObject,
+)]
+#[object(serde, nvim_oxi = types)]
 pub struct WindowConfig {
     /// Decides which corner of the window to place at `(row, col)`.
     #[serde(default, deserialize_with = "utils::empty_string_is_none")]
@@ -271,13 +267,6 @@ impl WindowConfigBuilder {
     }
 }
 
-impl FromObject for WindowConfig {
-    #[inline]
-    fn from_object(obj: Object) -> Result<Self, conversion::Error> {
-        Self::deserialize(Deserializer::new(obj)).map_err(Into::into)
-    }
-}
-
 #[derive(Clone, Default, Debug, macros::OptsBuilder)]
 #[repr(C)]
 pub struct WindowOpts {
@@ -1,5 +1,8 @@
 use std::error::Error as StdError;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
 
+use crate::callback::{self, HandleKind};
 use crate::{Error, Handle, IntoResult, ffi};
 
 type Callback = Box<dyn FnMut() -> Result<(), Box<dyn StdError>> + 'static>;
@@ -7,14 +10,27 @@ type Callback = Box<dyn FnMut() -> Result<(), Box<dyn StdError>> + 'static>;
 /// Binding to libuv's [Async handle][1] used to trigger the execution of a
 /// callback in the Neovim thread.
 ///
+/// Cloning an `AsyncHandle` returns a new reference to the same handle, and
+/// dropping it doesn't close the handle. Use [`close`](AsyncHandle::close)
+/// to free the handle and its callback.
+///
 /// [1]: http://docs.libuv.org/en/v1.x/async.html
 #[derive(Clone)]
 pub struct AsyncHandle {
-    handle: Handle<ffi::uv_async_t, Callback>,
+    inner: Arc<Inner>,
 }
 
-unsafe impl Send for AsyncHandle {}
-unsafe impl Sync for AsyncHandle {}
+struct Inner {
+    /// The handle, or `None` if it's been closed. It's guarded by a lock so
+    /// that it can't be closed while another thread is sending to it.
+    handle: Mutex<Option<Handle<ffi::uv_async_t, Callback>>>,
+
+    /// The thread the handle was created on.
+    thread: ThreadId,
+}
+
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
 
 impl AsyncHandle {
     /// Registers a new callback on the Neovim event loop, returning an
@@ -43,7 +59,12 @@ impl AsyncHandle {
 
         unsafe { handle.set_data(callback) };
 
-        Ok(Self { handle })
+        let inner = Inner {
+            handle: Mutex::new(Some(handle)),
+            thread: thread::current().id(),
+        };
+
+        Ok(Self { inner: Arc::new(inner) })
     }
 
     /// Wakes up the Neovim event loop and executes the callback associated to
@@ -58,9 +79,16 @@ impl AsyncHandle {
     /// will be called again.
     ///
     /// [libuv]: https://libuv.org/
+    ///
+    /// Fails if the handle has been closed.
     pub fn send(&self) -> Result<(), Error> {
-        let retv =
-            unsafe { ffi::uv_async_send(self.handle.as_ptr() as *mut _) };
+        let handle = self.inner.handle.lock().unwrap();
+
+        let Some(handle) = &*handle else {
+            return Err(Error::HandleClosed);
+        };
+
+        let retv = unsafe { ffi::uv_async_send(handle.as_ptr() as *mut _) };
 
         if retv < 0 {
             return Err(Error::AsyncTrigger);
@@ -68,6 +96,85 @@ impl AsyncHandle {
 
         Ok(())
     }
+
+    /// Closes the handle, executing `callback` once libuv is done with it.
+    ///
+    /// Every clone of this handle is closed too, and calling
+    /// [`send`](AsyncHandle::send) on them will fail. The handle's callback
+    /// is freed before `callback` is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a thread other than the one the handle was
+    /// created on.
+    pub fn close<Cb>(self, callback: Cb)
+    where
+        Cb: FnOnce() + 'static,
+    {
+        self.with_handle(|handle| {
+            if let Some(mut handle) = handle.take() {
+                unsafe { handle.close_with(Some(Box::new(callback))) };
+            }
+        })
+    }
+
+    /// Returns whether the handle is active, which is always the case until
+    /// it's closed.
+    pub fn is_active(&self) -> bool {
+        self.inner.handle.lock().unwrap().is_some()
+    }
+
+    /// Returns whether the handle is closing or closed.
+    pub fn is_closing(&self) -> bool {
+        self.inner.handle.lock().unwrap().is_none()
+    }
+
+    /// Makes the handle keep the event loop alive. Handles are referenced
+    /// by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a thread other than the one the handle was
+    /// created on.
+    pub fn ref_(&mut self) {
+        self.with_handle(|handle| handle.iter_mut().for_each(Handle::ref_))
+    }
+
+    /// Stops the handle from keeping the event loop alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a thread other than the one the handle was
+    /// created on.
+    pub fn unref(&mut self) {
+        self.with_handle(|handle| handle.iter_mut().for_each(Handle::unref))
+    }
+
+    /// Returns whether the handle is referenced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a thread other than the one the handle was
+    /// created on.
+    pub fn has_ref(&self) -> bool {
+        self.with_handle(|handle| handle.as_ref().is_some_and(Handle::has_ref))
+    }
+
+    /// Calls `fun` with the handle, panicking if we're not on the thread
+    /// that created it since libuv handles are not thread safe.
+    fn with_handle<F, R>(&self, fun: F) -> R
+    where
+        F: FnOnce(&mut Option<Handle<ffi::uv_async_t, Callback>>) -> R,
+    {
+        assert_eq!(
+            thread::current().id(),
+            self.inner.thread,
+            "async handles can only be modified on the thread they were \
+             created on"
+        );
+
+        fun(&mut self.inner.handle.lock().unwrap())
+    }
 }
 
 extern "C" fn async_cb(ptr: *mut ffi::uv_async_t) {
@@ -78,8 +185,6 @@ extern "C" fn async_cb(ptr: *mut ffi::uv_async_t) {
     if !callback.is_null() {
         let callback = unsafe { &mut *callback };
 
-        if let Err(_err) = callback() {
-            // TODO: what now?
-        }
+        callback::call(HandleKind::Async, callback);
     }
 }
@@ -0,0 +1,146 @@
+//! Error reporting for the callbacks registered on libuv handles.
+//!
+//! Callbacks are executed by libuv from within Neovim's event loop, so
+//! there's no caller to return their errors to, and unwinding out of them
+//! would abort the whole editor. Instead, every callback is executed via
+//! [`call`], which catches both errors and panics and forwards them to the
+//! handler set with [`set_error_handler`].
+
+use core::cell::RefCell;
+use core::fmt;
+use std::any::Any;
+use std::error::Error as StdError;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use api::types::LogLevel;
+use thiserror::Error as ThisError;
+
+type ErrorHandler = Rc<dyn Fn(CallbackError) + 'static>;
+
+thread_local! {
+    static ERROR_HANDLER: RefCell<Option<ErrorHandler>> =
+        const { RefCell::new(None) };
+}
+
+/// An error returned, or a panic raised, by a callback executed by libuv.
+#[derive(Debug, ThisError)]
+pub enum CallbackError {
+    #[error("{handle} callback returned an error: {error}")]
+    Error { handle: HandleKind, error: Box<dyn StdError> },
+
+    #[error("{handle} callback panicked: {message}")]
+    Panic { handle: HandleKind, message: String },
+}
+
+/// The kind of handle whose callback failed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HandleKind {
+    Async,
+    Check,
+    FsEvent,
+    FsPoll,
+    Idle,
+    Prepare,
+    Signal,
+    Timer,
+
+    /// A task spawned via [`spawn`](crate::spawn) whose
+    /// [`JoinHandle`](crate::JoinHandle) has been dropped.
+    Task,
+
+    /// The `after` callback of [`queue_work`](crate::queue_work), or the
+    /// work itself.
+    Work,
+}
+
+impl CallbackError {
+    /// Returns the kind of handle whose callback failed.
+    pub fn handle(&self) -> HandleKind {
+        match self {
+            Self::Error { handle, .. } | Self::Panic { handle, .. } => *handle,
+        }
+    }
+}
+
+impl fmt::Display for HandleKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Async => "async",
+            Self::Check => "check",
+            Self::FsEvent => "fs_event",
+            Self::FsPoll => "fs_poll",
+            Self::Idle => "idle",
+            Self::Prepare => "prepare",
+            Self::Signal => "signal",
+            Self::Timer => "timer",
+            Self::Task => "task",
+            Self::Work => "work",
+        })
+    }
+}
+
+/// Sets the function called with the errors returned, and the panics raised,
+/// by the callbacks of all the handles.
+///
+/// The default handler reports them via [`api::notify`] at
+/// [`LogLevel::Error`].
+///
+/// NOTE: this function **must** be called from the main thread.
+///
+/// [`api::notify`]: https://docs.rs/nvim-oxi/latest/nvim_oxi/api/fn.notify.html
+pub fn set_error_handler<F>(handler: F)
+where
+    F: Fn(CallbackError) + 'static,
+{
+    ERROR_HANDLER.with(|h| *h.borrow_mut() = Some(Rc::new(handler)));
+}
+
+/// Restores the default error handler.
+pub fn reset_error_handler() {
+    ERROR_HANDLER.with(|h| *h.borrow_mut() = None);
+}
+
+/// Executes a callback of the given handle, reporting the error it returns
+/// or the panic it raises.
+pub(crate) fn call<F>(handle: HandleKind, callback: F)
+where
+    F: FnOnce() -> Result<(), Box<dyn StdError>>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(callback)) {
+        Ok(Ok(())) => {},
+        Ok(Err(error)) => report(CallbackError::Error { handle, error }),
+        Err(payload) => report(CallbackError::Panic {
+            handle,
+            message: panic_message(&*payload),
+        }),
+    }
+}
+
+/// Forwards the error to the current error handler.
+pub(crate) fn report(err: CallbackError) {
+    // Cloned so that the handler can replace itself.
+    let handler = ERROR_HANDLER.with(|h| h.borrow().clone());
+
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| match handler {
+        Some(handler) => handler(err),
+        None => default_handler(err),
+    }));
+}
+
+/// Returns the message of a panic payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
+}
+
+fn default_handler(err: CallbackError) {
+    let _ =
+        api::notify(&err.to_string(), LogLevel::Error, &Default::default());
+}
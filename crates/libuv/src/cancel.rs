@@ -0,0 +1,401 @@
+use core::cell::RefCell;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::rc::{Rc, Weak};
+
+use api::opts::{CreateAutocmdOpts, CreateAutocmdOptsBuilder};
+use api::{Buffer, Window};
+
+use crate::{
+    CheckHandle,
+    Child,
+    FsEventHandle,
+    FsPollHandle,
+    IdleHandle,
+    JoinHandle,
+    PrepareHandle,
+    SignalHandle,
+    TimerHandle,
+    spawn,
+};
+
+type OnCancel = Box<dyn FnOnce() + 'static>;
+
+/// A token used to cancel the async work tied to a given scope, e.g. the
+/// lifetime of a buffer or a window.
+///
+/// Cancelling a token aborts all the tasks spawned via
+/// [`spawn`](CancellationToken::spawn), stops all the handles and processes
+/// registered via [`own`](CancellationToken::own), and cancels all its
+/// [child tokens](CancellationToken::child_token).
+///
+/// Cloning a token returns a new reference to the same token.
+///
+/// NOTE: tokens can only be used on the main thread.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Rc<RefCell<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    is_cancelled: bool,
+
+    /// The key given to the next callback or waker.
+    next_key: u64,
+
+    /// The callbacks to execute when the token is cancelled.
+    on_cancel: Vec<(u64, OnCancel)>,
+
+    /// The wakers of the tasks waiting for the token to be cancelled, keyed
+    /// by the [`Cancelled`] future that registered them.
+    wakers: Vec<(u64, Waker)>,
+
+    /// The id of the autocommand which cancels the token, if any.
+    autocmd_id: Option<u32>,
+
+    /// The parent of a child token, and the key of the callback which
+    /// cancels the child when the parent is cancelled.
+    parent: Option<(Weak<RefCell<Inner>>, CancelKey)>,
+}
+
+/// The key returned by [`CancellationToken::on_cancel`] and
+/// [`CancellationToken::own`], which can be passed to
+/// [`remove_on_cancel`](CancellationToken::remove_on_cancel) to unregister
+/// the callback.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CancelKey(u64);
+
+/// The future returned by [`CancellationToken::cancelled`].
+///
+/// It resolves once the token has been cancelled.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Cancelled {
+    token: CancellationToken,
+
+    /// The key of the waker registered by this future, if any.
+    key: Option<u64>,
+}
+
+/// Trait implemented by the values that can be owned by a
+/// [`CancellationToken`], and which are stopped when the token is cancelled.
+pub trait Cancellable: 'static {
+    /// Stops the value.
+    fn cancel(self);
+}
+
+impl CancellationToken {
+    /// Creates a new token that's only cancelled by calling
+    /// [`cancel`](CancellationToken::cancel).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new token that's cancelled when the given buffer is wiped
+    /// out.
+    pub fn for_buffer(buffer: &Buffer) -> Result<Self, api::Error> {
+        Self::with_autocmd("BufWipeout", |opts| opts.buffer(buffer.clone()))
+    }
+
+    /// Creates a new token that's cancelled when the given window is closed.
+    pub fn for_window(window: &Window) -> Result<Self, api::Error> {
+        // `WinClosed` matches its pattern against the window's id.
+        let pattern = window.handle().to_string();
+        Self::with_autocmd("WinClosed", |opts| opts.patterns([&*pattern]))
+    }
+
+    /// Creates a new token that's cancelled right before Neovim exits.
+    pub fn on_vim_leave() -> Result<Self, api::Error> {
+        Self::with_autocmd("VimLeavePre", |opts| opts)
+    }
+
+    fn with_autocmd<F>(event: &str, configure: F) -> Result<Self, api::Error>
+    where
+        F: FnOnce(
+            &mut CreateAutocmdOptsBuilder,
+        ) -> &mut CreateAutocmdOptsBuilder,
+    {
+        let token = Self::new();
+
+        // The autocommand only holds a weak reference to the token so that
+        // dropping every other reference to it frees the token.
+        let weak = Rc::downgrade(&token.inner);
+
+        let opts = configure(&mut CreateAutocmdOpts::builder())
+            .once(true)
+            .callback(move |_| {
+                if let Some(inner) = Weak::upgrade(&weak) {
+                    // The autocommand is deleted by Neovim since it's `once`.
+                    inner.borrow_mut().autocmd_id = None;
+                    CancellationToken { inner }.cancel();
+                }
+                true
+            })
+            .build();
+
+        let id = api::create_autocmd([event], &opts)?;
+
+        token.inner.borrow_mut().autocmd_id = Some(id);
+
+        Ok(token)
+    }
+
+    /// Cancels the token, executing all the cleanup tied to it.
+    ///
+    /// Does nothing if the token has already been cancelled.
+    pub fn cancel(&self) {
+        let (on_cancel, wakers, autocmd_id) = {
+            let inner = &mut *self.inner.borrow_mut();
+
+            if inner.is_cancelled {
+                return;
+            }
+
+            inner.is_cancelled = true;
+
+            (
+                core::mem::take(&mut inner.on_cancel),
+                core::mem::take(&mut inner.wakers),
+                inner.autocmd_id.take(),
+            )
+        };
+
+        if let Some(id) = autocmd_id {
+            // The autocommand may have already been deleted by the user.
+            let _ = api::del_autocmd(id);
+        }
+
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+
+        for (_, on_cancel) in on_cancel {
+            on_cancel();
+        }
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.borrow().is_cancelled
+    }
+
+    /// Returns a future that resolves once the token has been cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled { token: self.clone(), key: None }
+    }
+
+    /// Registers a callback to be executed when the token is cancelled,
+    /// returning a key that can be used to unregister it.
+    ///
+    /// If the token has already been cancelled the callback is executed
+    /// immediately.
+    pub fn on_cancel<F>(&self, fun: F) -> CancelKey
+    where
+        F: FnOnce() + 'static,
+    {
+        let mut inner = self.inner.borrow_mut();
+
+        let key = inner.next_key();
+
+        if inner.is_cancelled {
+            drop(inner);
+            fun();
+        } else {
+            inner.on_cancel.push((key, Box::new(fun)));
+        }
+
+        CancelKey(key)
+    }
+
+    /// Unregisters a callback registered via
+    /// [`on_cancel`](CancellationToken::on_cancel) or
+    /// [`own`](CancellationToken::own), dropping it without executing it.
+    ///
+    /// Returns whether the callback was still registered, i.e. whether the
+    /// token hadn't been cancelled yet.
+    pub fn remove_on_cancel(&self, key: CancelKey) -> bool {
+        self.inner.borrow_mut().remove_on_cancel(key)
+    }
+
+    /// Creates a new token that's cancelled when this token is cancelled,
+    /// but which can also be cancelled on its own without affecting this
+    /// one.
+    ///
+    /// Dropping every reference to the child unregisters it from this
+    /// token.
+    pub fn child_token(&self) -> Self {
+        let child = Self::new();
+        let weak = Rc::downgrade(&child.inner);
+
+        let key = self.on_cancel(move || {
+            if let Some(inner) = Weak::upgrade(&weak) {
+                CancellationToken { inner }.cancel();
+            }
+        });
+
+        child.inner.borrow_mut().parent =
+            Some((Rc::downgrade(&self.inner), key));
+
+        child
+    }
+
+    /// Transfers ownership of a handle, a process or a task to the token,
+    /// which will stop it when cancelled.
+    ///
+    /// Passing the returned key to
+    /// [`remove_on_cancel`](CancellationToken::remove_on_cancel) drops the
+    /// value without stopping it.
+    pub fn own<C: Cancellable>(&self, value: C) -> CancelKey {
+        self.on_cancel(move || value.cancel())
+    }
+
+    /// Spawns a new task via [`spawn`](crate::spawn) which is aborted when
+    /// the token is cancelled.
+    ///
+    /// The returned [`JoinHandle`] resolves to `None` if the token was
+    /// cancelled before the task could complete.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let token = self.clone();
+        spawn(async move { token.run_until_cancelled(future).await })
+    }
+
+    /// Runs the given future until it completes or the token is cancelled,
+    /// whichever happens first.
+    ///
+    /// Returns `None` if the token was cancelled first.
+    pub async fn run_until_cancelled<F: Future>(
+        &self,
+        future: F,
+    ) -> Option<F::Output> {
+        let mut future = core::pin::pin!(future);
+        let mut cancelled = self.cancelled();
+
+        core::future::poll_fn(|cx| {
+            if Pin::new(&mut cancelled).poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            future.as_mut().poll(cx).map(Some)
+        })
+        .await
+    }
+}
+
+impl Inner {
+    fn next_key(&mut self) -> u64 {
+        let key = self.next_key;
+        self.next_key += 1;
+        key
+    }
+
+    fn remove_on_cancel(&mut self, CancelKey(key): CancelKey) -> bool {
+        let idx = self.on_cancel.iter().position(|(k, _)| *k == key);
+        idx.map(|idx| self.on_cancel.remove(idx)).is_some()
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Some(id) = self.autocmd_id.take() {
+            let _ = api::del_autocmd(id);
+        }
+
+        // The parent may be borrowed if it's dropping this token while
+        // being cancelled, in which case the callback has already been
+        // taken out of it.
+        if let Some((parent, key)) = self.parent.take() {
+            if let Some(parent) = Weak::upgrade(&parent) {
+                if let Ok(mut parent) = parent.try_borrow_mut() {
+                    parent.remove_on_cancel(key);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        let inner = &mut *this.token.inner.borrow_mut();
+
+        if inner.is_cancelled {
+            return Poll::Ready(());
+        }
+
+        // Each future keeps at most one waker registered, replacing it if the
+        // future has been moved to a different task.
+        let registered = this
+            .key
+            .and_then(|key| inner.wakers.iter_mut().find(|(k, _)| *k == key));
+
+        match registered {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => {
+                let key = inner.next_key();
+                inner.wakers.push((key, cx.waker().clone()));
+                this.key = Some(key);
+            },
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let inner = &mut *self.token.inner.borrow_mut();
+            inner.wakers.retain(|(k, _)| *k != key);
+        }
+    }
+}
+
+impl<T: 'static> Cancellable for JoinHandle<T> {
+    fn cancel(self) {
+        self.abort();
+    }
+}
+
+impl Cancellable for Child {
+    /// Sends `SIGTERM` to the process if it's still running.
+    fn cancel(mut self) {
+        let _ = self.kill(libc::SIGTERM);
+    }
+}
+
+macro_rules! impl_cancellable_for_handle {
+    ($($handle:ty),*) => {
+        $(
+            impl Cancellable for $handle {
+                fn cancel(self) {
+                    self.close(|| {});
+                }
+            }
+        )*
+    };
+}
+
+impl_cancellable_for_handle!(
+    CheckHandle,
+    FsEventHandle,
+    FsPollHandle,
+    IdleHandle,
+    PrepareHandle,
+    SignalHandle,
+    TimerHandle
+);
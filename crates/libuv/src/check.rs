@@ -0,0 +1,93 @@
+use core::mem::ManuallyDrop;
+use std::error::Error as StdError;
+
+use crate::callback::{self, HandleKind};
+use crate::handle::impl_lifecycle;
+use crate::{Error, Handle, IntoResult, ffi};
+
+pub(crate) type Callback = Box<
+    dyn FnMut(&mut CheckHandle) -> Result<(), Box<dyn StdError>> + 'static,
+>;
+
+/// Binding to libuv's [Check handle][1] used to run a callback once per
+/// event loop iteration, right after polling for I/O.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/check.html
+pub struct CheckHandle {
+    handle: Handle<ffi::uv_check_t, Callback>,
+}
+
+impl_lifecycle!(CheckHandle, handle);
+
+impl CheckHandle {
+    fn new() -> Result<Self, Error> {
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_check_init(uv_loop, handle.as_mut_ptr())
+        })?;
+
+        Ok(Self { handle })
+    }
+
+    /// Starts the handle, executing the callback once per event loop
+    /// iteration until the handle is stopped.
+    pub fn start<Cb, R>(mut callback: Cb) -> Result<Self, Error>
+    where
+        Cb: FnMut(&mut Self) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        let mut check = Self::new()?;
+
+        let callback: Callback = Box::new(move |check| {
+            // Type erase the callback by boxing its error.
+            callback(check)
+                .into_result()
+                .map_err(|err| Box::new(err) as Box<dyn StdError>)
+        });
+
+        unsafe { check.handle.set_data(callback) };
+
+        let retv = unsafe {
+            ffi::uv_check_start(check.handle.as_mut_ptr(), Some(check_cb as _))
+        };
+
+        if retv < 0 {
+            return Err(Error::CheckStart);
+        }
+
+        Ok(check)
+    }
+
+    /// Stops the handle. The callback will not be called anymore.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let retv = unsafe { ffi::uv_check_stop(self.handle.as_mut_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::CheckStop);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CheckHandle {
+    fn drop(&mut self) {
+        unsafe { self.handle.drop_owned() };
+    }
+}
+
+extern "C" fn check_cb(ptr: *mut ffi::uv_check_t) {
+    let handle: Handle<_, Callback> = unsafe { Handle::from_raw(ptr) };
+
+    let callback = unsafe { handle.get_data() };
+
+    if !callback.is_null() {
+        // The handle is owned by someone else, so we can't drop it.
+        let mut handle = ManuallyDrop::new(CheckHandle { handle });
+        let callback = unsafe { &mut *callback };
+
+        callback::call(HandleKind::Check, || callback(&mut handle));
+
+        unsafe { handle.handle.close_if_detached() };
+    }
+}
@@ -0,0 +1,183 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::thread::{self, ThreadId};
+
+use crate::callback::{self, HandleKind};
+use crate::{AsyncHandle, Error, oneshot};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+static MAIN_THREAD: OnceLock<MainThread> = OnceLock::new();
+
+/// Same as [`MainThread::dispatch`], using the [`MainThread`] returned by
+/// [`MainThread::get`].
+pub fn dispatch<F, T>(fun: F) -> Dispatch<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    MainThread::get().dispatch(fun)
+}
+
+/// Same as [`MainThread::dispatch_sync`], using the [`MainThread`] returned
+/// by [`MainThread::get`].
+pub fn dispatch_sync<F, T>(fun: F) -> Result<T, Error>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    MainThread::get().dispatch_sync(fun)
+}
+
+/// A handle to the Neovim thread which can be used to execute arbitrary
+/// closures on it from any other thread, and to get their results back.
+///
+/// Unlike an [`AsyncHandle`], which is tied to a single callback, a
+/// `MainThread` queues every closure it's given and executes all of them in
+/// order the next time the Neovim event loop wakes up.
+#[derive(Clone)]
+pub struct MainThread {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    jobs: Mutex<VecDeque<Job>>,
+    handle: AsyncHandle,
+    thread: ThreadId,
+}
+
+/// The future returned by [`MainThread::dispatch`].
+///
+/// It resolves to the return value of the dispatched closure once it's been
+/// executed on the main thread.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Dispatch<T> {
+    receiver: oneshot::Receiver<T>,
+}
+
+impl MainThread {
+    /// Creates the global `MainThread`. Called when the crate is initialized.
+    pub(crate) fn init() -> Result<(), Error> {
+        if MAIN_THREAD.get().is_none() {
+            let _ = MAIN_THREAD.set(Self::new()?);
+        }
+        Ok(())
+    }
+
+    fn new() -> Result<Self, Error> {
+        let handle = AsyncHandle::new(|| {
+            // libuv coalesces multiple calls to `uv_async_send` into a single
+            // execution of this callback, so we drain the whole queue
+            // instead of executing a single job.
+            //
+            // The lock is released before executing the jobs so that they
+            // can dispatch new jobs without deadlocking.
+            let jobs = core::mem::take(
+                &mut *MainThread::get().inner.jobs.lock().unwrap(),
+            );
+
+            for job in jobs {
+                callback::call(HandleKind::Async, || {
+                    job();
+                    Ok(())
+                });
+            }
+        })?;
+
+        let inner = Inner {
+            jobs: Mutex::default(),
+            handle,
+            thread: thread::current().id(),
+        };
+
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Returns a handle to the Neovim thread.
+    ///
+    /// NOTE: this will panic if the crate has not been initialized by calling
+    /// [`init`](crate::init).
+    pub fn get() -> Self {
+        MAIN_THREAD
+            .get()
+            .expect("nvim-oxi-libuv has not been initialized")
+            .clone()
+    }
+
+    /// Returns whether the current thread is the Neovim thread.
+    pub fn is_current(&self) -> bool {
+        thread::current().id() == self.inner.thread
+    }
+
+    /// Queues a closure to be executed on the main thread, returning a future
+    /// that resolves to its return value.
+    ///
+    /// The returned future is `Send` and can be awaited in any async runtime.
+    pub fn dispatch<F, T>(&self, fun: F) -> Dispatch<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        let receiver = match self.push(Box::new(move || sender.send(fun()))) {
+            Ok(()) => receiver,
+            Err(err) => oneshot::failed(err),
+        };
+
+        Dispatch { receiver }
+    }
+
+    /// Executes a closure on the main thread, blocking the current thread
+    /// until the closure has returned.
+    ///
+    /// If called from the main thread the closure is executed immediately,
+    /// since waiting for the event loop would cause a deadlock.
+    ///
+    /// If the closure couldn't be queued an error is returned and the closure
+    /// is never executed.
+    pub fn dispatch_sync<F, T>(&self, fun: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        if self.is_current() {
+            return Ok(fun());
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        self.push(Box::new(move || {
+            let _ = sender.send(fun());
+        }))?;
+
+        receiver.recv().map_err(|_| Error::Canceled)
+    }
+
+    /// Queues a job and wakes up the event loop. If that fails the job is
+    /// removed from the queue, so it's guaranteed to never be executed.
+    fn push(&self, job: Job) -> Result<(), Error> {
+        // The lock is held until the handle has been woken up, so the job
+        // can't be drained by the callback before we know whether to keep it.
+        let jobs = &mut *self.inner.jobs.lock().unwrap();
+        jobs.push_back(job);
+
+        self.inner.handle.send().inspect_err(|_| {
+            jobs.pop_back();
+        })
+    }
+}
+
+impl<T> Future for Dispatch<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver).poll(cx)
+    }
+}
@@ -1,5 +1,10 @@
+use core::ffi::{CStr, c_int};
+use core::fmt;
+
 use thiserror::Error as ThisError;
 
+use crate::ffi;
+
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Clone, Debug, Eq, PartialEq, ThisError)]
@@ -7,15 +12,165 @@ pub enum Error {
     #[error("Couldn't trigger async handle")]
     AsyncTrigger,
 
+    #[error("Couldn't start check handle")]
+    CheckStart,
+
+    #[error("Couldn't stop check handle")]
+    CheckStop,
+
+    #[error("The operation was canceled before completing")]
+    Canceled,
+
+    #[error("File system operation failed: {0}")]
+    Fs(ErrorCode),
+
+    #[error("Couldn't watch file: {0}")]
+    FsEvent(ErrorCode),
+
+    #[error("Couldn't start fs event handle: {0}")]
+    FsEventStart(ErrorCode),
+
+    #[error("Couldn't stop fs event handle")]
+    FsEventStop,
+
+    #[error("Couldn't stat file: {0}")]
+    FsPoll(ErrorCode),
+
+    #[error("Couldn't start fs poll handle: {0}")]
+    FsPollStart(ErrorCode),
+
+    #[error("Couldn't stop fs poll handle")]
+    FsPollStop,
+
+    #[error("The handle has been closed")]
+    HandleClosed,
+
     #[error("Couldn't initialize handle")]
     HandleInit,
 
     #[error("Couldn't allocate memory for a new handle")]
     HandleMemAlloc,
 
+    #[error("Couldn't start idle handle")]
+    IdleStart,
+
+    #[error("Couldn't stop idle handle")]
+    IdleStop,
+
+    #[error("Couldn't start prepare handle")]
+    PrepareStart,
+
+    #[error("Couldn't stop prepare handle")]
+    PrepareStop,
+
+    #[error("Couldn't send signal to process: {0}")]
+    ProcessKill(ErrorCode),
+
+    #[error("Couldn't spawn process: {0}")]
+    ProcessSpawn(ErrorCode),
+
+    #[error("Couldn't queue work on the thread pool: {0}")]
+    QueueWork(ErrorCode),
+
+    #[error("Couldn't start signal handle: {0}")]
+    SignalStart(ErrorCode),
+
+    #[error("Couldn't stop signal handle")]
+    SignalStop,
+
+    #[error("Couldn't get socket address: {0}")]
+    SocketAddr(ErrorCode),
+
+    #[error("Couldn't set socket option: {0}")]
+    SocketOption(ErrorCode),
+
+    #[error("Couldn't accept incoming connection: {0}")]
+    StreamAccept(ErrorCode),
+
+    #[error("Couldn't bind stream: {0}")]
+    StreamBind(ErrorCode),
+
+    #[error("Couldn't connect stream: {0}")]
+    StreamConnect(ErrorCode),
+
+    #[error("Couldn't listen for incoming connections: {0}")]
+    StreamListen(ErrorCode),
+
+    #[error("Couldn't read from stream: {0}")]
+    StreamRead(ErrorCode),
+
+    #[error("Couldn't shut down stream: {0}")]
+    StreamShutdown(ErrorCode),
+
+    #[error("Couldn't write to stream: {0}")]
+    StreamWrite(ErrorCode),
+
+    #[error("Couldn't restart timer handle")]
+    TimerAgain,
+
     #[error("Couldn't start timer handle")]
     TimerStart,
 
     #[error("Couldn't stop timer handle")]
     TimerStop,
+
+    #[error("The future didn't complete before the timeout elapsed")]
+    Timeout,
+
+    #[error("The work queued on the thread pool panicked: {0}")]
+    WorkPanicked(String),
+}
+
+/// An error code returned by a libuv function.
+///
+/// On Unix systems these are the negated values of the corresponding `errno`
+/// constants.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ErrorCode(c_int);
+
+impl ErrorCode {
+    pub(crate) fn new(code: c_int) -> Self {
+        debug_assert!(code < 0, "libuv error codes are negative");
+        Self(code)
+    }
+
+    /// Returns the numeric value of the error code.
+    pub fn code(&self) -> i32 {
+        self.0
+    }
+
+    /// Returns the name of the error code, e.g. `"ENOENT"`.
+    pub fn name(&self) -> &'static str {
+        unsafe { CStr::from_ptr(ffi::uv_err_name(self.0)) }
+            .to_str()
+            .unwrap_or("UNKNOWN")
+    }
+
+    /// Returns a human-readable description of the error code, e.g. `"no
+    /// such file or directory"`.
+    pub fn message(&self) -> &'static str {
+        unsafe { CStr::from_ptr(ffi::uv_strerror(self.0)) }
+            .to_str()
+            .unwrap_or("unknown error")
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message(), self.name())
+    }
+}
+
+impl From<ErrorCode> for std::io::Error {
+    fn from(code: ErrorCode) -> Self {
+        #[cfg(unix)]
+        {
+            Self::from_raw_os_error(-code.0)
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self::other(code.to_string())
+        }
+    }
 }
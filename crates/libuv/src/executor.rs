@@ -0,0 +1,312 @@
+use core::cell::{Cell, OnceCell, RefCell};
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::Wake;
+use std::thread;
+
+use crate::AsyncHandle;
+use crate::callback::{self, CallbackError, HandleKind};
+
+type LocalFuture = Pin<Box<dyn Future<Output = ()> + 'static>>;
+
+thread_local! {
+    static EXECUTOR: OnceCell<Executor> = const { OnceCell::new() };
+}
+
+/// Spawns a new task on the Neovim event loop, returning a [`JoinHandle`]
+/// that can be `.await`ed to get its output.
+///
+/// The task is polled on the main thread every time it's woken up, which
+/// means the future doesn't have to be `Send` and it can freely call any
+/// function in the [`api`] module.
+///
+/// The task will keep running in the background even if the returned
+/// [`JoinHandle`] is dropped. Use [`JoinHandle::abort`] to cancel it.
+///
+/// If the task panics, the panic is resumed when the [`JoinHandle`] is
+/// polled. If the [`JoinHandle`] has been dropped, the panic is reported to
+/// the handler set with [`set_error_handler`](crate::set_error_handler).
+///
+/// NOTE: this function **must** be called from the main thread.
+///
+/// [`api`]: https://docs.rs/nvim-oxi/latest/nvim_oxi/api/index.html
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let state = Rc::new(RefCell::new(JoinState::default()));
+
+    let future = {
+        let state = state.clone();
+        async move {
+            let output = CatchUnwind { future }.await;
+
+            match output {
+                // Nobody's going to await the panic.
+                Err(payload) if Rc::strong_count(&state) == 1 => {
+                    callback::report(CallbackError::Panic {
+                        handle: HandleKind::Task,
+                        message: callback::panic_message(&*payload),
+                    })
+                },
+                output => state.borrow_mut().complete(output),
+            }
+        }
+    };
+
+    let id = Executor::with(|executor| executor.spawn(Box::pin(future)));
+
+    JoinHandle { id, state }
+}
+
+/// An owned permission to await the output of a task spawned via [`spawn`].
+///
+/// Dropping a `JoinHandle` detaches the task, which will keep running in the
+/// background.
+pub struct JoinHandle<T> {
+    id: TaskId,
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Cancels the task, dropping its future without polling it again.
+    pub fn abort(self) {
+        Executor::with(|executor| executor.abort(self.id));
+    }
+
+    /// Returns whether the task has completed.
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().is_finished
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("id", &self.id.0)
+            .field("is_finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let state = &mut *self.state.borrow_mut();
+
+        match state.output.take() {
+            Some(Ok(output)) => Poll::Ready(output),
+
+            Some(Err(payload)) => panic::resume_unwind(payload),
+
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+struct JoinState<T> {
+    output: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+    is_finished: bool,
+}
+
+impl<T> Default for JoinState<T> {
+    fn default() -> Self {
+        Self { output: None, waker: None, is_finished: false }
+    }
+}
+
+impl<T> JoinState<T> {
+    fn complete(&mut self, output: thread::Result<T>) {
+        self.output = Some(output);
+        self.is_finished = true;
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A future that catches the panics raised while polling the inner future.
+struct CatchUnwind<F> {
+    future: F,
+}
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = thread::Result<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the inner future is never moved out of the pinned wrapper.
+        let future =
+            unsafe { self.map_unchecked_mut(|this| &mut this.future) };
+
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct TaskId(u64);
+
+struct Task {
+    future: LocalFuture,
+    waker: Arc<TaskWaker>,
+}
+
+/// The executor driving all the tasks spawned on the main thread.
+///
+/// Tasks are woken up by pushing their id on a thread-safe queue and then
+/// triggering an [`AsyncHandle`] whose callback polls every task in the queue.
+struct Executor {
+    /// The tasks that are currently not being polled.
+    tasks: RefCell<HashMap<TaskId, Task>>,
+
+    /// The id that'll be assigned to the next spawned task.
+    next_id: Cell<u64>,
+
+    /// The id of the task that's currently being polled, if any.
+    current: Cell<Option<TaskId>>,
+
+    /// Whether the task that's currently being polled has been aborted from
+    /// within its own `poll`.
+    abort_current: Cell<bool>,
+
+    queue: Arc<Queue>,
+}
+
+/// The queue of tasks that are ready to be polled.
+struct Queue {
+    ready: Mutex<VecDeque<TaskId>>,
+    handle: AsyncHandle,
+}
+
+struct TaskWaker {
+    id: TaskId,
+
+    /// Whether the task is already in the ready queue. Used to avoid polling
+    /// a task multiple times if it's woken up more than once before the
+    /// executor gets to it.
+    is_queued: AtomicBool,
+
+    queue: Arc<Queue>,
+}
+
+impl Executor {
+    fn new() -> Self {
+        let handle = AsyncHandle::new(|| Self::with(Self::run))
+            .expect("couldn't initialize the executor's async handle");
+
+        Self {
+            tasks: RefCell::default(),
+            next_id: Cell::new(0),
+            current: Cell::new(None),
+            abort_current: Cell::new(false),
+            queue: Arc::new(Queue { ready: Mutex::default(), handle }),
+        }
+    }
+
+    fn with<F, R>(fun: F) -> R
+    where
+        F: FnOnce(&Self) -> R,
+    {
+        EXECUTOR.with(|executor| fun(executor.get_or_init(Self::new)))
+    }
+
+    fn spawn(&self, future: LocalFuture) -> TaskId {
+        let id = TaskId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+
+        let waker = Arc::new(TaskWaker {
+            id,
+            is_queued: AtomicBool::new(false),
+            queue: self.queue.clone(),
+        });
+
+        self.tasks
+            .borrow_mut()
+            .insert(id, Task { future, waker: waker.clone() });
+
+        // Schedule the first poll.
+        waker.wake();
+
+        id
+    }
+
+    fn abort(&self, id: TaskId) {
+        let task = self.tasks.borrow_mut().remove(&id);
+
+        if task.is_none() && self.current.get() == Some(id) {
+            self.abort_current.set(true);
+        }
+
+        drop(task);
+    }
+
+    /// Polls all the tasks that are currently in the ready queue.
+    fn run(&self) {
+        let ready = core::mem::take(&mut *self.queue.ready.lock().unwrap());
+
+        for id in ready {
+            self.poll(id);
+        }
+    }
+
+    fn poll(&self, id: TaskId) {
+        // The task is removed from the map while it's being polled so that
+        // its future can spawn or abort other tasks.
+        let Some(mut task) = self.tasks.borrow_mut().remove(&id) else {
+            return;
+        };
+
+        task.waker.is_queued.store(false, Ordering::Release);
+
+        let waker = Waker::from(task.waker.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        self.current.set(Some(id));
+        let poll = task.future.as_mut().poll(&mut cx);
+        self.current.set(None);
+
+        if self.abort_current.replace(false) || poll.is_ready() {
+            return;
+        }
+
+        self.tasks.borrow_mut().insert(id, task);
+    }
+}
+
+impl Queue {
+    fn push(&self, id: TaskId) {
+        self.ready.lock().unwrap().push_back(id);
+
+        // libuv coalesces multiple calls to `uv_async_send`, but that's ok
+        // since every callback invocation drains the whole queue.
+        let _ = self.handle.send();
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.is_queued.swap(true, Ordering::AcqRel) {
+            self.queue.push(self.id);
+        }
+    }
+}
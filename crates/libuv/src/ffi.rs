@@ -8,8 +8,11 @@
 #![allow(non_snake_case)]
 #![allow(non_camel_case_types)]
 
-use core::ffi::{c_int, c_uint, c_void};
+use core::ffi::{c_char, c_int, c_long, c_uint, c_void};
 use core::marker::{PhantomData, PhantomPinned};
+use std::alloc::Layout;
+
+use crate::handle::{handle_layout, req_layout};
 
 pub(crate) type uv_timer_cb =
     Option<unsafe extern "C" fn(handle: *mut uv_timer_t)>;
@@ -17,6 +20,154 @@ pub(crate) type uv_timer_cb =
 pub(crate) type uv_async_cb =
     Option<unsafe extern "C" fn(handle: *mut uv_async_t)>;
 
+pub(crate) type uv_idle_cb =
+    Option<unsafe extern "C" fn(handle: *mut uv_idle_t)>;
+
+pub(crate) type uv_prepare_cb =
+    Option<unsafe extern "C" fn(handle: *mut uv_prepare_t)>;
+
+pub(crate) type uv_check_cb =
+    Option<unsafe extern "C" fn(handle: *mut uv_check_t)>;
+
+pub(crate) type uv_signal_cb =
+    Option<unsafe extern "C" fn(handle: *mut uv_signal_t, signum: c_int)>;
+
+pub(crate) type uv_close_cb =
+    Option<unsafe extern "C" fn(handle: *mut uv_handle_t)>;
+
+pub(crate) type uv_alloc_cb = Option<
+    unsafe extern "C" fn(
+        handle: *mut uv_handle_t,
+        suggested_size: usize,
+        buf: *mut uv_buf_t,
+    ),
+>;
+
+pub(crate) type uv_read_cb = Option<
+    unsafe extern "C" fn(
+        stream: *mut uv_stream_t,
+        nread: isize,
+        buf: *const uv_buf_t,
+    ),
+>;
+
+pub(crate) type uv_write_cb =
+    Option<unsafe extern "C" fn(req: *mut uv_write_t, status: c_int)>;
+
+pub(crate) type uv_shutdown_cb =
+    Option<unsafe extern "C" fn(req: *mut uv_shutdown_t, status: c_int)>;
+
+pub(crate) type uv_connect_cb =
+    Option<unsafe extern "C" fn(req: *mut uv_connect_t, status: c_int)>;
+
+pub(crate) type uv_connection_cb =
+    Option<unsafe extern "C" fn(server: *mut uv_stream_t, status: c_int)>;
+
+pub(crate) type uv_fs_event_cb = Option<
+    unsafe extern "C" fn(
+        handle: *mut uv_fs_event_t,
+        filename: *const c_char,
+        events: c_int,
+        status: c_int,
+    ),
+>;
+
+pub(crate) type uv_fs_poll_cb = Option<
+    unsafe extern "C" fn(
+        handle: *mut uv_fs_poll_t,
+        status: c_int,
+        prev: *const uv_stat_t,
+        curr: *const uv_stat_t,
+    ),
+>;
+
+pub(crate) type uv_work_cb = Option<unsafe extern "C" fn(req: *mut uv_work_t)>;
+
+pub(crate) type uv_after_work_cb =
+    Option<unsafe extern "C" fn(req: *mut uv_work_t, status: c_int)>;
+
+pub(crate) type uv_fs_cb = Option<unsafe extern "C" fn(req: *mut uv_fs_t)>;
+
+pub(crate) type uv_exit_cb = Option<
+    unsafe extern "C" fn(
+        process: *mut uv_process_t,
+        exit_status: i64,
+        term_signal: c_int,
+    ),
+>;
+
+pub(crate) type uv_handle_type = c_uint;
+
+pub(crate) const UV_CHECK: uv_handle_type = 2;
+pub(crate) const UV_FS_EVENT: uv_handle_type = 3;
+pub(crate) const UV_FS_POLL: uv_handle_type = 4;
+pub(crate) const UV_IDLE: uv_handle_type = 6;
+pub(crate) const UV_NAMED_PIPE: uv_handle_type = 7;
+pub(crate) const UV_PREPARE: uv_handle_type = 9;
+pub(crate) const UV_PROCESS: uv_handle_type = 10;
+pub(crate) const UV_TCP: uv_handle_type = 12;
+pub(crate) const UV_SIGNAL: uv_handle_type = 16;
+
+pub(crate) type uv_req_type = c_uint;
+
+pub(crate) const UV_CONNECT: uv_req_type = 2;
+pub(crate) const UV_WRITE: uv_req_type = 3;
+pub(crate) const UV_SHUTDOWN: uv_req_type = 4;
+pub(crate) const UV_FS: uv_req_type = 6;
+pub(crate) const UV_WORK: uv_req_type = 7;
+
+pub(crate) const UV_EOF: c_int = -4095;
+pub(crate) const UV_EINVAL: c_int = -22;
+pub(crate) const UV_ESRCH: c_int = -3;
+
+pub(crate) const UV_RENAME: c_int = 1;
+pub(crate) const UV_CHANGE: c_int = 2;
+
+pub(crate) const UV_FS_EVENT_WATCH_ENTRY: c_uint = 1;
+pub(crate) const UV_FS_EVENT_STAT: c_uint = 2;
+pub(crate) const UV_FS_EVENT_RECURSIVE: c_uint = 4;
+
+pub(crate) type uv_file = c_int;
+
+pub(crate) type uv_dirent_type_t = c_uint;
+
+pub(crate) const UV_DIRENT_FILE: uv_dirent_type_t = 1;
+pub(crate) const UV_DIRENT_DIR: uv_dirent_type_t = 2;
+pub(crate) const UV_DIRENT_LINK: uv_dirent_type_t = 3;
+pub(crate) const UV_DIRENT_FIFO: uv_dirent_type_t = 4;
+pub(crate) const UV_DIRENT_SOCKET: uv_dirent_type_t = 5;
+pub(crate) const UV_DIRENT_CHAR: uv_dirent_type_t = 6;
+pub(crate) const UV_DIRENT_BLOCK: uv_dirent_type_t = 7;
+
+pub(crate) const UV_FS_O_RDONLY: c_int = 0;
+pub(crate) const UV_FS_O_WRONLY: c_int = 1;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) const UV_FS_O_CREAT: c_int = 0o100;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) const UV_FS_O_TRUNC: c_int = 0o1000;
+
+#[cfg(windows)]
+pub(crate) const UV_FS_O_CREAT: c_int = 0x0100;
+#[cfg(windows)]
+pub(crate) const UV_FS_O_TRUNC: c_int = 0x0200;
+
+// macOS and the BSDs.
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+pub(crate) const UV_FS_O_CREAT: c_int = 0x0200;
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+pub(crate) const UV_FS_O_TRUNC: c_int = 0x0400;
+
+pub(crate) type uv_stdio_flags = c_uint;
+
+pub(crate) const UV_IGNORE: uv_stdio_flags = 0x00;
+pub(crate) const UV_CREATE_PIPE: uv_stdio_flags = 0x01;
+pub(crate) const UV_INHERIT_FD: uv_stdio_flags = 0x02;
+pub(crate) const UV_READABLE_PIPE: uv_stdio_flags = 0x10;
+pub(crate) const UV_WRITABLE_PIPE: uv_stdio_flags = 0x20;
+
+pub(crate) const UV_PROCESS_DETACHED: c_uint = 1 << 3;
+
 #[repr(C)]
 struct handle {
     data: [u8; 0],
@@ -39,6 +190,215 @@ pub(crate) struct uv_timer_t(uv_timer_s);
 
 impl crate::ProperLayout for uv_timer_t {}
 
+#[repr(C)]
+pub(crate) struct uv_idle_t(handle);
+
+impl crate::ProperLayout for uv_idle_t {
+    fn layout() -> Layout {
+        handle_layout(UV_IDLE)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_prepare_t(handle);
+
+impl crate::ProperLayout for uv_prepare_t {
+    fn layout() -> Layout {
+        handle_layout(UV_PREPARE)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_check_t(handle);
+
+impl crate::ProperLayout for uv_check_t {
+    fn layout() -> Layout {
+        handle_layout(UV_CHECK)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_signal_t(handle);
+
+impl crate::ProperLayout for uv_signal_t {
+    fn layout() -> Layout {
+        handle_layout(UV_SIGNAL)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_req_t(handle);
+
+#[repr(C)]
+pub(crate) struct uv_stream_t(handle);
+
+#[repr(C)]
+pub(crate) struct uv_pipe_t(handle);
+
+impl crate::ProperLayout for uv_pipe_t {
+    fn layout() -> Layout {
+        handle_layout(UV_NAMED_PIPE)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_fs_event_t(handle);
+
+impl crate::ProperLayout for uv_fs_event_t {
+    fn layout() -> Layout {
+        handle_layout(UV_FS_EVENT)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_fs_poll_t(handle);
+
+impl crate::ProperLayout for uv_fs_poll_t {
+    fn layout() -> Layout {
+        handle_layout(UV_FS_POLL)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_tcp_t(handle);
+
+impl crate::ProperLayout for uv_tcp_t {
+    fn layout() -> Layout {
+        handle_layout(UV_TCP)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_process_t(handle);
+
+impl crate::ProperLayout for uv_process_t {
+    fn layout() -> Layout {
+        handle_layout(UV_PROCESS)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_connect_t(handle);
+
+impl crate::ProperLayout for uv_connect_t {
+    fn layout() -> Layout {
+        req_layout(UV_CONNECT)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_write_t(handle);
+
+impl crate::ProperLayout for uv_write_t {
+    fn layout() -> Layout {
+        req_layout(UV_WRITE)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_shutdown_t(handle);
+
+impl crate::ProperLayout for uv_shutdown_t {
+    fn layout() -> Layout {
+        req_layout(UV_SHUTDOWN)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_work_t(handle);
+
+impl crate::ProperLayout for uv_work_t {
+    fn layout() -> Layout {
+        req_layout(UV_WORK)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_fs_t(handle);
+
+impl crate::ProperLayout for uv_fs_t {
+    fn layout() -> Layout {
+        req_layout(UV_FS)
+    }
+}
+
+#[repr(C)]
+pub(crate) struct uv_dirent_t {
+    pub(crate) name: *const c_char,
+    pub(crate) r#type: uv_dirent_type_t,
+}
+
+#[repr(C)]
+pub(crate) struct sockaddr(handle);
+
+/// Large enough to hold any socket address, like `struct sockaddr_storage`.
+#[repr(C, align(8))]
+pub(crate) struct sockaddr_storage(pub(crate) [u8; 128]);
+
+#[repr(C)]
+pub(crate) struct uv_buf_t {
+    pub(crate) base: *mut c_char,
+    pub(crate) len: usize,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct uv_timespec_t {
+    pub(crate) tv_sec: c_long,
+    pub(crate) tv_nsec: c_long,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct uv_stat_t {
+    pub(crate) st_dev: u64,
+    pub(crate) st_mode: u64,
+    pub(crate) st_nlink: u64,
+    pub(crate) st_uid: u64,
+    pub(crate) st_gid: u64,
+    pub(crate) st_rdev: u64,
+    pub(crate) st_ino: u64,
+    pub(crate) st_size: u64,
+    pub(crate) st_blksize: u64,
+    pub(crate) st_blocks: u64,
+    pub(crate) st_flags: u64,
+    pub(crate) st_gen: u64,
+    pub(crate) st_atim: uv_timespec_t,
+    pub(crate) st_mtim: uv_timespec_t,
+    pub(crate) st_ctim: uv_timespec_t,
+    pub(crate) st_birthtim: uv_timespec_t,
+}
+
+#[repr(C)]
+pub(crate) struct uv_process_options_t {
+    pub(crate) exit_cb: uv_exit_cb,
+    pub(crate) file: *const c_char,
+    pub(crate) args: *mut *mut c_char,
+    pub(crate) env: *mut *mut c_char,
+    pub(crate) cwd: *const c_char,
+    pub(crate) flags: c_uint,
+    pub(crate) stdio_count: c_int,
+    pub(crate) stdio: *mut uv_stdio_container_t,
+    pub(crate) uid: uv_uid_t,
+    pub(crate) gid: uv_gid_t,
+}
+
+#[repr(C)]
+pub(crate) struct uv_stdio_container_t {
+    pub(crate) flags: uv_stdio_flags,
+    pub(crate) data: uv_stdio_container_data,
+}
+
+#[repr(C)]
+pub(crate) union uv_stdio_container_data {
+    pub(crate) stream: *mut uv_stream_t,
+    pub(crate) fd: c_int,
+}
+
+type uv_uid_t = u32;
+
+type uv_gid_t = u32;
+
 unsafe extern "C" {
     // https://github.com/luvit/luv/blob/master/src/luv.c#L751
     pub(crate) fn luv_loop(
@@ -51,8 +411,164 @@ unsafe extern "C" {
         cb: uv_async_cb,
     ) -> c_int;
 
+    pub(crate) fn uv_accept(
+        server: *mut uv_stream_t,
+        client: *mut uv_stream_t,
+    ) -> c_int;
+
     pub(crate) fn uv_async_send(async_: *mut uv_async_t) -> c_int;
 
+    pub(crate) fn uv_check_init(
+        loop_: *mut uv_loop_t,
+        check: *mut uv_check_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_check_start(
+        check: *mut uv_check_t,
+        cb: uv_check_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_check_stop(check: *mut uv_check_t) -> c_int;
+
+    pub(crate) fn uv_close(handle: *mut uv_handle_t, close_cb: uv_close_cb);
+
+    pub(crate) fn uv_err_name(err: c_int) -> *const c_char;
+
+    pub(crate) fn uv_fs_close(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        file: uv_file,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_event_getpath(
+        handle: *mut uv_fs_event_t,
+        buffer: *mut c_char,
+        size: *mut usize,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_event_init(
+        loop_: *mut uv_loop_t,
+        handle: *mut uv_fs_event_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_event_start(
+        handle: *mut uv_fs_event_t,
+        cb: uv_fs_event_cb,
+        path: *const c_char,
+        flags: c_uint,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_event_stop(handle: *mut uv_fs_event_t) -> c_int;
+
+    pub(crate) fn uv_fs_fstat(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        file: uv_file,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_get_ptr(req: *const uv_fs_t) -> *mut c_void;
+
+    pub(crate) fn uv_fs_get_result(req: *const uv_fs_t) -> isize;
+
+    pub(crate) fn uv_fs_get_statbuf(req: *mut uv_fs_t) -> *mut uv_stat_t;
+
+    pub(crate) fn uv_fs_mkdir(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        mode: c_int,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_open(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        flags: c_int,
+        mode: c_int,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_poll_init(
+        loop_: *mut uv_loop_t,
+        handle: *mut uv_fs_poll_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_poll_start(
+        handle: *mut uv_fs_poll_t,
+        poll_cb: uv_fs_poll_cb,
+        path: *const c_char,
+        interval: c_uint,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_poll_stop(handle: *mut uv_fs_poll_t) -> c_int;
+
+    pub(crate) fn uv_fs_read(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        file: uv_file,
+        bufs: *const uv_buf_t,
+        nbufs: c_uint,
+        offset: i64,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_realpath(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_rename(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        new_path: *const c_char,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_req_cleanup(req: *mut uv_fs_t);
+
+    pub(crate) fn uv_fs_scandir(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        flags: c_int,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_scandir_next(
+        req: *mut uv_fs_t,
+        ent: *mut uv_dirent_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_stat(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_unlink(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        path: *const c_char,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_fs_write(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_fs_t,
+        file: uv_file,
+        bufs: *const uv_buf_t,
+        nbufs: c_uint,
+        offset: i64,
+        cb: uv_fs_cb,
+    ) -> c_int;
+
     pub(crate) fn uv_handle_get_data(
         handle: *const uv_handle_t,
     ) -> *mut c_void;
@@ -62,6 +578,180 @@ unsafe extern "C" {
         data: *mut c_void,
     );
 
+    pub(crate) fn uv_has_ref(handle: *const uv_handle_t) -> c_int;
+
+    pub(crate) fn uv_handle_size(r#type: uv_handle_type) -> usize;
+
+    pub(crate) fn uv_is_active(handle: *const uv_handle_t) -> c_int;
+
+    pub(crate) fn uv_is_closing(handle: *const uv_handle_t) -> c_int;
+
+    pub(crate) fn uv_idle_init(
+        loop_: *mut uv_loop_t,
+        idle: *mut uv_idle_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_idle_start(idle: *mut uv_idle_t, cb: uv_idle_cb)
+    -> c_int;
+
+    pub(crate) fn uv_idle_stop(idle: *mut uv_idle_t) -> c_int;
+
+    pub(crate) fn uv_ip4_addr(
+        ip: *const c_char,
+        port: c_int,
+        addr: *mut sockaddr,
+    ) -> c_int;
+
+    pub(crate) fn uv_ip6_addr(
+        ip: *const c_char,
+        port: c_int,
+        addr: *mut sockaddr,
+    ) -> c_int;
+
+    pub(crate) fn uv_ip_name(
+        src: *const sockaddr,
+        dst: *mut c_char,
+        size: usize,
+    ) -> c_int;
+
+    pub(crate) fn uv_listen(
+        stream: *mut uv_stream_t,
+        backlog: c_int,
+        cb: uv_connection_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_pipe_bind(
+        handle: *mut uv_pipe_t,
+        name: *const c_char,
+    ) -> c_int;
+
+    pub(crate) fn uv_pipe_connect(
+        req: *mut uv_connect_t,
+        handle: *mut uv_pipe_t,
+        name: *const c_char,
+        cb: uv_connect_cb,
+    );
+
+    pub(crate) fn uv_pipe_init(
+        loop_: *mut uv_loop_t,
+        handle: *mut uv_pipe_t,
+        ipc: c_int,
+    ) -> c_int;
+
+    pub(crate) fn uv_prepare_init(
+        loop_: *mut uv_loop_t,
+        prepare: *mut uv_prepare_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_prepare_start(
+        prepare: *mut uv_prepare_t,
+        cb: uv_prepare_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_prepare_stop(prepare: *mut uv_prepare_t) -> c_int;
+
+    pub(crate) fn uv_process_get_pid(handle: *const uv_process_t) -> c_int;
+
+    pub(crate) fn uv_process_kill(
+        handle: *mut uv_process_t,
+        signum: c_int,
+    ) -> c_int;
+
+    pub(crate) fn uv_queue_work(
+        loop_: *mut uv_loop_t,
+        req: *mut uv_work_t,
+        work_cb: uv_work_cb,
+        after_work_cb: uv_after_work_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_read_start(
+        stream: *mut uv_stream_t,
+        alloc_cb: uv_alloc_cb,
+        read_cb: uv_read_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_read_stop(stream: *mut uv_stream_t) -> c_int;
+
+    pub(crate) fn uv_ref(handle: *mut uv_handle_t);
+
+    pub(crate) fn uv_req_get_data(req: *const uv_req_t) -> *mut c_void;
+
+    pub(crate) fn uv_req_set_data(req: *mut uv_req_t, data: *mut c_void);
+
+    pub(crate) fn uv_req_size(r#type: uv_req_type) -> usize;
+
+    pub(crate) fn uv_shutdown(
+        req: *mut uv_shutdown_t,
+        handle: *mut uv_stream_t,
+        cb: uv_shutdown_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_signal_init(
+        loop_: *mut uv_loop_t,
+        signal: *mut uv_signal_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_signal_start(
+        signal: *mut uv_signal_t,
+        cb: uv_signal_cb,
+        signum: c_int,
+    ) -> c_int;
+
+    pub(crate) fn uv_signal_start_oneshot(
+        signal: *mut uv_signal_t,
+        cb: uv_signal_cb,
+        signum: c_int,
+    ) -> c_int;
+
+    pub(crate) fn uv_signal_stop(signal: *mut uv_signal_t) -> c_int;
+
+    pub(crate) fn uv_spawn(
+        loop_: *mut uv_loop_t,
+        handle: *mut uv_process_t,
+        options: *const uv_process_options_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_stream_get_write_queue_size(
+        stream: *const uv_stream_t,
+    ) -> usize;
+
+    pub(crate) fn uv_strerror(err: c_int) -> *const c_char;
+
+    pub(crate) fn uv_tcp_bind(
+        handle: *mut uv_tcp_t,
+        addr: *const sockaddr,
+        flags: c_uint,
+    ) -> c_int;
+
+    pub(crate) fn uv_tcp_connect(
+        req: *mut uv_connect_t,
+        handle: *mut uv_tcp_t,
+        addr: *const sockaddr,
+        cb: uv_connect_cb,
+    ) -> c_int;
+
+    pub(crate) fn uv_tcp_getpeername(
+        handle: *const uv_tcp_t,
+        name: *mut sockaddr,
+        namelen: *mut c_int,
+    ) -> c_int;
+
+    pub(crate) fn uv_tcp_getsockname(
+        handle: *const uv_tcp_t,
+        name: *mut sockaddr,
+        namelen: *mut c_int,
+    ) -> c_int;
+
+    pub(crate) fn uv_tcp_init(
+        loop_: *mut uv_loop_t,
+        handle: *mut uv_tcp_t,
+    ) -> c_int;
+
+    pub(crate) fn uv_tcp_nodelay(
+        handle: *mut uv_tcp_t,
+        enable: c_int,
+    ) -> c_int;
+
     pub(crate) fn uv_timer_init(
         loop_: *mut uv_loop_t,
         handle: *mut uv_timer_t,
@@ -75,6 +765,24 @@ unsafe extern "C" {
     ) -> c_int;
 
     pub(crate) fn uv_timer_stop(handle: *mut uv_timer_t) -> c_int;
+
+    pub(crate) fn uv_timer_again(handle: *mut uv_timer_t) -> c_int;
+
+    pub(crate) fn uv_timer_set_repeat(handle: *mut uv_timer_t, repeat: u64);
+
+    pub(crate) fn uv_timer_get_repeat(handle: *const uv_timer_t) -> u64;
+
+    pub(crate) fn uv_timer_get_due_in(handle: *const uv_timer_t) -> u64;
+
+    pub(crate) fn uv_unref(handle: *mut uv_handle_t);
+
+    pub(crate) fn uv_write(
+        req: *mut uv_write_t,
+        handle: *mut uv_stream_t,
+        bufs: *const uv_buf_t,
+        nbufs: c_uint,
+        cb: uv_write_cb,
+    ) -> c_int;
 }
 
 #[repr(C)]
@@ -109,10 +817,6 @@ struct uv_timer_s {
     start_id: u64,
 }
 
-type uv_handle_type = c_uint;
-
-type uv_close_cb = Option<unsafe extern "C" fn(handle: *mut uv_handle_t)>;
-
 #[repr(C)]
 #[derive(Copy, Clone)]
 struct uv__queue {
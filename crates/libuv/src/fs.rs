@@ -0,0 +1,435 @@
+//! Asynchronous file system operations executed on libuv's thread pool.
+//!
+//! Unlike the functions in [`std::fs`], these don't block the Neovim thread
+//! while the operation is in progress, which makes them safe to use on slow
+//! or network file systems. The returned futures complete on the main thread,
+//! so they can be awaited from within a task spawned with
+//! [`spawn`](crate::spawn).
+//!
+//! NOTE: all the functions in this module **must** be called from the main
+//! thread.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use nvim_oxi::libuv::fs;
+//!
+//! nvim_oxi::spawn(async {
+//!     let contents = fs::read("Cargo.toml").await?;
+//!     let entries = fs::scandir(".").await?;
+//!     # Ok::<_, nvim_oxi::libuv::Error>(())
+//! });
+//! ```
+
+use core::ffi::{CStr, c_char, c_int};
+use std::ffi::{CString, OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub use crate::FileStat;
+use crate::error::ErrorCode;
+use crate::request::Request;
+use crate::{Error, ffi, oneshot, utils};
+
+/// The size of the chunks files are read in when their size is unknown.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Complete = Box<dyn FnOnce(*mut ffi::uv_fs_t) + 'static>;
+
+/// An entry returned by [`scandir`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DirEntry {
+    name: OsString,
+    file_type: FileType,
+}
+
+/// The type of a [`DirEntry`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FileType {
+    File,
+    Dir,
+    Symlink,
+    Fifo,
+    Socket,
+    CharDevice,
+    BlockDevice,
+
+    /// The file system doesn't report the type of its entries. Use [`stat`]
+    /// to get it.
+    Unknown,
+}
+
+impl DirEntry {
+    /// Returns the name of the entry, without the leading path.
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// Returns the type of the entry.
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+}
+
+impl From<ffi::uv_dirent_type_t> for FileType {
+    fn from(ty: ffi::uv_dirent_type_t) -> Self {
+        match ty {
+            ffi::UV_DIRENT_FILE => Self::File,
+            ffi::UV_DIRENT_DIR => Self::Dir,
+            ffi::UV_DIRENT_LINK => Self::Symlink,
+            ffi::UV_DIRENT_FIFO => Self::Fifo,
+            ffi::UV_DIRENT_SOCKET => Self::Socket,
+            ffi::UV_DIRENT_CHAR => Self::CharDevice,
+            ffi::UV_DIRENT_BLOCK => Self::BlockDevice,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Reads the entire contents of a file.
+pub async fn read(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
+    let file = File::open(path.as_ref(), ffi::UV_FS_O_RDONLY, 0).await?;
+
+    let size = file.size().await? as usize;
+
+    let mut contents = Vec::with_capacity(size);
+
+    loop {
+        let len = if contents.len() < size {
+            size - contents.len()
+        } else {
+            CHUNK_SIZE
+        };
+
+        let chunk = file.read(contents.len() as i64, len).await?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        contents.extend_from_slice(&chunk);
+    }
+
+    file.close().await?;
+
+    Ok(contents)
+}
+
+/// Writes `contents` to a file, creating it if it doesn't exist and
+/// truncating it if it does.
+pub async fn write(
+    path: impl AsRef<Path>,
+    contents: impl Into<Vec<u8>>,
+) -> Result<(), Error> {
+    let flags = ffi::UV_FS_O_WRONLY | ffi::UV_FS_O_CREAT | ffi::UV_FS_O_TRUNC;
+
+    let file = File::open(path.as_ref(), flags, 0o666).await?;
+
+    let mut contents = contents.into();
+    let mut offset = 0;
+
+    while !contents.is_empty() {
+        let (written, rest) = file.write(offset, contents).await?;
+        offset += written as i64;
+        contents = rest;
+    }
+
+    file.close().await
+}
+
+/// Returns information about a file, following symbolic links.
+pub async fn stat(path: impl AsRef<Path>) -> Result<FileStat, Error> {
+    let path = to_cstring(path.as_ref())?;
+
+    submit(
+        |uv_loop, req, cb| unsafe {
+            ffi::uv_fs_stat(uv_loop, req, path.as_ptr(), cb)
+        },
+        |req| FileStat::from(unsafe { &*ffi::uv_fs_get_statbuf(req) }),
+    )
+    .await
+}
+
+/// Returns the entries of a directory, excluding `.` and `..`.
+pub async fn scandir(path: impl AsRef<Path>) -> Result<Vec<DirEntry>, Error> {
+    let path = to_cstring(path.as_ref())?;
+
+    submit(
+        |uv_loop, req, cb| unsafe {
+            ffi::uv_fs_scandir(uv_loop, req, path.as_ptr(), 0, cb)
+        },
+        |req| {
+            let mut entries = Vec::new();
+
+            let mut dirent =
+                ffi::uv_dirent_t { name: core::ptr::null(), r#type: 0 };
+
+            while unsafe { ffi::uv_fs_scandir_next(req, &mut dirent) } >= 0 {
+                let name = unsafe { CStr::from_ptr(dirent.name) }.to_bytes();
+
+                entries.push(DirEntry {
+                    name: utils::to_os_string(name),
+                    file_type: dirent.r#type.into(),
+                });
+            }
+
+            entries
+        },
+    )
+    .await
+}
+
+/// Creates a new directory with permissions `0o777` (before the umask is
+/// applied).
+pub async fn mkdir(path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = to_cstring(path.as_ref())?;
+
+    submit(
+        |uv_loop, req, cb| unsafe {
+            ffi::uv_fs_mkdir(uv_loop, req, path.as_ptr(), 0o777, cb)
+        },
+        |_| (),
+    )
+    .await
+}
+
+/// Renames a file or directory, replacing `to` if it already exists.
+pub async fn rename(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+) -> Result<(), Error> {
+    let from = to_cstring(from.as_ref())?;
+    let to = to_cstring(to.as_ref())?;
+
+    submit(
+        |uv_loop, req, cb| unsafe {
+            ffi::uv_fs_rename(uv_loop, req, from.as_ptr(), to.as_ptr(), cb)
+        },
+        |_| (),
+    )
+    .await
+}
+
+/// Removes a file.
+pub async fn unlink(path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = to_cstring(path.as_ref())?;
+
+    submit(
+        |uv_loop, req, cb| unsafe {
+            ffi::uv_fs_unlink(uv_loop, req, path.as_ptr(), cb)
+        },
+        |_| (),
+    )
+    .await
+}
+
+/// Returns the canonical, absolute form of a path with all the symbolic
+/// links resolved.
+pub async fn realpath(path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let path = to_cstring(path.as_ref())?;
+
+    submit(
+        |uv_loop, req, cb| unsafe {
+            ffi::uv_fs_realpath(uv_loop, req, path.as_ptr(), cb)
+        },
+        |req| {
+            let ptr = unsafe { ffi::uv_fs_get_ptr(req) } as *const c_char;
+            let path = unsafe { CStr::from_ptr(ptr) }.to_bytes();
+            utils::to_os_string(path).into()
+        },
+    )
+    .await
+}
+
+/// An open file.
+///
+/// Every request on the file holds a reference to its descriptor until it
+/// completes, so if the file is dropped while a request is in flight, e.g.
+/// because the future driving it was dropped, the descriptor is only closed
+/// once the last pending request is done with it.
+struct File {
+    fd: Rc<Fd>,
+}
+
+/// A file descriptor, closed when dropped if [`File::close`] wasn't called.
+struct Fd(ffi::uv_file);
+
+impl File {
+    async fn open(
+        path: &Path,
+        flags: c_int,
+        mode: c_int,
+    ) -> Result<Self, Error> {
+        let path = to_cstring(path)?;
+
+        let fd = submit(
+            |uv_loop, req, cb| unsafe {
+                ffi::uv_fs_open(uv_loop, req, path.as_ptr(), flags, mode, cb)
+            },
+            |req| unsafe { ffi::uv_fs_get_result(req) } as ffi::uv_file,
+        )
+        .await?;
+
+        Ok(Self { fd: Rc::new(Fd(fd)) })
+    }
+
+    async fn size(&self) -> Result<u64, Error> {
+        let (fd, guard) = (self.fd.0, Rc::clone(&self.fd));
+
+        submit(
+            |uv_loop, req, cb| unsafe {
+                ffi::uv_fs_fstat(uv_loop, req, fd, cb)
+            },
+            move |req| {
+                drop(guard);
+                unsafe { (*ffi::uv_fs_get_statbuf(req)).st_size }
+            },
+        )
+        .await
+    }
+
+    /// Reads up to `len` bytes starting at `offset`.
+    async fn read(&self, offset: i64, len: usize) -> Result<Vec<u8>, Error> {
+        let (fd, guard) = (self.fd.0, Rc::clone(&self.fd));
+
+        // The buffer and the descriptor are moved into the completion
+        // callback so that they stay alive until libuv is done with them,
+        // even if this future is dropped.
+        let mut buffer = vec![0u8; len];
+        let buf = ffi::uv_buf_t {
+            base: buffer.as_mut_ptr() as *mut c_char,
+            len: buffer.len(),
+        };
+
+        submit(
+            |uv_loop, req, cb| unsafe {
+                ffi::uv_fs_read(uv_loop, req, fd, &buf, 1, offset, cb)
+            },
+            move |req| {
+                drop(guard);
+                let read = unsafe { ffi::uv_fs_get_result(req) } as usize;
+                buffer.truncate(read);
+                buffer
+            },
+        )
+        .await
+    }
+
+    /// Writes `bytes` at `offset`, returning the number of bytes written
+    /// together with the ones that weren't.
+    async fn write(
+        &self,
+        offset: i64,
+        mut bytes: Vec<u8>,
+    ) -> Result<(usize, Vec<u8>), Error> {
+        let (fd, guard) = (self.fd.0, Rc::clone(&self.fd));
+
+        let buf = ffi::uv_buf_t {
+            base: bytes.as_mut_ptr() as *mut c_char,
+            len: bytes.len(),
+        };
+
+        submit(
+            |uv_loop, req, cb| unsafe {
+                ffi::uv_fs_write(uv_loop, req, fd, &buf, 1, offset, cb)
+            },
+            move |req| {
+                drop(guard);
+                let written = unsafe { ffi::uv_fs_get_result(req) } as usize;
+                bytes.drain(..written);
+                (written, bytes)
+            },
+        )
+        .await
+    }
+
+    /// Closes the file, or lets the last pending request close it if there
+    /// are any.
+    async fn close(self) -> Result<(), Error> {
+        match Rc::try_unwrap(self.fd) {
+            Ok(fd) => {
+                let fd = core::mem::ManuallyDrop::new(fd);
+                close(fd.0).await
+            },
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+impl Drop for Fd {
+    fn drop(&mut self) {
+        // The close is submitted right away, so it'll happen even if the
+        // returned future is never polled.
+        drop(close(self.0));
+    }
+}
+
+fn close(fd: ffi::uv_file) -> oneshot::Receiver<()> {
+    submit(
+        |uv_loop, req, cb| unsafe { ffi::uv_fs_close(uv_loop, req, fd, cb) },
+        |_| (),
+    )
+}
+
+/// Submits a new file system request by calling `start` with the Neovim
+/// event loop, the request and the callback to pass to libuv.
+///
+/// When the request completes successfully, `complete` is called on the main
+/// thread to extract the request's output.
+fn submit<S, C, T>(start: S, complete: C) -> oneshot::Receiver<T>
+where
+    S: FnOnce(*mut ffi::uv_loop_t, *mut ffi::uv_fs_t, ffi::uv_fs_cb) -> c_int,
+    C: FnOnce(*mut ffi::uv_fs_t) -> T + 'static,
+    T: 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+
+    let complete: Complete = Box::new(move |req| {
+        let result = unsafe { ffi::uv_fs_get_result(req) };
+
+        if result < 0 {
+            sender.fail(Error::Fs(ErrorCode::new(result as c_int)));
+        } else {
+            sender.send(complete(req));
+        }
+    });
+
+    let mut req = match Request::<ffi::uv_fs_t, _>::new(Some(complete)) {
+        Ok(req) => req,
+        Err(err) => return oneshot::failed(err),
+    };
+
+    let retv = unsafe {
+        crate::with_loop(|uv_loop| {
+            start(uv_loop, req.as_mut_ptr(), Some(fs_cb))
+        })
+    };
+
+    if retv < 0 {
+        unsafe { ffi::uv_fs_req_cleanup(req.as_mut_ptr()) };
+        drop(unsafe { req.into_data() });
+        return oneshot::failed(Error::Fs(ErrorCode::new(retv)));
+    }
+
+    receiver
+}
+
+extern "C" fn fs_cb(ptr: *mut ffi::uv_fs_t) {
+    let mut req = unsafe { Request::<_, Option<Complete>>::from_raw(ptr) };
+
+    // The completion callback has to be called before the request is freed
+    // since it reads the request's results.
+    if let Some(complete) = unsafe { req.data_mut() }.take() {
+        complete(ptr);
+    }
+
+    unsafe {
+        ffi::uv_fs_req_cleanup(ptr);
+        drop(req.into_data());
+    }
+}
+
+fn to_cstring(path: &Path) -> Result<CString, Error> {
+    utils::to_cstring(path.as_os_str())
+        .ok_or(Error::Fs(ErrorCode::new(ffi::UV_EINVAL)))
+}
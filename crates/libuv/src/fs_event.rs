@@ -0,0 +1,321 @@
+use core::ffi::{CStr, c_char, c_int, c_uint};
+use core::mem::ManuallyDrop;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::error::Error as StdError;
+use std::path::{Path, PathBuf};
+
+use futures_core::Stream;
+
+use crate::callback::{self, HandleKind};
+use crate::error::ErrorCode;
+use crate::handle::impl_lifecycle;
+use crate::queue::Queue;
+use crate::{Error, Handle, IntoResult, ffi, utils};
+
+pub(crate) type Callback = Box<
+    dyn FnMut(
+            &mut FsEventHandle,
+            Result<FsEvent, Error>,
+        ) -> Result<(), Box<dyn StdError>>
+        + 'static,
+>;
+
+/// Binding to libuv's [FS Event handle][1] used to get notified when a file
+/// or a directory changes.
+///
+/// The watcher relies on the OS's native file notification APIs (inotify,
+/// FSEvents, kqueue, etc.), so its behavior differs slightly across
+/// platforms. See [`FsPollHandle`](crate::FsPollHandle) for a portable, if
+/// less efficient, alternative.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/fs_event.html
+pub struct FsEventHandle {
+    handle: Handle<ffi::uv_fs_event_t, State>,
+}
+
+/// The data stored in the handle.
+struct State {
+    callback: Callback,
+
+    /// Whether the watched path is a directory, checked once when the
+    /// handle is started to avoid blocking on every event.
+    is_dir: bool,
+}
+
+impl_lifecycle!(FsEventHandle, handle);
+
+/// The options used to start a [`FsEventHandle`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FsEventOptions {
+    flags: c_uint,
+}
+
+/// A change reported by a [`FsEventHandle`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FsEvent {
+    /// The path of the file that changed. When watching a directory this is
+    /// the path of the entry inside it that changed.
+    pub path: PathBuf,
+
+    /// The kind of change.
+    pub kind: FsEventKind,
+}
+
+/// The kind of change reported by a [`FsEventHandle`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FsEventKind {
+    /// The file was renamed, created or deleted.
+    Rename,
+
+    /// The file's contents or metadata changed.
+    Change,
+}
+
+/// A stream of the changes reported by a [`FsEventHandle`], returned by
+/// [`FsEventHandle::watch`].
+///
+/// The watcher is stopped when the stream is dropped.
+#[must_use = "streams do nothing unless polled"]
+pub struct FsEventStream {
+    handle: FsEventHandle,
+    queue: Queue<Result<FsEvent, Error>>,
+}
+
+impl FsEventOptions {
+    /// Watch the entries of a directory recursively.
+    ///
+    /// NOTE: this is currently only supported on macOS and Windows.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.set(ffi::UV_FS_EVENT_RECURSIVE, recursive);
+        self
+    }
+
+    /// When watching a directory, only watch the directory itself and not
+    /// its entries.
+    ///
+    /// NOTE: this is currently ignored on most platforms.
+    pub fn watch_entry(mut self, watch_entry: bool) -> Self {
+        self.set(ffi::UV_FS_EVENT_WATCH_ENTRY, watch_entry);
+        self
+    }
+
+    /// Use periodic `stat()` calls instead of the platform's native file
+    /// notification APIs.
+    ///
+    /// NOTE: this is currently ignored on most platforms.
+    pub fn stat(mut self, stat: bool) -> Self {
+        self.set(ffi::UV_FS_EVENT_STAT, stat);
+        self
+    }
+
+    fn set(&mut self, flag: c_uint, value: bool) {
+        if value {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+    }
+}
+
+impl FsEventHandle {
+    fn new() -> Result<Self, Error> {
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_fs_event_init(uv_loop, handle.as_mut_ptr())
+        })?;
+
+        Ok(Self { handle })
+    }
+
+    /// Starts watching the given path, calling `callback` every time a
+    /// change is detected.
+    pub fn start<Cb, R>(
+        path: impl AsRef<Path>,
+        options: FsEventOptions,
+        mut callback: Cb,
+    ) -> Result<Self, Error>
+    where
+        Cb: FnMut(&mut Self, Result<FsEvent, Error>) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        let is_dir = path.as_ref().is_dir();
+
+        let path = utils::to_cstring(path.as_ref().as_os_str())
+            .ok_or(Error::FsEventStart(ErrorCode::new(ffi::UV_EINVAL)))?;
+
+        let mut fs_event = Self::new()?;
+
+        let callback: Callback = Box::new(move |fs_event, event| {
+            // Type erase the callback by boxing its error.
+            callback(fs_event, event)
+                .into_result()
+                .map_err(|err| Box::new(err) as Box<dyn StdError>)
+        });
+
+        unsafe { fs_event.handle.set_data(State { callback, is_dir }) };
+
+        let retv = unsafe {
+            ffi::uv_fs_event_start(
+                fs_event.handle.as_mut_ptr(),
+                Some(fs_event_cb as _),
+                path.as_ptr(),
+                options.flags,
+            )
+        };
+
+        if retv < 0 {
+            return Err(Error::FsEventStart(ErrorCode::new(retv)));
+        }
+
+        Ok(fs_event)
+    }
+
+    /// Starts watching the given path, returning a stream of the detected
+    /// changes.
+    pub fn watch(
+        path: impl AsRef<Path>,
+        options: FsEventOptions,
+    ) -> Result<FsEventStream, Error> {
+        let queue = Queue::default();
+
+        let handle = {
+            let queue = queue.clone();
+            Self::start(path, options, move |_, event| queue.push(event))?
+        };
+
+        Ok(FsEventStream { handle, queue })
+    }
+
+    /// Stops watching for changes.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let retv = unsafe { ffi::uv_fs_event_stop(self.handle.as_mut_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::FsEventStop);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path being watched, or `None` if the handle is not
+    /// active.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.get_path().ok()
+    }
+
+    fn get_path(&self) -> Result<PathBuf, ErrorCode> {
+        let mut buffer = [0 as c_char; 4096];
+        let mut size = buffer.len();
+
+        let retv = unsafe {
+            ffi::uv_fs_event_getpath(
+                self.handle.as_ptr() as *mut _,
+                buffer.as_mut_ptr(),
+                &mut size,
+            )
+        };
+
+        if retv < 0 {
+            return Err(ErrorCode::new(retv));
+        }
+
+        let bytes = unsafe {
+            core::slice::from_raw_parts(buffer.as_ptr() as *const u8, size)
+        };
+
+        Ok(utils::to_os_string(bytes).into())
+    }
+}
+
+impl FsEventStream {
+    /// Waits for the next change.
+    pub async fn next_event(&mut self) -> Result<FsEvent, Error> {
+        core::future::poll_fn(|cx| self.queue.poll_pop(cx)).await
+    }
+}
+
+impl Stream for FsEventStream {
+    type Item = Result<FsEvent, Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.queue.poll_pop(cx).map(Some)
+    }
+}
+
+impl Drop for FsEventStream {
+    fn drop(&mut self) {
+        let _ = self.handle.stop();
+    }
+}
+
+impl Drop for FsEventHandle {
+    fn drop(&mut self) {
+        unsafe { self.handle.drop_owned() };
+    }
+}
+
+extern "C" fn fs_event_cb(
+    ptr: *mut ffi::uv_fs_event_t,
+    filename: *const c_char,
+    events: c_int,
+    status: c_int,
+) {
+    let handle: Handle<_, State> = unsafe { Handle::from_raw(ptr) };
+
+    let state = unsafe { handle.get_data() };
+
+    if state.is_null() {
+        return;
+    }
+
+    // The handle is owned by someone else, so we can't drop it.
+    let mut handle = ManuallyDrop::new(FsEventHandle { handle });
+    let State { callback, is_dir } = unsafe { &mut *state };
+
+    let watched = if status < 0 {
+        Err(ErrorCode::new(status))
+    } else {
+        handle.get_path()
+    };
+
+    let watched = match watched {
+        Ok(watched) => watched,
+        Err(code) => {
+            let err = Error::FsEvent(code);
+            callback::call(HandleKind::FsEvent, || {
+                callback(&mut handle, Err(err))
+            });
+            unsafe { handle.handle.close_if_detached() };
+            return;
+        },
+    };
+
+    let path = if filename.is_null() {
+        watched
+    } else {
+        let filename = unsafe { CStr::from_ptr(filename) }.to_bytes();
+        let filename = utils::to_os_string(filename);
+
+        // When watching a single file libuv reports its basename.
+        if *is_dir { watched.join(filename) } else { watched }
+    };
+
+    for (flag, kind) in [
+        (ffi::UV_RENAME, FsEventKind::Rename),
+        (ffi::UV_CHANGE, FsEventKind::Change),
+    ] {
+        if events & flag != 0 {
+            let event = FsEvent { path: path.clone(), kind };
+
+            callback::call(HandleKind::FsEvent, || {
+                callback(&mut handle, Ok(event))
+            });
+        }
+    }
+
+    unsafe { handle.handle.close_if_detached() };
+}
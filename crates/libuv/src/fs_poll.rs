@@ -0,0 +1,198 @@
+use core::ffi::c_int;
+use core::mem::ManuallyDrop;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::error::Error as StdError;
+use std::path::Path;
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use crate::callback::{self, HandleKind};
+use crate::error::ErrorCode;
+use crate::handle::impl_lifecycle;
+use crate::queue::Queue;
+use crate::{Error, FileStat, Handle, IntoResult, ffi, utils};
+
+pub(crate) type Callback = Box<
+    dyn FnMut(
+            &mut FsPollHandle,
+            Result<FsPollEvent, Error>,
+        ) -> Result<(), Box<dyn StdError>>
+        + 'static,
+>;
+
+/// Binding to libuv's [FS Poll handle][1] used to get notified when a file
+/// changes.
+///
+/// Unlike [`FsEventHandle`](crate::FsEventHandle) it doesn't rely on the
+/// OS's file notification APIs, but it periodically `stat()`s the file
+/// instead. This makes it work on any file system, including network ones.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/fs_poll.html
+pub struct FsPollHandle {
+    handle: Handle<ffi::uv_fs_poll_t, Callback>,
+}
+
+impl_lifecycle!(FsPollHandle, handle);
+
+/// A change reported by a [`FsPollHandle`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FsPollEvent {
+    /// The stat of the file before the change.
+    pub prev: FileStat,
+
+    /// The stat of the file after the change.
+    pub curr: FileStat,
+}
+
+/// A stream of the changes reported by a [`FsPollHandle`], returned by
+/// [`FsPollHandle::watch`].
+///
+/// The watcher is stopped when the stream is dropped.
+#[must_use = "streams do nothing unless polled"]
+pub struct FsPollStream {
+    handle: FsPollHandle,
+    queue: Queue<Result<FsPollEvent, Error>>,
+}
+
+impl FsPollHandle {
+    fn new() -> Result<Self, Error> {
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_fs_poll_init(uv_loop, handle.as_mut_ptr())
+        })?;
+
+        Ok(Self { handle })
+    }
+
+    /// Starts checking the given path for changes every `interval`, calling
+    /// `callback` every time a change is detected.
+    ///
+    /// The callback is also called with an error if the file can't be
+    /// `stat()`ed, e.g. because it doesn't exist or it was deleted. It won't
+    /// be called again until the error changes or the file is recreated.
+    pub fn start<Cb, R>(
+        path: impl AsRef<Path>,
+        interval: Duration,
+        mut callback: Cb,
+    ) -> Result<Self, Error>
+    where
+        Cb: FnMut(&mut Self, Result<FsPollEvent, Error>) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        let path = utils::to_cstring(path.as_ref().as_os_str())
+            .ok_or(Error::FsPollStart(ErrorCode::new(ffi::UV_EINVAL)))?;
+
+        let mut fs_poll = Self::new()?;
+
+        let callback: Callback = Box::new(move |fs_poll, event| {
+            // Type erase the callback by boxing its error.
+            callback(fs_poll, event)
+                .into_result()
+                .map_err(|err| Box::new(err) as Box<dyn StdError>)
+        });
+
+        unsafe { fs_poll.handle.set_data(callback) };
+
+        let retv = unsafe {
+            ffi::uv_fs_poll_start(
+                fs_poll.handle.as_mut_ptr(),
+                Some(fs_poll_cb as _),
+                path.as_ptr(),
+                interval.as_millis() as _,
+            )
+        };
+
+        if retv < 0 {
+            return Err(Error::FsPollStart(ErrorCode::new(retv)));
+        }
+
+        Ok(fs_poll)
+    }
+
+    /// Starts checking the given path for changes every `interval`,
+    /// returning a stream of the detected changes.
+    pub fn watch(
+        path: impl AsRef<Path>,
+        interval: Duration,
+    ) -> Result<FsPollStream, Error> {
+        let queue = Queue::default();
+
+        let handle = {
+            let queue = queue.clone();
+            Self::start(path, interval, move |_, event| queue.push(event))?
+        };
+
+        Ok(FsPollStream { handle, queue })
+    }
+
+    /// Stops checking for changes.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let retv = unsafe { ffi::uv_fs_poll_stop(self.handle.as_mut_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::FsPollStop);
+        }
+
+        Ok(())
+    }
+}
+
+impl FsPollStream {
+    /// Waits for the next change.
+    pub async fn next_event(&mut self) -> Result<FsPollEvent, Error> {
+        core::future::poll_fn(|cx| self.queue.poll_pop(cx)).await
+    }
+}
+
+impl Stream for FsPollStream {
+    type Item = Result<FsPollEvent, Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.queue.poll_pop(cx).map(Some)
+    }
+}
+
+impl Drop for FsPollStream {
+    fn drop(&mut self) {
+        let _ = self.handle.stop();
+    }
+}
+
+impl Drop for FsPollHandle {
+    fn drop(&mut self) {
+        unsafe { self.handle.drop_owned() };
+    }
+}
+
+extern "C" fn fs_poll_cb(
+    ptr: *mut ffi::uv_fs_poll_t,
+    status: c_int,
+    prev: *const ffi::uv_stat_t,
+    curr: *const ffi::uv_stat_t,
+) {
+    let handle: Handle<_, Callback> = unsafe { Handle::from_raw(ptr) };
+
+    let callback = unsafe { handle.get_data() };
+
+    if !callback.is_null() {
+        // The handle is owned by someone else, so we can't drop it.
+        let mut handle = ManuallyDrop::new(FsPollHandle { handle });
+        let callback = unsafe { &mut *callback };
+
+        let event = if status < 0 {
+            Err(Error::FsPoll(ErrorCode::new(status)))
+        } else {
+            let (prev, curr) = unsafe { (&*prev, &*curr) };
+            Ok(FsPollEvent { prev: prev.into(), curr: curr.into() })
+        };
+
+        callback::call(HandleKind::FsPoll, || callback(&mut handle, event));
+
+        unsafe { handle.handle.close_if_detached() };
+    }
+}
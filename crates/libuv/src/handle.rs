@@ -4,15 +4,68 @@ use std::marker::PhantomData;
 
 use crate::{Error, Result, ffi};
 
-/// TODO: docs
-pub(crate) trait ProperLayout: Sized {}
+/// Trait implemented by the libuv handle and request types that can be
+/// allocated by Rust.
+pub(crate) trait ProperLayout: Sized {
+    /// The memory layout of the type.
+    ///
+    /// Types that are fully defined on the Rust side can use the default
+    /// implementation. Opaque types should return the size reported by libuv
+    /// at runtime via [`handle_layout`] or [`req_layout`].
+    fn layout() -> Layout {
+        Layout::new::<Self>()
+    }
+}
 
-/// TODO: docs
+/// Returns the layout of a handle of the given type.
+pub(crate) fn handle_layout(ty: ffi::uv_handle_type) -> Layout {
+    let size = unsafe { ffi::uv_handle_size(ty) };
+    Layout::from_size_align(size, ALIGN).expect("valid layout")
+}
+
+/// Returns the layout of a request of the given type.
+pub(crate) fn req_layout(ty: ffi::uv_req_type) -> Layout {
+    let size = unsafe { ffi::uv_req_size(ty) };
+    Layout::from_size_align(size, ALIGN).expect("valid layout")
+}
+
+/// The alignment used to allocate opaque handles and requests, which is the
+/// largest alignment required by any of the fields of libuv's structs.
+const ALIGN: usize = if align_of::<u64>() > align_of::<*mut c_void>() {
+    align_of::<u64>()
+} else {
+    align_of::<*mut c_void>()
+};
+
+type OnClose = Box<dyn FnOnce() + 'static>;
+
+/// A pointer to a libuv handle of type `T` allocated by Rust, whose `data`
+/// field stores a `D`.
+///
+/// The handle's memory and its data are only freed once the handle is
+/// closed, either explicitly or when the owning wrapper is dropped while the
+/// handle is inactive (see [`drop_owned`](Self::drop_owned)).
 pub(crate) struct Handle<T, D: 'static> {
     ptr: *mut T,
     data: PhantomData<D>,
 }
 
+/// What's actually stored in the handle's `data` field.
+///
+/// `data` is the first field so that a pointer to a `HandleData<D>` is also a
+/// valid pointer to a `D`.
+#[repr(C)]
+struct HandleData<D> {
+    data: D,
+
+    /// Whether the Rust value owning the handle has been dropped while the
+    /// handle was still active.
+    is_detached: bool,
+
+    /// A callback to execute once the handle has been closed.
+    on_close: Option<OnClose>,
+}
+
 impl<T, D> Clone for Handle<T, D> {
     fn clone(&self) -> Self {
         Self { ptr: self.ptr, data: PhantomData }
@@ -20,32 +73,101 @@ impl<T, D> Clone for Handle<T, D> {
 }
 
 impl<T: ProperLayout, D> Handle<T, D> {
-    /// TODO: docs
+    /// Allocates a new handle and initializes it by calling `initializer`
+    /// with the Neovim event loop.
     pub(crate) fn new<I>(initializer: I) -> Result<Handle<T, D>>
     where
         I: FnOnce(*mut ffi::uv_loop_t, &mut Self) -> i32,
     {
-        let layout = Layout::new::<T>();
-        let ptr = unsafe { alloc::alloc(layout) as *mut T };
-
-        if ptr.is_null() {
-            return Err(Error::HandleMemAlloc);
-        }
-
-        let mut handle = Self { ptr, data: PhantomData };
+        let mut handle = Self::alloc()?;
 
         let retv = unsafe {
             crate::with_loop(|uv_loop| initializer(uv_loop, &mut handle))
         };
 
         if retv < 0 {
-            unsafe { alloc::dealloc(ptr as *mut u8, layout) };
+            unsafe { alloc::dealloc(handle.ptr as *mut u8, T::layout()) };
             return Err(Error::HandleInit);
         }
 
         Ok(handle)
     }
 
+    /// Allocates the memory for a new handle without initializing it.
+    ///
+    /// The handle's memory will only be freed by [`close`](Self::close), so
+    /// the caller is responsible for freeing it if the initialization fails
+    /// before libuv has taken ownership of the handle.
+    pub(crate) fn alloc() -> Result<Handle<T, D>> {
+        let ptr = unsafe { alloc::alloc_zeroed(T::layout()) as *mut T };
+
+        if ptr.is_null() {
+            return Err(Error::HandleMemAlloc);
+        }
+
+        Ok(Self { ptr, data: PhantomData })
+    }
+
+    /// Closes the handle, freeing its memory and its data once libuv is done
+    /// with it.
+    ///
+    /// Does nothing if the handle is already being closed.
+    pub(crate) unsafe fn close(&mut self) {
+        self.close_with(None);
+    }
+
+    /// Same as [`close`](Self::close), but also executes `on_close` once the
+    /// handle has been closed.
+    ///
+    /// If the handle is already being closed `on_close` is dropped without
+    /// being called.
+    pub(crate) unsafe fn close_with(&mut self, on_close: Option<OnClose>) {
+        if self.is_closing() {
+            return;
+        }
+
+        if let Some(data) = self.handle_data().as_mut() {
+            data.on_close = on_close;
+        }
+
+        ffi::uv_close(
+            self.ptr as *mut ffi::uv_handle_t,
+            Some(close_cb::<T, D> as _),
+        );
+    }
+
+    /// Should be called when the Rust value owning the handle is dropped.
+    ///
+    /// Inactive handles are closed right away, while active ones are
+    /// detached and left running. A detached handle is closed as soon as it
+    /// becomes inactive at the end of one of its callbacks (see
+    /// [`close_if_detached`](Self::close_if_detached)).
+    pub(crate) unsafe fn drop_owned(&mut self) {
+        if !self.is_active() {
+            self.close();
+        } else if let Some(data) = self.handle_data().as_mut() {
+            data.is_detached = true;
+        }
+    }
+
+    /// Closes the handle if it's been detached and it's not active anymore.
+    ///
+    /// Should be called at the end of every callback of handles that can be
+    /// stopped from within their own callbacks.
+    pub(crate) unsafe fn close_if_detached(&mut self) {
+        let is_detached = self
+            .handle_data()
+            .as_ref()
+            .map(|data| data.is_detached)
+            .unwrap_or(false);
+
+        if is_detached && !self.is_active() {
+            self.close();
+        }
+    }
+}
+
+impl<T, D> Handle<T, D> {
     pub(crate) fn as_ptr(&self) -> *const T {
         self.ptr.cast()
     }
@@ -59,16 +181,129 @@ impl<T: ProperLayout, D> Handle<T, D> {
     }
 
     pub(crate) unsafe fn get_data(&self) -> *mut D {
-        ffi::uv_handle_get_data(self.as_ptr() as *const ffi::uv_handle_t)
-            as *mut D
+        // This is ok because `data` is the first field of `HandleData`.
+        self.handle_data() as *mut D
     }
 
     pub(crate) unsafe fn set_data(&mut self, data: D) {
-        let data = Box::into_raw(Box::new(data));
+        let data = HandleData { data, is_detached: false, on_close: None };
 
         ffi::uv_handle_set_data(
             self.as_mut_ptr() as *mut ffi::uv_handle_t,
-            data as *mut c_void,
+            Box::into_raw(Box::new(data)) as *mut c_void,
         )
     }
+
+    unsafe fn handle_data(&self) -> *mut HandleData<D> {
+        ffi::uv_handle_get_data(self.as_ptr() as *const ffi::uv_handle_t)
+            as *mut HandleData<D>
+    }
+
+    fn as_handle_ptr(&self) -> *mut ffi::uv_handle_t {
+        self.ptr as *mut ffi::uv_handle_t
+    }
+
+    /// Returns whether the handle is active.
+    ///
+    /// What "active" means depends on the type of the handle, e.g. a timer
+    /// is active while it's started and a stream while it's reading, writing
+    /// or listening for connections.
+    pub(crate) fn is_active(&self) -> bool {
+        unsafe { ffi::uv_is_active(self.as_handle_ptr()) != 0 }
+    }
+
+    /// Returns whether the handle is closing or closed.
+    pub(crate) fn is_closing(&self) -> bool {
+        unsafe { ffi::uv_is_closing(self.as_handle_ptr()) != 0 }
+    }
+
+    /// Makes the handle keep the event loop alive while it's active.
+    pub(crate) fn ref_(&mut self) {
+        unsafe { ffi::uv_ref(self.as_handle_ptr()) }
+    }
+
+    /// Stops the handle from keeping the event loop alive.
+    pub(crate) fn unref(&mut self) {
+        unsafe { ffi::uv_unref(self.as_handle_ptr()) }
+    }
+
+    /// Returns whether the handle is referenced.
+    pub(crate) fn has_ref(&self) -> bool {
+        unsafe { ffi::uv_has_ref(self.as_handle_ptr()) != 0 }
+    }
+}
+
+extern "C" fn close_cb<T: ProperLayout, D: 'static>(
+    ptr: *mut ffi::uv_handle_t,
+) {
+    let handle: Handle<T, D> = unsafe { Handle::from_raw(ptr as *mut T) };
+
+    let data = unsafe { handle.handle_data() };
+
+    let on_close = if !data.is_null() {
+        unsafe { Box::from_raw(data) }.on_close
+    } else {
+        None
+    };
+
+    unsafe { alloc::dealloc(ptr as *mut u8, T::layout()) };
+
+    if let Some(on_close) = on_close {
+        on_close();
+    }
 }
+
+/// Implements the methods used to manage the lifecycle of a handle on a
+/// wrapper type, given the path of the field holding a type exposing the
+/// same methods as [`Handle`].
+macro_rules! impl_lifecycle {
+    ($ty:ty, $($field:ident).+) => {
+        impl $ty {
+            /// Closes the handle, executing `callback` on the main thread
+            /// once libuv is done with it.
+            ///
+            /// The handle's callback and any data associated with it are
+            /// freed before `callback` is called.
+            pub fn close<Cb>(mut self, callback: Cb)
+            where
+                Cb: FnOnce() + 'static,
+            {
+                unsafe { self.$($field).+.close_with(Some(Box::new(callback))) }
+            }
+
+            /// Returns whether the handle is active.
+            ///
+            /// What "active" means depends on the type of the handle, e.g.
+            /// a timer is active while it's started and a stream while it's
+            /// reading, writing or listening for connections.
+            pub fn is_active(&self) -> bool {
+                self.$($field).+.is_active()
+            }
+
+            /// Returns whether the handle is closing or closed.
+            pub fn is_closing(&self) -> bool {
+                self.$($field).+.is_closing()
+            }
+
+            /// Makes the handle keep the event loop alive while it's
+            /// active. Handles are referenced by default.
+            pub fn ref_(&mut self) {
+                self.$($field).+.ref_()
+            }
+
+            /// Stops the handle from keeping the event loop alive, e.g. so
+            /// that a repeating timer doesn't prevent `nvim --headless`
+            /// from exiting.
+            pub fn unref(&mut self) {
+                self.$($field).+.unref()
+            }
+
+            /// Returns whether the handle is referenced.
+            pub fn has_ref(&self) -> bool {
+                self.$($field).+.has_ref()
+            }
+        }
+    };
+}
+
+pub(crate) use impl_lifecycle;
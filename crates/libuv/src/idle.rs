@@ -0,0 +1,96 @@
+use core::mem::ManuallyDrop;
+use std::error::Error as StdError;
+
+use crate::callback::{self, HandleKind};
+use crate::handle::impl_lifecycle;
+use crate::{Error, Handle, IntoResult, ffi};
+
+pub(crate) type Callback =
+    Box<dyn FnMut(&mut IdleHandle) -> Result<(), Box<dyn StdError>> + 'static>;
+
+/// Binding to libuv's [Idle handle][1] used to run a callback once per event
+/// loop iteration, right before the [`PrepareHandle`](crate::PrepareHandle)s.
+///
+/// NOTE: an active idle handle makes the event loop perform a zero timeout
+/// poll instead of blocking for I/O, so it should be stopped as soon as it's
+/// not needed anymore.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/idle.html
+pub struct IdleHandle {
+    handle: Handle<ffi::uv_idle_t, Callback>,
+}
+
+impl_lifecycle!(IdleHandle, handle);
+
+impl IdleHandle {
+    fn new() -> Result<Self, Error> {
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_idle_init(uv_loop, handle.as_mut_ptr())
+        })?;
+
+        Ok(Self { handle })
+    }
+
+    /// Starts the handle, executing the callback once per event loop
+    /// iteration until the handle is stopped.
+    pub fn start<Cb, R>(mut callback: Cb) -> Result<Self, Error>
+    where
+        Cb: FnMut(&mut Self) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        let mut idle = Self::new()?;
+
+        let callback: Callback = Box::new(move |idle| {
+            // Type erase the callback by boxing its error.
+            callback(idle)
+                .into_result()
+                .map_err(|err| Box::new(err) as Box<dyn StdError>)
+        });
+
+        unsafe { idle.handle.set_data(callback) };
+
+        let retv = unsafe {
+            ffi::uv_idle_start(idle.handle.as_mut_ptr(), Some(idle_cb as _))
+        };
+
+        if retv < 0 {
+            return Err(Error::IdleStart);
+        }
+
+        Ok(idle)
+    }
+
+    /// Stops the handle. The callback will not be called anymore.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let retv = unsafe { ffi::uv_idle_stop(self.handle.as_mut_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::IdleStop);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for IdleHandle {
+    fn drop(&mut self) {
+        unsafe { self.handle.drop_owned() };
+    }
+}
+
+extern "C" fn idle_cb(ptr: *mut ffi::uv_idle_t) {
+    let handle: Handle<_, Callback> = unsafe { Handle::from_raw(ptr) };
+
+    let callback = unsafe { handle.get_data() };
+
+    if !callback.is_null() {
+        // The handle is owned by someone else, so we can't drop it.
+        let mut handle = ManuallyDrop::new(IdleHandle { handle });
+        let callback = unsafe { &mut *callback };
+
+        callback::call(HandleKind::Idle, || callback(&mut handle));
+
+        unsafe { handle.handle.close_if_detached() };
+    }
+}
@@ -1,15 +1,64 @@
 mod r#async;
+mod callback;
+mod cancel;
+mod check;
+mod dispatch;
 mod error;
+mod executor;
 mod ffi;
+pub mod fs;
+mod fs_event;
+mod fs_poll;
 mod handle;
+mod idle;
 mod r#loop;
+mod oneshot;
+mod pipe;
+mod prepare;
+mod process;
+mod queue;
+mod request;
+mod signal;
+mod stat;
+mod stream;
+mod tcp;
+mod time;
 mod timer;
+mod utils;
+mod work;
 
 pub use r#async::AsyncHandle;
-pub use error::Error;
+pub use callback::{
+    CallbackError,
+    HandleKind,
+    reset_error_handler,
+    set_error_handler,
+};
+pub use cancel::{CancelKey, Cancellable, CancellationToken, Cancelled};
+pub use check::CheckHandle;
+pub use dispatch::{Dispatch, MainThread, dispatch, dispatch_sync};
 use error::Result;
+pub use error::{Error, ErrorCode};
+pub use executor::{JoinHandle, spawn};
+pub use fs_event::{
+    FsEvent,
+    FsEventHandle,
+    FsEventKind,
+    FsEventOptions,
+    FsEventStream,
+};
+pub use fs_poll::{FsPollEvent, FsPollHandle, FsPollStream};
 use handle::{Handle, ProperLayout};
+pub use idle::IdleHandle;
 pub use r#loop::init;
 use r#loop::with_loop;
 pub use luajit::IntoResult;
+pub use pipe::{PipeHandle, PipeListener};
+pub use prepare::PrepareHandle;
+pub use process::{Child, ExitStatus, Process, Stdio};
+pub use signal::SignalHandle;
+pub use stat::FileStat;
+pub use tcp::{TcpHandle, TcpListener};
+pub use time::{Interval, Sleep, Timeout, interval, sleep, timeout};
 pub use timer::TimerHandle;
+pub use work::{QueueWork, queue_work, queue_work_async};
@@ -12,11 +12,18 @@ thread_local! {
 ///
 /// NOTE: this function **must** be called before calling any other function
 /// exposed by this crate or there will be segfaults.
+///
+/// Returns an error if the handle used by the [`MainThread`] couldn't be
+/// initialized.
+///
+/// [`MainThread`]: crate::MainThread
 #[doc(hidden)]
-pub unsafe fn init(lua_state: *mut State) {
+pub unsafe fn init(lua_state: *mut State) -> Result<(), crate::Error> {
     LOOP.with(|uv_loop| {
         let _ = uv_loop.set(ffi::luv_loop(lua_state));
     });
+
+    crate::MainThread::init()
 }
 
 /// Executes a function with access to the libuv loop.
@@ -0,0 +1,86 @@
+//! A minimal single-use channel used to send the result of an operation
+//! completed on the main thread to the future waiting for it.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+
+use crate::Error;
+
+/// Creates a new oneshot channel.
+pub(crate) fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Mutex::new(Shared { value: None, waker: None }));
+    (Sender { shared: Some(shared.clone()) }, Receiver { shared })
+}
+
+/// Returns a receiver that immediately resolves to the given error.
+///
+/// Useful when an operation fails before it can be submitted to libuv.
+pub(crate) fn failed<T>(err: Error) -> Receiver<T> {
+    let shared = Shared { value: Some(Err(err)), waker: None };
+    Receiver { shared: Arc::new(Mutex::new(shared)) }
+}
+
+struct Shared<T> {
+    value: Option<Result<T, Error>>,
+    waker: Option<Waker>,
+}
+
+/// The sending half of a [`channel`].
+///
+/// If the sender is dropped without calling [`send`](Sender::send) or
+/// [`fail`](Sender::fail) the receiver will resolve to [`Error::Canceled`].
+pub(crate) struct Sender<T> {
+    shared: Option<Arc<Mutex<Shared<T>>>>,
+}
+
+/// The receiving half of a [`channel`].
+pub(crate) struct Receiver<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Sender<T> {
+    pub(crate) fn send(mut self, value: T) {
+        self.complete(Ok(value));
+    }
+
+    pub(crate) fn fail(mut self, err: Error) {
+        self.complete(Err(err));
+    }
+
+    fn complete(&mut self, value: Result<T, Error>) {
+        let Some(shared) = self.shared.take() else { return };
+        let waker = {
+            let shared = &mut *shared.lock().unwrap();
+            shared.value = Some(value);
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.complete(Err(Error::Canceled));
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let shared = &mut *self.shared.lock().unwrap();
+
+        match shared.value.take() {
+            Some(value) => Poll::Ready(value),
+
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
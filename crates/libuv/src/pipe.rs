@@ -0,0 +1,184 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::ffi::CString;
+use std::path::Path;
+
+use futures_core::Stream;
+
+use crate::error::ErrorCode;
+use crate::handle::impl_lifecycle;
+use crate::stream::{ListenerHandle, StreamHandle};
+use crate::{Error, Handle, ffi, utils};
+
+/// The default number of pending connections a [`PipeListener`] can queue.
+const BACKLOG: u32 = 128;
+
+/// Binding to libuv's [Pipe handle][1], used to communicate with child
+/// processes and over Unix domain sockets (or named pipes on Windows).
+///
+/// A `PipeHandle` is a [`Stream`] of the byte chunks read from it. Reading
+/// pauses when too many chunks are waiting to be consumed, and resumes once
+/// the stream is polled again.
+///
+/// The pipe is closed when the handle is dropped.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/pipe.html
+pub struct PipeHandle {
+    stream: StreamHandle<ffi::uv_pipe_t>,
+}
+
+/// A [`PipeHandle`] listening for incoming connections on a Unix domain
+/// socket (or a named pipe on Windows).
+///
+/// A `PipeListener` is a [`Stream`] of the accepted connections. It stops
+/// listening when it's dropped.
+pub struct PipeListener {
+    listener: ListenerHandle<ffi::uv_pipe_t>,
+}
+
+impl_lifecycle!(PipeHandle, stream.handle);
+impl_lifecycle!(PipeListener, listener.handle);
+
+impl PipeHandle {
+    pub(crate) fn new() -> Result<Self, Error> {
+        Ok(Self { stream: init()? })
+    }
+
+    pub(crate) fn as_stream_ptr(&mut self) -> *mut ffi::uv_stream_t {
+        self.stream.as_mut_ptr() as *mut ffi::uv_stream_t
+    }
+
+    /// Connects to the Unix domain socket (or named pipe on Windows) at the
+    /// given path.
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = to_cstring(path.as_ref(), Error::StreamConnect)?;
+
+        let mut pipe = Self::new()?;
+
+        pipe.stream
+            .connect(|req, handle, cb| {
+                unsafe {
+                    ffi::uv_pipe_connect(req, handle, path.as_ptr(), cb)
+                };
+                0
+            })
+            .await?;
+
+        Ok(pipe)
+    }
+
+    /// Reads the next chunk of bytes from the pipe, returning `None` once
+    /// the other end has been closed.
+    pub async fn read(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.stream.read().await
+    }
+
+    /// Reads from the pipe until the other end is closed, returning all the
+    /// bytes that were read.
+    pub async fn read_to_end(&mut self) -> Result<Vec<u8>, Error> {
+        self.stream.read_to_end().await
+    }
+
+    /// Writes `bytes` to the pipe, completing once they've all been written.
+    ///
+    /// Awaiting the returned future before issuing the next write ensures
+    /// that the pipe's write queue doesn't grow unbounded if the other end
+    /// can't keep up.
+    pub async fn write(
+        &mut self,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.stream.write(bytes.into()).await
+    }
+
+    /// Shuts down the write side of the pipe after all the pending writes
+    /// have completed, signaling EOF to the other end.
+    pub async fn shutdown(&mut self) -> Result<(), Error> {
+        self.stream.shutdown().await
+    }
+
+    /// Returns the number of bytes that are queued for writing.
+    pub fn write_queue_size(&self) -> usize {
+        self.stream.write_queue_size()
+    }
+}
+
+impl Stream for PipeHandle {
+    type Item = Result<Vec<u8>, Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().stream.poll_read(cx)
+    }
+}
+
+impl PipeListener {
+    /// Creates a Unix domain socket (or named pipe on Windows) bound to the
+    /// given path, and starts listening for incoming connections on it.
+    ///
+    /// The socket file is not removed when the listener is dropped.
+    pub fn bind(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = to_cstring(path.as_ref(), Error::StreamBind)?;
+
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_pipe_init(uv_loop, handle.as_mut_ptr(), 0)
+        })?;
+
+        let mut listener = ListenerHandle::new(handle);
+
+        let retv =
+            unsafe { ffi::uv_pipe_bind(listener.as_mut_ptr(), path.as_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::StreamBind(ErrorCode::new(retv)));
+        }
+
+        listener.listen(BACKLOG)?;
+
+        Ok(Self { listener })
+    }
+
+    /// Waits for the next incoming connection.
+    pub async fn accept(&mut self) -> Result<PipeHandle, Error> {
+        core::future::poll_fn(|cx| self.poll_accept(cx)).await
+    }
+
+    /// Polls for the next incoming connection.
+    pub fn poll_accept(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<PipeHandle, Error>> {
+        self.listener
+            .poll_accept(cx, init)
+            .map_ok(|stream| PipeHandle { stream })
+    }
+}
+
+impl Stream for PipeListener {
+    type Item = Result<PipeHandle, Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_accept(cx).map(Some)
+    }
+}
+
+fn init() -> Result<StreamHandle<ffi::uv_pipe_t>, Error> {
+    let handle = Handle::new(|uv_loop, handle| unsafe {
+        ffi::uv_pipe_init(uv_loop, handle.as_mut_ptr(), 0)
+    })?;
+
+    Ok(StreamHandle::new(handle))
+}
+
+fn to_cstring(
+    path: &Path,
+    err: fn(ErrorCode) -> Error,
+) -> Result<CString, Error> {
+    utils::to_cstring(path.as_os_str())
+        .ok_or_else(|| err(ErrorCode::new(ffi::UV_EINVAL)))
+}
@@ -0,0 +1,511 @@
+use core::cell::RefCell;
+use core::ffi::{c_char, c_int};
+use core::fmt;
+use core::task::{Poll, Waker};
+use std::collections::BTreeMap;
+use std::ffi::{CString, OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::error::ErrorCode;
+use crate::{Error, Handle, PipeHandle, ffi, utils};
+
+type State = Rc<RefCell<ExitState>>;
+
+/// A builder used to spawn child processes on the Neovim event loop via
+/// libuv's [`uv_spawn`][1].
+///
+/// Its API mirrors the one of [`std::process::Command`], but the spawned
+/// [`Child`] is fully asynchronous: its stdio pipes are [`PipeHandle`]s and
+/// waiting for it to exit doesn't block the editor.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::libuv::{Process, Stdio};
+///
+/// nvim_oxi::spawn(async {
+///     let mut child = Process::new("git")
+///         .args(["rev-parse", "HEAD"])
+///         .stdout(Stdio::Piped)
+///         .spawn()?;
+///
+///     let stdout = child.stdout.take().unwrap().read_to_end().await?;
+///     let status = child.wait().await;
+///     # Ok::<_, nvim_oxi::libuv::Error>(())
+/// });
+/// ```
+///
+/// [1]: http://docs.libuv.org/en/v1.x/process.html#c.uv_spawn
+#[derive(Clone, Debug)]
+pub struct Process {
+    program: OsString,
+    args: Vec<OsString>,
+    env: BTreeMap<OsString, Option<OsString>>,
+    env_clear: bool,
+    cwd: Option<PathBuf>,
+    detached: bool,
+    stdin: Stdio,
+    stdout: Stdio,
+    stderr: Stdio,
+}
+
+/// Describes what to do with a standard I/O stream of a child process.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Stdio {
+    /// The stream is discarded, as if it was redirected to `/dev/null`.
+    #[default]
+    Null,
+
+    /// The stream is inherited from Neovim.
+    Inherit,
+
+    /// A new pipe is created to connect the parent and the child process,
+    /// and it's exposed on the corresponding field of [`Child`].
+    Piped,
+}
+
+/// A child process spawned via [`Process::spawn`].
+///
+/// Dropping a `Child` doesn't kill the process, which will keep running in
+/// the background.
+pub struct Child {
+    /// The child's stdin, if it was configured with [`Stdio::Piped`].
+    pub stdin: Option<PipeHandle>,
+
+    /// The child's stdout, if it was configured with [`Stdio::Piped`].
+    pub stdout: Option<PipeHandle>,
+
+    /// The child's stderr, if it was configured with [`Stdio::Piped`].
+    pub stderr: Option<PipeHandle>,
+
+    handle: Handle<ffi::uv_process_t, State>,
+    state: State,
+    pid: u32,
+}
+
+/// The status of a child process that has exited.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ExitStatus {
+    code: i64,
+    signal: i32,
+}
+
+#[derive(Default)]
+struct ExitState {
+    status: Option<ExitStatus>,
+    waker: Option<Waker>,
+}
+
+impl Process {
+    /// Creates a new builder for the given program.
+    ///
+    /// The program is looked up in the `PATH` if it's not an absolute path.
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self {
+            program: program.as_ref().to_owned(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            env_clear: false,
+            cwd: None,
+            detached: false,
+            stdin: Stdio::default(),
+            stdout: Stdio::default(),
+            stderr: Stdio::default(),
+        }
+    }
+
+    /// Adds an argument to pass to the program.
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.args.push(arg.as_ref().to_owned());
+        self
+    }
+
+    /// Adds multiple arguments to pass to the program.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args.extend(args.into_iter().map(|arg| arg.as_ref().to_owned()));
+        self
+    }
+
+    /// Sets an environment variable for the child process.
+    pub fn env(
+        &mut self,
+        key: impl AsRef<OsStr>,
+        value: impl AsRef<OsStr>,
+    ) -> &mut Self {
+        self.env
+            .insert(key.as_ref().to_owned(), Some(value.as_ref().to_owned()));
+        self
+    }
+
+    /// Sets multiple environment variables for the child process.
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        for (key, value) in vars {
+            self.env(key, value);
+        }
+        self
+    }
+
+    /// Removes an environment variable from the ones inherited by the child
+    /// process.
+    pub fn env_remove(&mut self, key: impl AsRef<OsStr>) -> &mut Self {
+        self.env.insert(key.as_ref().to_owned(), None);
+        self
+    }
+
+    /// Clears all the environment variables, including the ones that'd be
+    /// inherited from Neovim.
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.env.clear();
+        self.env_clear = true;
+        self
+    }
+
+    /// Sets the working directory of the child process.
+    pub fn cwd(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.cwd = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Whether to spawn the child process in its own process group, making
+    /// it possible for it to keep running after Neovim exits.
+    pub fn detached(&mut self, detached: bool) -> &mut Self {
+        self.detached = detached;
+        self
+    }
+
+    /// Configures the child's stdin.
+    pub fn stdin(&mut self, stdin: Stdio) -> &mut Self {
+        self.stdin = stdin;
+        self
+    }
+
+    /// Configures the child's stdout.
+    pub fn stdout(&mut self, stdout: Stdio) -> &mut Self {
+        self.stdout = stdout;
+        self
+    }
+
+    /// Configures the child's stderr.
+    pub fn stderr(&mut self, stderr: Stdio) -> &mut Self {
+        self.stderr = stderr;
+        self
+    }
+
+    /// Spawns the process.
+    ///
+    /// NOTE: this function **must** be called from the main thread.
+    pub fn spawn(&self) -> Result<Child, Error> {
+        let file = to_cstring(&self.program)?;
+
+        let args = core::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|arg| to_cstring(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let env = self.build_env()?;
+
+        let cwd = self
+            .cwd
+            .as_ref()
+            .map(|cwd| to_cstring(cwd.as_os_str()))
+            .transpose()?;
+
+        let mut stdin = self.stdin.pipe()?;
+        let mut stdout = self.stdout.pipe()?;
+        let mut stderr = self.stderr.pipe()?;
+
+        let mut stdio = [
+            self.stdin.container(0, stdin.as_mut(), ffi::UV_READABLE_PIPE),
+            self.stdout.container(1, stdout.as_mut(), ffi::UV_WRITABLE_PIPE),
+            self.stderr.container(2, stderr.as_mut(), ffi::UV_WRITABLE_PIPE),
+        ];
+
+        let mut args = null_terminated(&args);
+        let mut env = env.as_deref().map(null_terminated);
+
+        let options = ffi::uv_process_options_t {
+            exit_cb: Some(exit_cb as _),
+            file: file.as_ptr(),
+            args: args.as_mut_ptr(),
+            env: env
+                .as_mut()
+                .map_or(core::ptr::null_mut(), |env| env.as_mut_ptr()),
+            cwd: cwd.as_ref().map_or(core::ptr::null(), |cwd| cwd.as_ptr()),
+            flags: if self.detached { ffi::UV_PROCESS_DETACHED } else { 0 },
+            stdio_count: stdio.len() as c_int,
+            stdio: stdio.as_mut_ptr(),
+            uid: 0,
+            gid: 0,
+        };
+
+        let mut handle = Handle::<ffi::uv_process_t, State>::alloc()?;
+
+        let state = State::default();
+        unsafe { handle.set_data(state.clone()) };
+
+        let retv = unsafe {
+            crate::with_loop(|uv_loop| {
+                ffi::uv_spawn(uv_loop, handle.as_mut_ptr(), &options)
+            })
+        };
+
+        if retv < 0 {
+            // The handle has to be closed even if the spawn failed.
+            unsafe { handle.close() };
+            return Err(Error::ProcessSpawn(ErrorCode::new(retv)));
+        }
+
+        let pid = unsafe { ffi::uv_process_get_pid(handle.as_ptr()) } as u32;
+
+        Ok(Child { stdin, stdout, stderr, handle, state, pid })
+    }
+
+    /// Returns the full environment of the child process, or `None` if it
+    /// should simply inherit Neovim's.
+    fn build_env(&self) -> Result<Option<Vec<CString>>, Error> {
+        if self.env.is_empty() && !self.env_clear {
+            return Ok(None);
+        }
+
+        let mut vars = if self.env_clear {
+            BTreeMap::new()
+        } else {
+            std::env::vars_os().collect::<BTreeMap<_, _>>()
+        };
+
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => vars.insert(key.clone(), value.clone()),
+                None => vars.remove(key),
+            };
+        }
+
+        vars.into_iter()
+            .map(|(key, value)| {
+                let mut var = key;
+                var.push("=");
+                var.push(value);
+                to_cstring(&var)
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+impl Stdio {
+    fn pipe(self) -> Result<Option<PipeHandle>, Error> {
+        match self {
+            Self::Piped => PipeHandle::new().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn container(
+        self,
+        fd: c_int,
+        pipe: Option<&mut PipeHandle>,
+        direction: ffi::uv_stdio_flags,
+    ) -> ffi::uv_stdio_container_t {
+        match (self, pipe) {
+            (Self::Piped, Some(pipe)) => ffi::uv_stdio_container_t {
+                flags: ffi::UV_CREATE_PIPE | direction,
+                data: ffi::uv_stdio_container_data {
+                    stream: pipe.as_stream_ptr(),
+                },
+            },
+
+            (Self::Inherit, _) => ffi::uv_stdio_container_t {
+                flags: ffi::UV_INHERIT_FD,
+                data: ffi::uv_stdio_container_data { fd },
+            },
+
+            _ => ffi::uv_stdio_container_t {
+                flags: ffi::UV_IGNORE,
+                data: ffi::uv_stdio_container_data { fd },
+            },
+        }
+    }
+}
+
+impl Child {
+    /// Returns the OS-assigned process identifier of the child.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Sends the given signal to the child process.
+    ///
+    /// Fails with `ESRCH` if the process has already exited.
+    pub fn kill(&mut self, signum: i32) -> Result<(), Error> {
+        if self.has_exited() {
+            return Err(Error::ProcessKill(ErrorCode::new(ffi::UV_ESRCH)));
+        }
+
+        let retv =
+            unsafe { ffi::uv_process_kill(self.handle.as_mut_ptr(), signum) };
+
+        if retv < 0 {
+            return Err(Error::ProcessKill(ErrorCode::new(retv)));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the exit status of the child if it has already exited,
+    /// without waiting.
+    pub fn try_wait(&self) -> Option<ExitStatus> {
+        self.state.borrow().status
+    }
+
+    /// Waits for the child to exit, returning its exit status.
+    ///
+    /// The child's stdin is not closed before waiting, so make sure to drop
+    /// it first if the child is reading from it until EOF.
+    pub async fn wait(&mut self) -> ExitStatus {
+        core::future::poll_fn(|cx| {
+            let state = &mut *self.state.borrow_mut();
+
+            match state.status {
+                Some(status) => Poll::Ready(status),
+
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                },
+            }
+        })
+        .await
+    }
+
+    /// Closes the process handle, executing `callback` on the main thread
+    /// once libuv is done with it.
+    ///
+    /// The process itself keeps running, but its exit status won't be
+    /// reported anymore. If the process has already exited its handle is
+    /// already closed, and `callback` is executed right away.
+    pub fn close<Cb>(mut self, callback: Cb)
+    where
+        Cb: FnOnce() + 'static,
+    {
+        if self.has_exited() {
+            callback();
+        } else {
+            unsafe { self.handle.close_with(Some(Box::new(callback))) }
+        }
+    }
+
+    /// Returns whether the process handle is active, i.e. whether the
+    /// process is still running.
+    pub fn is_active(&self) -> bool {
+        !self.has_exited() && self.handle.is_active()
+    }
+
+    /// Returns whether the process handle is closing or closed, which
+    /// happens automatically once the process exits.
+    pub fn is_closing(&self) -> bool {
+        self.has_exited() || self.handle.is_closing()
+    }
+
+    /// Makes the process handle keep the event loop alive until the
+    /// process exits. Handles are referenced by default.
+    pub fn ref_(&mut self) {
+        if !self.has_exited() {
+            self.handle.ref_()
+        }
+    }
+
+    /// Stops the process handle from keeping the event loop alive, e.g. so
+    /// that a long-running child doesn't prevent `nvim --headless` from
+    /// exiting.
+    pub fn unref(&mut self) {
+        if !self.has_exited() {
+            self.handle.unref()
+        }
+    }
+
+    /// Returns whether the process handle is referenced.
+    pub fn has_ref(&self) -> bool {
+        !self.has_exited() && self.handle.has_ref()
+    }
+
+    /// Returns whether the process has exited, in which case its handle
+    /// has already been closed and freed by [`exit_cb`].
+    fn has_exited(&self) -> bool {
+        self.state.borrow().status.is_some()
+    }
+}
+
+impl fmt::Debug for Child {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("pid", &self.pid)
+            .field("status", &self.try_wait())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ExitStatus {
+    /// Returns whether the process exited with a zero exit code and wasn't
+    /// terminated by a signal.
+    pub fn success(&self) -> bool {
+        self.code == 0 && self.signal == 0
+    }
+
+    /// Returns the exit code of the process.
+    pub fn code(&self) -> i64 {
+        self.code
+    }
+
+    /// Returns the number of the signal that terminated the process, if any.
+    pub fn signal(&self) -> Option<i32> {
+        (self.signal != 0).then_some(self.signal)
+    }
+}
+
+extern "C" fn exit_cb(
+    ptr: *mut ffi::uv_process_t,
+    exit_status: i64,
+    term_signal: c_int,
+) {
+    let mut handle: Handle<_, State> = unsafe { Handle::from_raw(ptr) };
+
+    let waker = {
+        let state = unsafe { &*handle.get_data() };
+        let state = &mut *state.borrow_mut();
+        state.status =
+            Some(ExitStatus { code: exit_status, signal: term_signal });
+        state.waker.take()
+    };
+
+    // The process handle is only needed until the process exits.
+    unsafe { handle.close() };
+
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+fn to_cstring(s: &OsStr) -> Result<CString, Error> {
+    utils::to_cstring(s)
+        .ok_or(Error::ProcessSpawn(ErrorCode::new(ffi::UV_EINVAL)))
+}
+
+/// Returns a null-terminated array of pointers to the given strings, which
+/// must outlive it.
+fn null_terminated(strings: &[CString]) -> Vec<*mut c_char> {
+    strings
+        .iter()
+        .map(|s| s.as_ptr() as *mut c_char)
+        .chain(core::iter::once(core::ptr::null_mut()))
+        .collect()
+}
@@ -0,0 +1,58 @@
+use core::cell::RefCell;
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// An unbounded queue used to turn the values passed to a handle's callback
+/// into a stream.
+pub(crate) struct Queue<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+struct Inner<T> {
+    items: VecDeque<T>,
+    waker: Option<Waker>,
+}
+
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        let inner = Inner { items: VecDeque::new(), waker: None };
+        Self { inner: Rc::new(RefCell::new(inner)) }
+    }
+}
+
+impl<T> Queue<T> {
+    /// Pushes a new item, waking up the task waiting on the queue.
+    pub(crate) fn push(&self, item: T) {
+        let waker = {
+            let inner = &mut *self.inner.borrow_mut();
+            inner.items.push_back(item);
+            inner.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Pops the oldest item, or registers the task to be woken up when the
+    /// next one is pushed.
+    pub(crate) fn poll_pop(&self, cx: &mut Context<'_>) -> Poll<T> {
+        let inner = &mut *self.inner.borrow_mut();
+
+        match inner.items.pop_front() {
+            Some(item) => Poll::Ready(item),
+
+            None => {
+                inner.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::alloc;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+
+use crate::{Error, ProperLayout, Result, ffi};
+
+/// A pointer to a libuv request of type `T` allocated by Rust, whose `data`
+/// field stores a `D`.
+///
+/// Unlike handles, requests are short-lived: they're created right before
+/// starting an operation, and they're freed in the operation's callback by
+/// calling [`Request::into_data`].
+pub(crate) struct Request<T: ProperLayout, D: 'static> {
+    ptr: *mut T,
+    data: PhantomData<D>,
+}
+
+impl<T: ProperLayout, D> Request<T, D> {
+    /// Allocates a new request storing the given data.
+    pub(crate) fn new(data: D) -> Result<Self> {
+        let ptr = unsafe { alloc::alloc_zeroed(T::layout()) as *mut T };
+
+        if ptr.is_null() {
+            return Err(Error::HandleMemAlloc);
+        }
+
+        let data = Box::into_raw(Box::new(data));
+
+        unsafe {
+            ffi::uv_req_set_data(
+                ptr as *mut ffi::uv_req_t,
+                data as *mut c_void,
+            )
+        };
+
+        Ok(Self { ptr, data: PhantomData })
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
+    }
+
+    pub(crate) unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self { ptr, data: PhantomData }
+    }
+
+    /// Returns a mutable reference to the data stored in the request.
+    pub(crate) unsafe fn data_mut(&mut self) -> &mut D {
+        &mut *(ffi::uv_req_get_data(self.ptr as *const ffi::uv_req_t)
+            as *mut D)
+    }
+
+    /// Frees the request, returning the data stored in it.
+    ///
+    /// This should be called from the request's callback, or right after
+    /// failing to submit the request.
+    pub(crate) unsafe fn into_data(self) -> D {
+        let data =
+            ffi::uv_req_get_data(self.ptr as *const ffi::uv_req_t) as *mut D;
+
+        let data = *Box::from_raw(data);
+
+        alloc::dealloc(self.ptr as *mut u8, T::layout());
+
+        data
+    }
+}
@@ -0,0 +1,138 @@
+use core::ffi::c_int;
+use core::mem::ManuallyDrop;
+use std::error::Error as StdError;
+
+use crate::callback::{self, HandleKind};
+use crate::error::ErrorCode;
+use crate::handle::impl_lifecycle;
+use crate::{Error, Handle, IntoResult, ffi};
+
+pub(crate) type Callback = Box<
+    dyn FnMut(&mut SignalHandle, i32) -> Result<(), Box<dyn StdError>>
+        + 'static,
+>;
+
+/// Binding to libuv's [Signal handle][1] used to handle Unix signals on the
+/// Neovim thread.
+///
+/// Unlike process-wide signal handlers installed via `sigaction(2)`, signal
+/// handles don't interfere with Neovim's own handlers: libuv dispatches every
+/// signal to all the handles watching it.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/signal.html
+pub struct SignalHandle {
+    handle: Handle<ffi::uv_signal_t, Callback>,
+}
+
+impl_lifecycle!(SignalHandle, handle);
+
+impl SignalHandle {
+    fn new() -> Result<Self, Error> {
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_signal_init(uv_loop, handle.as_mut_ptr())
+        })?;
+
+        Ok(Self { handle })
+    }
+
+    /// Executes a callback every time the process receives the signal
+    /// `signum`.
+    pub fn start<Cb, R>(signum: i32, callback: Cb) -> Result<Self, Error>
+    where
+        Cb: FnMut(&mut Self, i32) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        Self::start_inner(signum, callback, ffi::uv_signal_start)
+    }
+
+    /// Same as [`start()`](SignalHandle::start) but accepts a closure that
+    /// will be called once, after which the handle is automatically stopped.
+    pub fn start_oneshot<Cb, R>(
+        signum: i32,
+        callback: Cb,
+    ) -> Result<Self, Error>
+    where
+        Cb: FnOnce(i32) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        let mut callback = Some(callback);
+
+        Self::start_inner(
+            signum,
+            move |_, signum| callback.take().unwrap()(signum),
+            ffi::uv_signal_start_oneshot,
+        )
+    }
+
+    fn start_inner<Cb, R>(
+        signum: i32,
+        mut callback: Cb,
+        start: unsafe extern "C" fn(
+            *mut ffi::uv_signal_t,
+            ffi::uv_signal_cb,
+            c_int,
+        ) -> c_int,
+    ) -> Result<Self, Error>
+    where
+        Cb: FnMut(&mut Self, i32) -> R + 'static,
+        R: IntoResult<()>,
+        R::Error: StdError + 'static,
+    {
+        let mut signal = Self::new()?;
+
+        let callback: Callback = Box::new(move |signal, signum| {
+            // Type erase the callback by boxing its error.
+            callback(signal, signum)
+                .into_result()
+                .map_err(|err| Box::new(err) as Box<dyn StdError>)
+        });
+
+        unsafe { signal.handle.set_data(callback) };
+
+        let retv = unsafe {
+            start(signal.handle.as_mut_ptr(), Some(signal_cb as _), signum)
+        };
+
+        if retv < 0 {
+            return Err(Error::SignalStart(ErrorCode::new(retv)));
+        }
+
+        Ok(signal)
+    }
+
+    /// Stops watching for the signal. The callback will not be called
+    /// anymore.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let retv = unsafe { ffi::uv_signal_stop(self.handle.as_mut_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::SignalStop);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SignalHandle {
+    fn drop(&mut self) {
+        unsafe { self.handle.drop_owned() };
+    }
+}
+
+extern "C" fn signal_cb(ptr: *mut ffi::uv_signal_t, signum: c_int) {
+    let handle: Handle<_, Callback> = unsafe { Handle::from_raw(ptr) };
+
+    let callback = unsafe { handle.get_data() };
+
+    if !callback.is_null() {
+        // The handle is owned by someone else, so we can't drop it.
+        let mut handle = ManuallyDrop::new(SignalHandle { handle });
+        let callback = unsafe { &mut *callback };
+
+        callback::call(HandleKind::Signal, || callback(&mut handle, signum));
+
+        unsafe { handle.handle.close_if_detached() };
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, SystemTime};
+
+use crate::ffi;
+
+const S_IFMT: u64 = 0o170000;
+const S_IFDIR: u64 = 0o040000;
+const S_IFREG: u64 = 0o100000;
+const S_IFLNK: u64 = 0o120000;
+
+/// Information about a file, as returned by libuv's `stat` family of
+/// functions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FileStat {
+    dev: u64,
+    ino: u64,
+    mode: u64,
+    nlink: u64,
+    uid: u64,
+    gid: u64,
+    size: u64,
+    accessed: SystemTime,
+    modified: SystemTime,
+    changed: SystemTime,
+    created: SystemTime,
+}
+
+impl FileStat {
+    /// Returns the ID of the device containing the file.
+    pub fn dev(&self) -> u64 {
+        self.dev
+    }
+
+    /// Returns the inode number of the file.
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    /// Returns the file's type and permission bits.
+    pub fn mode(&self) -> u64 {
+        self.mode
+    }
+
+    /// Returns the number of hard links to the file.
+    pub fn nlink(&self) -> u64 {
+        self.nlink
+    }
+
+    /// Returns the user ID of the file's owner.
+    pub fn uid(&self) -> u64 {
+        self.uid
+    }
+
+    /// Returns the group ID of the file's owner.
+    pub fn gid(&self) -> u64 {
+        self.gid
+    }
+
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the last access time of the file.
+    pub fn accessed(&self) -> SystemTime {
+        self.accessed
+    }
+
+    /// Returns the last modification time of the file's contents.
+    pub fn modified(&self) -> SystemTime {
+        self.modified
+    }
+
+    /// Returns the last time the file's metadata was changed.
+    pub fn changed(&self) -> SystemTime {
+        self.changed
+    }
+
+    /// Returns the creation time of the file. On platforms that don't track
+    /// it this is the Unix epoch.
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Returns whether the file is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.mode & S_IFMT == S_IFDIR
+    }
+
+    /// Returns whether the file is a regular file.
+    pub fn is_file(&self) -> bool {
+        self.mode & S_IFMT == S_IFREG
+    }
+
+    /// Returns whether the file is a symbolic link.
+    pub fn is_symlink(&self) -> bool {
+        self.mode & S_IFMT == S_IFLNK
+    }
+}
+
+impl From<&ffi::uv_stat_t> for FileStat {
+    fn from(stat: &ffi::uv_stat_t) -> Self {
+        Self {
+            dev: stat.st_dev,
+            ino: stat.st_ino,
+            mode: stat.st_mode,
+            nlink: stat.st_nlink,
+            uid: stat.st_uid,
+            gid: stat.st_gid,
+            size: stat.st_size,
+            accessed: to_system_time(stat.st_atim),
+            modified: to_system_time(stat.st_mtim),
+            changed: to_system_time(stat.st_ctim),
+            created: to_system_time(stat.st_birthtim),
+        }
+    }
+}
+
+// `c_long` is 32 bits wide on Windows.
+#[allow(clippy::unnecessary_cast)]
+fn to_system_time(time: ffi::uv_timespec_t) -> SystemTime {
+    let since_epoch =
+        Duration::new(time.tv_sec.unsigned_abs() as u64, time.tv_nsec as u32);
+
+    if time.tv_sec >= 0 {
+        SystemTime::UNIX_EPOCH + since_epoch
+    } else {
+        SystemTime::UNIX_EPOCH - since_epoch
+    }
+}
@@ -0,0 +1,456 @@
+//! Shared implementation of libuv's [stream handles][1], used by pipes, TCP
+//! sockets and the stdio of child processes.
+//!
+//! [1]: http://docs.libuv.org/en/v1.x/stream.html
+
+use core::cell::RefCell;
+use core::ffi::{c_char, c_int};
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::error::ErrorCode;
+use crate::request::Request;
+use crate::{Error, Handle, ProperLayout, ffi, oneshot};
+
+/// The number of buffered bytes above which we stop reading from the stream
+/// until some of them are consumed.
+const MAX_BUFFERED: usize = 64 * 1024;
+
+pub(crate) type State = Rc<RefCell<ReadState>>;
+
+/// A libuv stream handle of type `T`.
+///
+/// Reading is only started the first time the stream is polled, and it's
+/// paused while more than [`MAX_BUFFERED`] bytes are waiting to be consumed.
+/// The handle is closed when the `StreamHandle` is dropped.
+pub(crate) struct StreamHandle<T: ProperLayout> {
+    pub(crate) handle: Handle<T, State>,
+    state: State,
+}
+
+#[derive(Default)]
+pub(crate) struct ReadState {
+    /// The chunks that have been read but not yet consumed.
+    chunks: VecDeque<Vec<u8>>,
+
+    /// The total number of bytes in `chunks`.
+    buffered: usize,
+
+    /// The buffer libuv reads into, reused across reads.
+    buffer: Vec<u8>,
+
+    error: Option<ErrorCode>,
+    is_eof: bool,
+    is_reading: bool,
+    waker: Option<Waker>,
+}
+
+struct WriteData {
+    bytes: Vec<u8>,
+    sender: oneshot::Sender<()>,
+}
+
+impl<T: ProperLayout> StreamHandle<T> {
+    /// Takes ownership of an initialized stream handle.
+    pub(crate) fn new(mut handle: Handle<T, State>) -> Self {
+        let state = State::default();
+        unsafe { handle.set_data(state.clone()) };
+        Self { handle, state }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const T {
+        self.handle.as_ptr()
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut T {
+        self.handle.as_mut_ptr()
+    }
+
+    fn as_stream_ptr(&mut self) -> *mut ffi::uv_stream_t {
+        self.handle.as_mut_ptr() as *mut ffi::uv_stream_t
+    }
+
+    /// Polls for the next chunk of bytes read from the stream, returning
+    /// `None` once the other end has been closed.
+    pub(crate) fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Vec<u8>, Error>>> {
+        let ptr = self.as_stream_ptr();
+        let state = &mut *self.state.borrow_mut();
+
+        if let Some(chunk) = state.chunks.pop_front() {
+            state.buffered -= chunk.len();
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+
+        if let Some(code) = state.error.take() {
+            return Poll::Ready(Some(Err(Error::StreamRead(code))));
+        }
+
+        if state.is_eof {
+            return Poll::Ready(None);
+        }
+
+        if !state.is_reading {
+            let retv = unsafe {
+                ffi::uv_read_start(
+                    ptr,
+                    Some(alloc_cb as _),
+                    Some(read_cb as _),
+                )
+            };
+
+            if retv < 0 {
+                state.is_eof = true;
+                let code = ErrorCode::new(retv);
+                return Poll::Ready(Some(Err(Error::StreamRead(code))));
+            }
+
+            state.is_reading = true;
+        }
+
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Reads the next chunk of bytes from the stream, returning `None` once
+    /// the other end has been closed.
+    pub(crate) async fn read(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        core::future::poll_fn(|cx| self.poll_read(cx)).await.transpose()
+    }
+
+    /// Reads from the stream until the other end is closed.
+    pub(crate) async fn read_to_end(&mut self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+
+        while let Some(chunk) = self.read().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Starts connecting the stream by calling `connect` with a new connect
+    /// request, the handle and the callback to pass to libuv, returning a
+    /// receiver that resolves once the connection is established.
+    pub(crate) fn connect<F>(&mut self, connect: F) -> oneshot::Receiver<()>
+    where
+        F: FnOnce(*mut ffi::uv_connect_t, *mut T, ffi::uv_connect_cb) -> c_int,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        let mut req = match Request::<ffi::uv_connect_t, _>::new(sender) {
+            Ok(req) => req,
+            Err(err) => return oneshot::failed(err),
+        };
+
+        let retv = connect(
+            req.as_mut_ptr(),
+            self.handle.as_mut_ptr(),
+            Some(connect_cb as _),
+        );
+
+        if retv < 0 {
+            let sender = unsafe { req.into_data() };
+            sender.fail(Error::StreamConnect(ErrorCode::new(retv)));
+        }
+
+        receiver
+    }
+
+    /// Queues `bytes` to be written to the stream, returning a receiver that
+    /// resolves once they've all been written.
+    pub(crate) fn write(&mut self, bytes: Vec<u8>) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+
+        let mut req = match Request::<ffi::uv_write_t, _>::new(WriteData {
+            bytes,
+            sender,
+        }) {
+            Ok(req) => req,
+            Err(err) => return oneshot::failed(err),
+        };
+
+        let buf = {
+            let bytes = unsafe { &mut req.data_mut().bytes };
+            ffi::uv_buf_t {
+                base: bytes.as_mut_ptr() as *mut c_char,
+                len: bytes.len(),
+            }
+        };
+
+        let retv = unsafe {
+            ffi::uv_write(
+                req.as_mut_ptr(),
+                self.as_stream_ptr(),
+                &buf,
+                1,
+                Some(write_cb as _),
+            )
+        };
+
+        if retv < 0 {
+            let WriteData { sender, .. } = unsafe { req.into_data() };
+            sender.fail(Error::StreamWrite(ErrorCode::new(retv)));
+        }
+
+        receiver
+    }
+
+    /// Shuts down the write side of the stream once all the pending writes
+    /// have completed.
+    pub(crate) fn shutdown(&mut self) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+
+        let mut req = match Request::<ffi::uv_shutdown_t, _>::new(sender) {
+            Ok(req) => req,
+            Err(err) => return oneshot::failed(err),
+        };
+
+        let retv = unsafe {
+            ffi::uv_shutdown(
+                req.as_mut_ptr(),
+                self.as_stream_ptr(),
+                Some(shutdown_cb as _),
+            )
+        };
+
+        if retv < 0 {
+            let sender = unsafe { req.into_data() };
+            sender.fail(Error::StreamShutdown(ErrorCode::new(retv)));
+        }
+
+        receiver
+    }
+
+    /// Returns the number of bytes queued for writing.
+    pub(crate) fn write_queue_size(&self) -> usize {
+        unsafe {
+            ffi::uv_stream_get_write_queue_size(
+                self.handle.as_ptr() as *const ffi::uv_stream_t
+            )
+        }
+    }
+}
+
+impl<T: ProperLayout> Drop for StreamHandle<T> {
+    fn drop(&mut self) {
+        unsafe { self.handle.close() };
+    }
+}
+
+unsafe fn get_state<'a>(ptr: *const ffi::uv_handle_t) -> &'a State {
+    let handle: Handle<_, State> =
+        Handle::from_raw(ptr as *mut ffi::uv_handle_t);
+    &*handle.get_data()
+}
+
+extern "C" fn alloc_cb(
+    ptr: *mut ffi::uv_handle_t,
+    suggested_size: usize,
+    buf: *mut ffi::uv_buf_t,
+) {
+    let state = &mut *unsafe { get_state(ptr) }.borrow_mut();
+
+    if state.buffer.len() < suggested_size {
+        state.buffer.resize(suggested_size, 0);
+    }
+
+    unsafe {
+        (*buf).base = state.buffer.as_mut_ptr() as *mut c_char;
+        (*buf).len = state.buffer.len();
+    }
+}
+
+extern "C" fn read_cb(
+    ptr: *mut ffi::uv_stream_t,
+    nread: isize,
+    _buf: *const ffi::uv_buf_t,
+) {
+    let waker = {
+        let state = &mut *unsafe { get_state(ptr as _) }.borrow_mut();
+
+        if nread > 0 {
+            let chunk = state.buffer[..nread as usize].to_vec();
+            state.buffered += chunk.len();
+            state.chunks.push_back(chunk);
+        } else if nread < 0 {
+            if nread as c_int != ffi::UV_EOF {
+                state.error = Some(ErrorCode::new(nread as c_int));
+            }
+            state.is_eof = true;
+        }
+
+        if state.is_eof || state.buffered > MAX_BUFFERED {
+            unsafe { ffi::uv_read_stop(ptr) };
+            state.is_reading = false;
+        }
+
+        state.waker.take()
+    };
+
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+extern "C" fn write_cb(ptr: *mut ffi::uv_write_t, status: c_int) {
+    let req = unsafe { Request::<_, WriteData>::from_raw(ptr) };
+    let WriteData { sender, .. } = unsafe { req.into_data() };
+
+    if status < 0 {
+        sender.fail(Error::StreamWrite(ErrorCode::new(status)));
+    } else {
+        sender.send(());
+    }
+}
+
+extern "C" fn connect_cb(ptr: *mut ffi::uv_connect_t, status: c_int) {
+    let req = unsafe { Request::<_, oneshot::Sender<()>>::from_raw(ptr) };
+    let sender = unsafe { req.into_data() };
+
+    if status < 0 {
+        sender.fail(Error::StreamConnect(ErrorCode::new(status)));
+    } else {
+        sender.send(());
+    }
+}
+
+extern "C" fn shutdown_cb(ptr: *mut ffi::uv_shutdown_t, status: c_int) {
+    let req = unsafe { Request::<_, oneshot::Sender<()>>::from_raw(ptr) };
+    let sender = unsafe { req.into_data() };
+
+    if status < 0 {
+        sender.fail(Error::StreamShutdown(ErrorCode::new(status)));
+    } else {
+        sender.send(());
+    }
+}
+
+pub(crate) type ListenState = Rc<RefCell<Incoming>>;
+
+/// A libuv stream handle of type `T` listening for incoming connections.
+///
+/// The handle is closed when the `ListenerHandle` is dropped.
+pub(crate) struct ListenerHandle<T: ProperLayout> {
+    pub(crate) handle: Handle<T, ListenState>,
+    state: ListenState,
+}
+
+#[derive(Default)]
+pub(crate) struct Incoming {
+    /// The number of connections that are ready to be accepted.
+    pending: usize,
+    error: Option<ErrorCode>,
+    waker: Option<Waker>,
+}
+
+impl<T: ProperLayout> ListenerHandle<T> {
+    /// Takes ownership of an initialized stream handle.
+    pub(crate) fn new(mut handle: Handle<T, ListenState>) -> Self {
+        let state = ListenState::default();
+        unsafe { handle.set_data(state.clone()) };
+        Self { handle, state }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const T {
+        self.handle.as_ptr()
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut T {
+        self.handle.as_mut_ptr()
+    }
+
+    /// Starts listening for incoming connections.
+    pub(crate) fn listen(&mut self, backlog: u32) -> Result<(), Error> {
+        let retv = unsafe {
+            ffi::uv_listen(
+                self.handle.as_mut_ptr() as *mut ffi::uv_stream_t,
+                backlog.min(c_int::MAX as u32) as c_int,
+                Some(connection_cb as _),
+            )
+        };
+
+        if retv < 0 {
+            return Err(Error::StreamListen(ErrorCode::new(retv)));
+        }
+
+        Ok(())
+    }
+
+    /// Polls for the next incoming connection, accepting it into the stream
+    /// handle returned by `init`.
+    pub(crate) fn poll_accept<I>(
+        &mut self,
+        cx: &mut Context<'_>,
+        init: I,
+    ) -> Poll<Result<StreamHandle<T>, Error>>
+    where
+        I: FnOnce() -> Result<StreamHandle<T>, Error>,
+    {
+        let server = self.handle.as_mut_ptr() as *mut ffi::uv_stream_t;
+        let state = &mut *self.state.borrow_mut();
+
+        if let Some(code) = state.error.take() {
+            return Poll::Ready(Err(Error::StreamAccept(code)));
+        }
+
+        if state.pending == 0 {
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let mut client = match init() {
+            Ok(client) => client,
+            Err(err) => return Poll::Ready(Err(err)),
+        };
+
+        let retv = unsafe {
+            ffi::uv_accept(
+                server,
+                client.as_mut_ptr() as *mut ffi::uv_stream_t,
+            )
+        };
+
+        if retv < 0 {
+            return Poll::Ready(Err(Error::StreamAccept(ErrorCode::new(
+                retv,
+            ))));
+        }
+
+        // Only count the connection as accepted once it's been moved into
+        // the client, so that a failed attempt can be retried.
+        state.pending -= 1;
+
+        Poll::Ready(Ok(client))
+    }
+}
+
+impl<T: ProperLayout> Drop for ListenerHandle<T> {
+    fn drop(&mut self) {
+        unsafe { self.handle.close() };
+    }
+}
+
+extern "C" fn connection_cb(ptr: *mut ffi::uv_stream_t, status: c_int) {
+    let handle: Handle<_, ListenState> = unsafe { Handle::from_raw(ptr) };
+    let state = unsafe { &*handle.get_data() };
+
+    let waker = {
+        let state = &mut *state.borrow_mut();
+
+        if status < 0 {
+            state.error = Some(ErrorCode::new(status));
+        } else {
+            state.pending += 1;
+        }
+
+        state.waker.take()
+    };
+
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
@@ -0,0 +1,299 @@
+use core::ffi::{CStr, c_char, c_int};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::ffi::CString;
+use std::net::{IpAddr, SocketAddr, SocketAddrV6};
+
+use futures_core::Stream;
+
+use crate::error::ErrorCode;
+use crate::handle::impl_lifecycle;
+use crate::stream::{ListenerHandle, StreamHandle};
+use crate::{Error, Handle, ffi};
+
+/// The default number of pending connections a [`TcpListener`] can queue.
+const BACKLOG: u32 = 128;
+
+type GetName = unsafe extern "C" fn(
+    *const ffi::uv_tcp_t,
+    *mut ffi::sockaddr,
+    *mut c_int,
+) -> c_int;
+
+/// Binding to libuv's [TCP handle][1] used to communicate over TCP sockets.
+///
+/// A `TcpHandle` is a [`Stream`] of the byte chunks read from it. Reading
+/// pauses when too many chunks are waiting to be consumed, and resumes once
+/// the stream is polled again.
+///
+/// The socket is closed when the handle is dropped.
+///
+/// [1]: http://docs.libuv.org/en/v1.x/tcp.html
+pub struct TcpHandle {
+    stream: StreamHandle<ffi::uv_tcp_t>,
+}
+
+/// A TCP socket listening for incoming connections.
+///
+/// A `TcpListener` is a [`Stream`] of the accepted connections. It stops
+/// listening when it's dropped.
+pub struct TcpListener {
+    listener: ListenerHandle<ffi::uv_tcp_t>,
+}
+
+impl_lifecycle!(TcpHandle, stream.handle);
+impl_lifecycle!(TcpListener, listener.handle);
+
+impl TcpHandle {
+    /// Opens a TCP connection to the given address.
+    pub async fn connect(addr: SocketAddr) -> Result<Self, Error> {
+        let addr = to_sockaddr(addr).map_err(Error::StreamConnect)?;
+
+        let mut tcp = Self { stream: init()? };
+
+        tcp.stream
+            .connect(|req, handle, cb| unsafe {
+                ffi::uv_tcp_connect(req, handle, addr.as_ptr(), cb)
+            })
+            .await?;
+
+        Ok(tcp)
+    }
+
+    /// Returns the local address of the socket.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        get_name(self.stream.as_ptr(), ffi::uv_tcp_getsockname)
+    }
+
+    /// Returns the address of the remote peer of the socket.
+    pub fn peer_addr(&self) -> Result<SocketAddr, Error> {
+        get_name(self.stream.as_ptr(), ffi::uv_tcp_getpeername)
+    }
+
+    /// Enables or disables Nagle's algorithm.
+    pub fn set_nodelay(&mut self, nodelay: bool) -> Result<(), Error> {
+        let retv = unsafe {
+            ffi::uv_tcp_nodelay(self.stream.as_mut_ptr(), nodelay as c_int)
+        };
+
+        if retv < 0 {
+            return Err(Error::SocketOption(ErrorCode::new(retv)));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the next chunk of bytes from the socket, returning `None` once
+    /// the other end has been closed.
+    pub async fn read(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.stream.read().await
+    }
+
+    /// Reads from the socket until the other end is closed, returning all
+    /// the bytes that were read.
+    pub async fn read_to_end(&mut self) -> Result<Vec<u8>, Error> {
+        self.stream.read_to_end().await
+    }
+
+    /// Writes `bytes` to the socket, completing once they've all been
+    /// written.
+    ///
+    /// Awaiting the returned future before issuing the next write ensures
+    /// that the socket's write queue doesn't grow unbounded if the other end
+    /// can't keep up.
+    pub async fn write(
+        &mut self,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.stream.write(bytes.into()).await
+    }
+
+    /// Shuts down the write side of the socket after all the pending writes
+    /// have completed, signaling EOF to the other end.
+    pub async fn shutdown(&mut self) -> Result<(), Error> {
+        self.stream.shutdown().await
+    }
+
+    /// Returns the number of bytes that are queued for writing.
+    pub fn write_queue_size(&self) -> usize {
+        self.stream.write_queue_size()
+    }
+}
+
+impl Stream for TcpHandle {
+    type Item = Result<Vec<u8>, Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().stream.poll_read(cx)
+    }
+}
+
+impl TcpListener {
+    /// Creates a TCP socket bound to the given address, and starts listening
+    /// for incoming connections on it.
+    ///
+    /// Binding to port 0 lets the OS pick a free port, which can then be
+    /// retrieved with [`local_addr`](Self::local_addr).
+    pub fn bind(addr: SocketAddr) -> Result<Self, Error> {
+        let addr = to_sockaddr(addr).map_err(Error::StreamBind)?;
+
+        let handle = Handle::new(|uv_loop, handle| unsafe {
+            ffi::uv_tcp_init(uv_loop, handle.as_mut_ptr())
+        })?;
+
+        let mut listener = ListenerHandle::new(handle);
+
+        let retv = unsafe {
+            ffi::uv_tcp_bind(listener.as_mut_ptr(), addr.as_ptr(), 0)
+        };
+
+        if retv < 0 {
+            return Err(Error::StreamBind(ErrorCode::new(retv)));
+        }
+
+        listener.listen(BACKLOG)?;
+
+        Ok(Self { listener })
+    }
+
+    /// Returns the local address the listener is bound to.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        get_name(self.listener.as_ptr(), ffi::uv_tcp_getsockname)
+    }
+
+    /// Waits for the next incoming connection.
+    pub async fn accept(&mut self) -> Result<TcpHandle, Error> {
+        core::future::poll_fn(|cx| self.poll_accept(cx)).await
+    }
+
+    /// Polls for the next incoming connection.
+    pub fn poll_accept(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<TcpHandle, Error>> {
+        self.listener
+            .poll_accept(cx, init)
+            .map_ok(|stream| TcpHandle { stream })
+    }
+}
+
+impl Stream for TcpListener {
+    type Item = Result<TcpHandle, Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_accept(cx).map(Some)
+    }
+}
+
+fn init() -> Result<StreamHandle<ffi::uv_tcp_t>, Error> {
+    let handle = Handle::new(|uv_loop, handle| unsafe {
+        ffi::uv_tcp_init(uv_loop, handle.as_mut_ptr())
+    })?;
+
+    Ok(StreamHandle::new(handle))
+}
+
+impl ffi::sockaddr_storage {
+    fn as_ptr(&self) -> *const ffi::sockaddr {
+        (self as *const Self).cast()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut ffi::sockaddr {
+        (self as *mut Self).cast()
+    }
+}
+
+/// The offset of the `sin6_flowinfo` field of a `sockaddr_in6`, which is the
+/// same on all platforms.
+const FLOWINFO_OFFSET: usize = 4;
+
+/// The offset of the `sin6_scope_id` field of a `sockaddr_in6`, which is the
+/// same on all platforms.
+const SCOPE_ID_OFFSET: usize = 24;
+
+fn to_sockaddr(addr: SocketAddr) -> Result<ffi::sockaddr_storage, ErrorCode> {
+    let mut storage = ffi::sockaddr_storage([0; 128]);
+
+    let ip = CString::new(addr.ip().to_string()).expect("no nul bytes");
+    let port = addr.port() as c_int;
+
+    let retv = unsafe {
+        match addr {
+            SocketAddr::V4(_) => {
+                ffi::uv_ip4_addr(ip.as_ptr(), port, storage.as_mut_ptr())
+            },
+            SocketAddr::V6(_) => {
+                ffi::uv_ip6_addr(ip.as_ptr(), port, storage.as_mut_ptr())
+            },
+        }
+    };
+
+    if retv < 0 {
+        return Err(ErrorCode::new(retv));
+    }
+
+    // `uv_ip6_addr` only parses the address, so the flow info and the scope
+    // id (needed to connect to link-local addresses) are set manually.
+    if let SocketAddr::V6(addr) = addr {
+        storage.0[FLOWINFO_OFFSET..FLOWINFO_OFFSET + 4]
+            .copy_from_slice(&addr.flowinfo().to_ne_bytes());
+        storage.0[SCOPE_ID_OFFSET..SCOPE_ID_OFFSET + 4]
+            .copy_from_slice(&addr.scope_id().to_ne_bytes());
+    }
+
+    Ok(storage)
+}
+
+fn get_name(
+    handle: *const ffi::uv_tcp_t,
+    get_name: GetName,
+) -> Result<SocketAddr, Error> {
+    let mut storage = ffi::sockaddr_storage([0; 128]);
+    let mut len = size_of::<ffi::sockaddr_storage>() as c_int;
+
+    let retv = unsafe { get_name(handle, storage.as_mut_ptr(), &mut len) };
+
+    if retv < 0 {
+        return Err(Error::SocketAddr(ErrorCode::new(retv)));
+    }
+
+    let mut name = [0 as c_char; 64];
+
+    let retv = unsafe {
+        ffi::uv_ip_name(storage.as_ptr(), name.as_mut_ptr(), name.len())
+    };
+
+    if retv < 0 {
+        return Err(Error::SocketAddr(ErrorCode::new(retv)));
+    }
+
+    let ip = unsafe { CStr::from_ptr(name.as_ptr()) }
+        .to_str()
+        .ok()
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .ok_or(Error::SocketAddr(ErrorCode::new(ffi::UV_EINVAL)))?;
+
+    // Both `sockaddr_in` and `sockaddr_in6` store the port in network byte
+    // order right after the address family.
+    let port = u16::from_be_bytes([storage.0[2], storage.0[3]]);
+
+    let IpAddr::V6(ip) = ip else {
+        return Ok(SocketAddr::new(ip, port));
+    };
+
+    let read_u32 = |offset: usize| {
+        let bytes = storage.0[offset..offset + 4].try_into().unwrap();
+        u32::from_ne_bytes(bytes)
+    };
+
+    let flowinfo = read_u32(FLOWINFO_OFFSET);
+    let scope_id = read_u32(SCOPE_ID_OFFSET);
+
+    Ok(SocketAddrV6::new(ip, port, flowinfo, scope_id).into())
+}
@@ -0,0 +1,256 @@
+//! Futures and streams built on top of [`TimerHandle`]s.
+//!
+//! All the types in this module are driven by the Neovim event loop, so they
+//! can only be created and polled on the main thread, e.g. from within a task
+//! spawned with [`spawn`](crate::spawn).
+
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use crate::{Error, TimerHandle};
+
+/// Returns a future that completes after `duration` has elapsed.
+///
+/// The future resolves to an error if the underlying timer couldn't be
+/// started.
+///
+/// # Examples
+///
+/// ```ignore
+/// use std::time::Duration;
+///
+/// use nvim_oxi::libuv;
+///
+/// nvim_oxi::spawn(async {
+///     libuv::sleep(Duration::from_millis(500)).await?;
+///     nvim_oxi::print!("Half a second later..");
+///     Ok::<_, libuv::Error>(())
+/// });
+/// ```
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep { timer: Timer::start(duration, Duration::ZERO, true) }
+}
+
+/// Returns a stream that yields immediately, and then once every `period`.
+///
+/// If the stream is not polled for longer than `period` the missed ticks are
+/// coalesced into a single one. Every tick is an error if the underlying
+/// timer couldn't be started.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+pub fn interval(period: Duration) -> Interval {
+    assert!(!period.is_zero(), "`period` must be non-zero");
+    Interval { timer: Timer::start(Duration::ZERO, period, false), period }
+}
+
+/// Requires a future to complete before `duration` has elapsed, resolving to
+/// [`Error::Timeout`] if it doesn't, or to the error returned by [`sleep`]
+/// if the timer couldn't be started.
+///
+/// The inner future is dropped without being polled again if the timeout
+/// elapses first.
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout { future, sleep: sleep(duration) }
+}
+
+/// The future returned by [`sleep`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep {
+    timer: Timer,
+}
+
+impl Sleep {
+    /// Returns how long until the sleep completes.
+    pub fn remaining(&self) -> Duration {
+        self.timer.handle.as_ref().map_or(Duration::ZERO, |h| h.get_due_in())
+    }
+
+    /// Resets the sleep so that it completes after `duration` from now,
+    /// regardless of whether it had already completed.
+    ///
+    /// This can be used to debounce events by pushing the deadline forward
+    /// every time a new event comes in.
+    pub fn reset(&mut self, duration: Duration) {
+        let Some(handle) = &mut self.timer.handle else { return };
+
+        let shared = &mut *self.timer.shared.borrow_mut();
+        shared.ticks = 0;
+
+        if let Err(err) = handle.restart(duration, Duration::ZERO) {
+            shared.error = Some(err);
+        }
+    }
+
+    /// Returns whether the sleep has completed.
+    pub fn is_elapsed(&self) -> bool {
+        self.timer.shared.borrow().ticks > 0
+    }
+}
+
+impl Future for Sleep {
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.timer.poll_tick(cx, false)
+    }
+}
+
+/// The stream returned by [`interval`].
+#[must_use = "streams do nothing unless polled"]
+pub struct Interval {
+    timer: Timer,
+    period: Duration,
+}
+
+impl Interval {
+    /// Completes when the next tick is reached.
+    pub async fn tick(&mut self) -> Result<(), Error> {
+        core::future::poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Polls for the next tick.
+    pub fn poll_tick(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Error>> {
+        self.timer.poll_tick(cx, true)
+    }
+
+    /// Returns the period of the interval.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+impl Stream for Interval {
+    type Item = Result<(), Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+}
+
+/// The future returned by [`timeout`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+impl<F> Timeout<F> {
+    /// Consumes the `Timeout`, returning the inner future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the inner future is never moved out of the pinned `Timeout`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+
+        Pin::new(&mut this.sleep)
+            .poll(cx)
+            .map(|res| res.and(Err(Error::Timeout)))
+    }
+}
+
+/// A [`TimerHandle`] whose callback counts the number of times it's fired
+/// and wakes up the task waiting on it.
+struct Timer {
+    /// `None` if the timer couldn't be created.
+    handle: Option<TimerHandle>,
+    shared: Rc<RefCell<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    ticks: u64,
+    waker: Option<Waker>,
+
+    /// The error returned when starting the timer, if it failed.
+    error: Option<Error>,
+}
+
+impl Timer {
+    fn start(timeout: Duration, repeat: Duration, is_oneshot: bool) -> Self {
+        let shared = Rc::new(RefCell::new(Shared::default()));
+
+        let callback = {
+            let shared = shared.clone();
+            move |timer: &mut TimerHandle| {
+                if is_oneshot {
+                    let _ = timer.stop();
+                }
+
+                let waker = {
+                    let shared = &mut *shared.borrow_mut();
+                    shared.ticks += 1;
+                    shared.waker.take()
+                };
+
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+        };
+
+        let handle = match TimerHandle::start(timeout, repeat, callback) {
+            Ok(handle) => Some(handle),
+            Err(err) => {
+                shared.borrow_mut().error = Some(err);
+                None
+            },
+        };
+
+        Self { handle, shared }
+    }
+
+    /// Returns `Ready` if the timer has fired at least once since the last
+    /// time the ticks were consumed, or if it couldn't be started.
+    fn poll_tick(
+        &self,
+        cx: &mut Context<'_>,
+        consume: bool,
+    ) -> Poll<Result<(), Error>> {
+        let shared = &mut *self.shared.borrow_mut();
+
+        if let Some(err) = &shared.error {
+            Poll::Ready(Err(err.clone()))
+        } else if shared.ticks > 0 {
+            if consume {
+                shared.ticks = 0;
+            }
+            Poll::Ready(Ok(()))
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(handle) = &mut self.handle {
+            let _ = handle.stop();
+        }
+    }
+}
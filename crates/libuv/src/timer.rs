@@ -1,6 +1,9 @@
+use core::mem::ManuallyDrop;
 use std::error::Error as StdError;
 use std::time::Duration;
 
+use crate::callback::{self, HandleKind};
+use crate::handle::impl_lifecycle;
 use crate::{Error, Handle, IntoResult, ffi};
 
 pub(crate) type Callback = Box<
@@ -15,6 +18,8 @@ pub struct TimerHandle {
     handle: Handle<ffi::uv_timer_t, Callback>,
 }
 
+impl_lifecycle!(TimerHandle, handle);
+
 impl TimerHandle {
     fn new() -> Result<Self, Error> {
         let handle = Handle::new(|uv_loop, handle| unsafe {
@@ -49,9 +54,24 @@ impl TimerHandle {
 
         unsafe { timer.handle.set_data(callback) };
 
+        timer.restart(timeout, repeat)?;
+
+        Ok(timer)
+    }
+
+    /// Restarts the timer with a new timeout and repeat interval, keeping
+    /// its callback.
+    ///
+    /// Unlike [`again()`](TimerHandle::again) this also works for
+    /// non-repeating timers.
+    pub(crate) fn restart(
+        &mut self,
+        timeout: Duration,
+        repeat: Duration,
+    ) -> Result<(), Error> {
         let retv = unsafe {
             ffi::uv_timer_start(
-                timer.handle.as_mut_ptr(),
+                self.handle.as_mut_ptr(),
                 Some(timer_cb as _),
                 timeout.as_millis() as u64,
                 repeat.as_millis() as u64,
@@ -62,7 +82,7 @@ impl TimerHandle {
             return Err(Error::TimerStart);
         }
 
-        Ok(timer)
+        Ok(())
     }
 
     /// Same as [`start()`](TimerHandle::start) but accepts a closure that
@@ -92,6 +112,55 @@ impl TimerHandle {
 
         Ok(())
     }
+
+    /// Stops the timer, and if it's repeating restarts it using the repeat
+    /// value as the timeout.
+    ///
+    /// Fails if the timer has never been started before.
+    pub fn again(&mut self) -> Result<(), Error> {
+        let retv = unsafe { ffi::uv_timer_again(self.handle.as_mut_ptr()) };
+
+        if retv < 0 {
+            return Err(Error::TimerAgain);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the repeat interval.
+    ///
+    /// The timer will be scheduled to run on the given interval regardless of
+    /// the callback execution duration. If the repeat value is set from a
+    /// timer callback it does not immediately take effect: if the timer was
+    /// non-repeating before it will have been stopped, and if it was repeating
+    /// the old repeat value will have been used to schedule the next timeout.
+    pub fn set_repeat(&mut self, repeat: Duration) {
+        unsafe {
+            ffi::uv_timer_set_repeat(
+                self.handle.as_mut_ptr(),
+                repeat.as_millis() as u64,
+            )
+        };
+    }
+
+    /// Returns the timer's repeat interval.
+    pub fn get_repeat(&self) -> Duration {
+        let repeat = unsafe { ffi::uv_timer_get_repeat(self.handle.as_ptr()) };
+        Duration::from_millis(repeat)
+    }
+
+    /// Returns how long until the timer fires. If the timer has already
+    /// expired the returned duration is zero.
+    pub fn get_due_in(&self) -> Duration {
+        let due_in = unsafe { ffi::uv_timer_get_due_in(self.handle.as_ptr()) };
+        Duration::from_millis(due_in)
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        unsafe { self.handle.drop_owned() };
+    }
 }
 
 extern "C" fn timer_cb(ptr: *mut ffi::uv_timer_t) {
@@ -100,11 +169,12 @@ extern "C" fn timer_cb(ptr: *mut ffi::uv_timer_t) {
     let callback = unsafe { handle.get_data() };
 
     if !callback.is_null() {
-        let mut handle = TimerHandle { handle };
+        // The handle is owned by someone else, so we can't drop it.
+        let mut handle = ManuallyDrop::new(TimerHandle { handle });
         let callback = unsafe { &mut *callback };
 
-        if let Err(_err) = callback(&mut handle) {
-            // TODO: what now?
-        }
+        callback::call(HandleKind::Timer, || callback(&mut handle));
+
+        unsafe { handle.handle.close_if_detached() };
     }
 }
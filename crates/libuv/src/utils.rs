@@ -0,0 +1,23 @@
+use std::ffi::{CString, OsStr, OsString};
+
+/// Converts an OS string to a C string, returning `None` if it contains
+/// interior nul bytes.
+pub(crate) fn to_cstring(s: &OsStr) -> Option<CString> {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(s).to_vec();
+
+    #[cfg(not(unix))]
+    let bytes = s.to_string_lossy().into_owned().into_bytes();
+
+    CString::new(bytes).ok()
+}
+
+/// Converts a C string returned by libuv to an OS string.
+pub(crate) fn to_os_string(bytes: &[u8]) -> OsString {
+    #[cfg(unix)]
+    return <OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(bytes)
+        .to_owned();
+
+    #[cfg(not(unix))]
+    return String::from_utf8_lossy(bytes).into_owned().into();
+}
@@ -0,0 +1,190 @@
+use core::ffi::c_int;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::callback::{self, CallbackError, HandleKind};
+use crate::error::ErrorCode;
+use crate::request::Request;
+use crate::{Error, ffi, oneshot};
+
+/// Runs `work` on libuv's thread pool, then calls `after` on the main thread
+/// with its output.
+///
+/// Since `after` is executed on the Neovim thread it can freely call any
+/// function in the [`api`] module.
+///
+/// The size of the thread pool is 4 by default, and it can be changed by
+/// setting the `UV_THREADPOOL_SIZE` environment variable before Neovim
+/// starts. Note that the pool is shared with libuv's file system operations
+/// and DNS lookups, so long-running jobs should be split into smaller ones.
+///
+/// If `work` panics `after` is not called, and the panic is reported to the
+/// handler set with [`set_error_handler`](crate::set_error_handler).
+///
+/// NOTE: this function **must** be called from the main thread.
+///
+/// [`api`]: https://docs.rs/nvim-oxi/latest/nvim_oxi/api/index.html
+pub fn queue_work<W, T, A>(work: W, after: A) -> Result<(), Error>
+where
+    W: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+    A: FnOnce(T) + 'static,
+{
+    submit(work, move |output| match output {
+        Ok(output) => callback::call(HandleKind::Work, || {
+            after(output);
+            Ok(())
+        }),
+
+        Err(Error::WorkPanicked(message)) => {
+            callback::report(CallbackError::Panic {
+                handle: HandleKind::Work,
+                message,
+            })
+        },
+
+        Err(_) => {},
+    })
+}
+
+/// Same as [`queue_work`], but returns a future that resolves to the output
+/// of `work`.
+///
+/// The future resolves to [`Error::WorkPanicked`] if `work` panics. Dropping
+/// the future doesn't stop `work` from running.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::libuv;
+///
+/// nvim_oxi::spawn(async {
+///     let sum = libuv::queue_work_async(|| (0..1_000_000u64).sum::<u64>())
+///         .await
+///         .unwrap();
+///
+///     nvim_oxi::print!("{sum}");
+/// });
+/// ```
+pub fn queue_work_async<W, T>(work: W) -> QueueWork<T>
+where
+    W: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+
+    let receiver = match submit(work, move |output| match output {
+        Ok(output) => sender.send(output),
+        Err(err) => sender.fail(err),
+    }) {
+        Ok(()) => receiver,
+        Err(err) => oneshot::failed(err),
+    };
+
+    QueueWork { receiver }
+}
+
+/// The future returned by [`queue_work_async`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct QueueWork<T> {
+    receiver: oneshot::Receiver<T>,
+}
+
+impl<T> Future for QueueWork<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver).poll(cx)
+    }
+}
+
+/// The data stored in a work request.
+struct Work<W, T, A> {
+    /// The closure to run on the thread pool. Only accessed by `work_cb`.
+    work: Option<W>,
+
+    /// The output of `work`, or the message of its panic. Written by
+    /// `work_cb` and read by `after_work_cb`.
+    output: Option<Result<T, String>>,
+
+    /// The closure to call on the main thread. Only accessed by
+    /// `after_work_cb`.
+    after: A,
+}
+
+fn submit<W, T, A>(work: W, after: A) -> Result<(), Error>
+where
+    W: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+    A: FnOnce(Result<T, Error>) + 'static,
+{
+    let data = Work { work: Some(work), output: None, after };
+
+    let mut req = Request::<ffi::uv_work_t, Work<W, T, A>>::new(data)?;
+
+    let retv = unsafe {
+        crate::with_loop(|uv_loop| {
+            ffi::uv_queue_work(
+                uv_loop,
+                req.as_mut_ptr(),
+                Some(work_cb::<W, T, A> as _),
+                Some(after_work_cb::<W, T, A> as _),
+            )
+        })
+    };
+
+    if retv < 0 {
+        drop(unsafe { req.into_data() });
+        return Err(Error::QueueWork(ErrorCode::new(retv)));
+    }
+
+    Ok(())
+}
+
+/// Called on one of the threads of the pool.
+extern "C" fn work_cb<W, T, A>(ptr: *mut ffi::uv_work_t)
+where
+    W: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+    A: FnOnce(Result<T, Error>) + 'static,
+{
+    let mut req = unsafe { Request::<_, Work<W, T, A>>::from_raw(ptr) };
+
+    // SAFETY: the main thread doesn't access the request until
+    // `after_work_cb` is called, which happens after this function returns.
+    let data = unsafe { req.data_mut() };
+
+    let Some(work) = data.work.take() else { return };
+
+    // Unwinding into libuv's C code would abort the process.
+    data.output = Some(
+        panic::catch_unwind(AssertUnwindSafe(work))
+            .map_err(|payload| callback::panic_message(&*payload)),
+    );
+}
+
+/// Called on the main thread once `work_cb` has returned, or if the request
+/// was canceled.
+extern "C" fn after_work_cb<W, T, A>(ptr: *mut ffi::uv_work_t, status: c_int)
+where
+    W: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+    A: FnOnce(Result<T, Error>) + 'static,
+{
+    let req = unsafe { Request::<_, Work<W, T, A>>::from_raw(ptr) };
+
+    let Work { output, after, .. } = unsafe { req.into_data() };
+
+    let output = match output {
+        Some(Ok(output)) if status >= 0 => Ok(output),
+        Some(Err(message)) => Err(Error::WorkPanicked(message)),
+        _ => Err(Error::Canceled),
+    };
+
+    after(output);
+}
@@ -0,0 +1,99 @@
+use core::ffi::c_char;
+use std::ffi::CString;
+
+use crate::ffi::{self, State};
+use crate::{Error, Poppable, traceback};
+
+/// The chunkname of the code evaluated by [`eval`].
+const EVAL_CHUNKNAME: &str = "=(eval)";
+
+/// The chunkname of the code executed by [`exec`].
+const EXEC_CHUNKNAME: &str = "=(exec)";
+
+/// Evaluates a Lua expression, returning its value(s).
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::lua;
+///
+/// let n = lua::eval::<i32>("1 + 2")?;
+/// assert_eq!(n, 3);
+/// ```
+pub fn eval<R: Poppable>(expr: &str) -> Result<R, Error> {
+    let source = format!("return {expr}");
+
+    unsafe {
+        crate::with_state(|lstate| {
+            let base = ffi::lua_gettop(lstate);
+            load_chunk(lstate, &source, EVAL_CHUNKNAME)?;
+            traceback::pcall(lstate, 0, -1)?;
+            R::pop_above(lstate, base)
+        })
+    }
+}
+
+/// Executes a chunk of Lua code, discarding any value it returns.
+pub fn exec(source: &str) -> Result<(), Error> {
+    unsafe {
+        crate::with_state(|lstate| {
+            load_chunk(lstate, source, EXEC_CHUNKNAME)?;
+            traceback::pcall(lstate, 0, 0)
+        })
+    }
+}
+
+/// Loads a chunk of Lua code without running it, pushing it on the stack as
+/// a function.
+///
+/// The `chunkname` is used in error messages and stack tracebacks.
+pub(crate) unsafe fn load_chunk(
+    lstate: *mut State,
+    source: &str,
+    chunkname: &str,
+) -> Result<(), Error> {
+    let name = CString::new(chunkname)
+        .map_err(|_| Error::InvalidChunkname(chunkname.to_owned()))?;
+
+    let retv = ffi::luaL_loadbuffer(
+        lstate,
+        source.as_ptr() as *const c_char,
+        source.len(),
+        name.as_ptr(),
+    );
+
+    match retv {
+        ffi::LUA_OK => Ok(()),
+        ffi::LUA_ERRSYNTAX => {
+            let message = traceback::pop_message(lstate);
+            Err(syntax_error(chunkname, &message))
+        },
+        _ => Err(Error::MemoryError(traceback::pop_message(lstate))),
+    }
+}
+
+/// Parses an error message of the form `<source>:<line>: <message>`, where
+/// `<source>` is the chunkname as formatted by Lua.
+fn syntax_error(chunkname: &str, message: &str) -> Error {
+    // Lua strips the leading `=` or `@` when displaying a chunkname.
+    let chunkname = chunkname.strip_prefix(['=', '@']).unwrap_or(chunkname);
+
+    let location = message.match_indices(':').find_map(|(idx, _)| {
+        let rest = &message[idx + 1..];
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let line = rest[..digits].parse::<u32>().ok()?;
+        let msg = rest[digits..].strip_prefix(": ")?;
+        Some((line, msg))
+    });
+
+    let (line, message) = match location {
+        Some((line, msg)) => (Some(line), msg),
+        None => (None, message),
+    };
+
+    Error::SyntaxError {
+        chunkname: chunkname.to_owned(),
+        line,
+        message: message.to_owned(),
+    }
+}
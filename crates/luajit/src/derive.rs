@@ -0,0 +1,160 @@
+//! Helpers used by the code generated by `#[derive(Pushable, Poppable)]`.
+
+use core::ffi::{CStr, c_int};
+
+use crate::ffi::{self, State};
+use crate::{Error, Poppable, Pushable};
+
+/// Pops the table at the top of the stack when dropped, together with any
+/// value left above it by a failed pop.
+pub struct PopOnDrop {
+    lstate: *mut State,
+    top: c_int,
+}
+
+impl PopOnDrop {
+    unsafe fn new(lstate: *mut State) -> Self {
+        Self { lstate, top: ffi::lua_gettop(lstate) - 1 }
+    }
+}
+
+impl Drop for PopOnDrop {
+    fn drop(&mut self) {
+        unsafe { ffi::lua_settop(self.lstate, self.top) };
+    }
+}
+
+/// Pushes a new table with space pre-allocated for `narr` array elements and
+/// `nrec` fields.
+pub unsafe fn new_table(lstate: *mut State, narr: c_int, nrec: c_int) {
+    ffi::lua_createtable(lstate, narr, nrec);
+}
+
+/// Pushes `value` and assigns it to the `field` of the table right below it.
+pub unsafe fn set_field<T: Pushable>(
+    lstate: *mut State,
+    field: &CStr,
+    value: T,
+) -> Result<(), Error> {
+    push_one(lstate, value)?;
+    ffi::lua_setfield(lstate, -2, field.as_ptr());
+    Ok(())
+}
+
+/// Pushes `value` and assigns it to the index `idx` of the table right below
+/// it.
+pub unsafe fn set_index<T: Pushable>(
+    lstate: *mut State,
+    idx: c_int,
+    value: T,
+) -> Result<(), Error> {
+    push_one(lstate, value)?;
+    ffi::lua_rawseti(lstate, -2, idx);
+    Ok(())
+}
+
+/// Checks that the value at the top of the stack is a table, returning a
+/// guard which pops it when dropped.
+pub unsafe fn pop_table<T>(lstate: *mut State) -> Result<PopOnDrop, Error> {
+    if ffi::lua_gettop(lstate) == 0 {
+        return Err(Error::PopEmptyStack);
+    }
+
+    match ffi::lua_type(lstate, -1) {
+        ffi::LUA_TTABLE => Ok(PopOnDrop::new(lstate)),
+        other => Err(Error::pop_wrong_type::<T>(ffi::LUA_TTABLE, other)),
+    }
+}
+
+/// Pops the `field` of the table at the top of the stack.
+///
+/// If the field is `nil` and a `default` is given, the default is returned
+/// instead.
+pub unsafe fn get_field<Owner, T: Poppable>(
+    lstate: *mut State,
+    field: &CStr,
+    default: Option<fn() -> T>,
+) -> Result<T, Error> {
+    ffi::lua_getfield(lstate, -1, field.as_ptr());
+    pop_member::<Owner, T>(lstate, default, || {
+        format!("field `{}`", field.to_string_lossy())
+    })
+}
+
+/// Pops the element at index `idx` of the table at the top of the stack.
+pub unsafe fn get_index<Owner, T: Poppable>(
+    lstate: *mut State,
+    idx: c_int,
+) -> Result<T, Error> {
+    ffi::lua_rawgeti(lstate, -1, idx);
+    pop_member::<Owner, T>(lstate, None, || format!("element {idx}"))
+}
+
+/// Pops the name of the enum variant at the top of the stack.
+///
+/// Unit variants are represented as strings, while all the other variants
+/// are tables whose `tag` field contains the variant's name. In the latter
+/// case the table is left on the stack and a guard popping it is returned
+/// together with the name.
+pub unsafe fn pop_variant<T>(
+    lstate: *mut State,
+    tag: &CStr,
+) -> Result<(String, Option<PopOnDrop>), Error> {
+    if ffi::lua_gettop(lstate) == 0 {
+        return Err(Error::PopEmptyStack);
+    }
+
+    match ffi::lua_type(lstate, -1) {
+        ffi::LUA_TSTRING => Ok((<String as Poppable>::pop(lstate)?, None)),
+
+        ffi::LUA_TTABLE => {
+            let table = PopOnDrop::new(lstate);
+            let name = get_field::<T, String>(lstate, tag, None)?;
+            Ok((name, Some(table)))
+        },
+
+        _ => Err(Error::pop_wrong_type_at_idx::<T>(lstate, -1)),
+    }
+}
+
+/// Returns the error for an enum variant that doesn't match any of the
+/// variants of `T`.
+pub fn unknown_variant<T>(name: &str) -> Error {
+    Error::pop_error(
+        core::any::type_name::<T>(),
+        format!("unknown variant `{name}`"),
+    )
+}
+
+/// Pushes `value`, making sure it takes up exactly one stack slot.
+unsafe fn push_one<T: Pushable>(
+    lstate: *mut State,
+    value: T,
+) -> Result<(), Error> {
+    match value.push(lstate)? {
+        0 => ffi::lua_pushnil(lstate),
+        1 => {},
+        n => ffi::lua_pop(lstate, n - 1),
+    }
+    Ok(())
+}
+
+unsafe fn pop_member<Owner, T: Poppable>(
+    lstate: *mut State,
+    default: Option<fn() -> T>,
+    describe: impl FnOnce() -> String,
+) -> Result<T, Error> {
+    if let Some(default) = default {
+        if ffi::lua_type(lstate, -1) == ffi::LUA_TNIL {
+            ffi::lua_pop(lstate, 1);
+            return Ok(default());
+        }
+    }
+
+    T::pop(lstate).map_err(|err| {
+        Error::pop_error(
+            core::any::type_name::<Owner>(),
+            format!("invalid {}: {err}", describe()),
+        )
+    })
+}
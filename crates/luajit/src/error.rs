@@ -2,7 +2,7 @@ use core::ffi::c_int;
 
 use thiserror::Error as ThisError;
 
-use crate::utils;
+use crate::{Traceback, utils};
 
 #[derive(Clone, Debug, Eq, PartialEq, ThisError, Hash)]
 pub enum Error {
@@ -18,8 +18,20 @@ pub enum Error {
     )]
     PushError { ty: &'static str, message: Option<String> },
 
-    #[error("Lua runtime error: {0}")]
-    RuntimeError(String),
+    #[error(
+        "Lua runtime error: {message}{}",
+        traceback.as_ref().map(|tb| format!("\n{tb}")).unwrap_or_default()
+    )]
+    RuntimeError { message: String, traceback: Option<Traceback> },
+
+    #[error(
+        "Lua syntax error in {chunkname}{}: {message}",
+        line.map(|line| format!(" at line {line}")).unwrap_or_default()
+    )]
+    SyntaxError { chunkname: String, line: Option<u32>, message: String },
+
+    #[error("Invalid chunkname {0:?}: chunknames can't contain nul bytes")]
+    InvalidChunkname(String),
 
     #[error("Lua memory error: {0}")]
     MemoryError(String),
@@ -29,6 +41,15 @@ pub enum Error {
 }
 
 impl Error {
+    /// Returns the stack traceback of the error, if it was raised by Lua
+    /// code called from Rust.
+    pub fn traceback(&self) -> Option<&Traceback> {
+        match self {
+            Self::RuntimeError { traceback, .. } => traceback.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn pop_error<M: Into<String>>(ty: &'static str, message: M) -> Self {
         Self::PopError { ty, message: Some(message.into()) }
     }
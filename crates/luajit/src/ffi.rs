@@ -22,9 +22,14 @@ pub const fn lua_upvalueindex(i: c_int) -> c_int {
     LUA_GLOBALSINDEX - i
 }
 
+// https://www.lua.org/manual/5.1/manual.html#lua_CFunction
+pub const LUA_MINSTACK: c_int = 20;
+
 // Thread status.
 pub const LUA_OK: c_int = 0;
+pub const LUA_YIELD: c_int = 1;
 pub const LUA_ERRRUN: c_int = 2;
+pub const LUA_ERRSYNTAX: c_int = 3;
 pub const LUA_ERRMEM: c_int = 4;
 pub const LUA_ERRERR: c_int = 5;
 
@@ -43,6 +48,25 @@ pub const LUA_TTHREAD: c_int = 8;
 // https://www.lua.org/manual/5.1/manual.html#lua_CFunction
 pub type CFunction = unsafe extern "C" fn(L: *mut State) -> c_int;
 
+// https://www.lua.org/manual/5.1/manual.html#lua_Debug
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub struct lua_Debug {
+    pub event: c_int,
+    pub name: *const c_char,
+    pub namewhat: *const c_char,
+    pub what: *const c_char,
+    pub source: *const c_char,
+    pub currentline: c_int,
+    pub nups: c_int,
+    pub linedefined: c_int,
+    pub lastlinedefined: c_int,
+    pub short_src: [c_char; LUA_IDSIZE],
+    i_ci: c_int,
+}
+
+const LUA_IDSIZE: usize = 60;
+
 // https://www.lua.org/manual/5.1/manual.html#lua_Integer
 pub type Integer = isize;
 
@@ -57,6 +81,9 @@ unsafe extern "C" {
     // https://www.lua.org/manual/5.1/manual.html#lua_call
     pub fn lua_call(L: *mut State, nargs: c_int, nresults: c_int);
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_checkstack
+    pub fn lua_checkstack(L: *mut State, extra: c_int) -> c_int;
+
     /// Binding to [`lua_createtable()`] (-0, +1).
     ///
     /// Creates a new empty table and pushes it onto the stack. The new table
@@ -79,12 +106,28 @@ unsafe extern "C" {
     // https://www.lua.org/manual/5.1/manual.html#lua_getmetatable
     pub fn lua_getmetatable(L: *mut State, index: c_int) -> c_int;
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_getstack
+    pub fn lua_getstack(
+        L: *mut State,
+        level: c_int,
+        ar: *mut lua_Debug,
+    ) -> c_int;
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_gettable
+    pub fn lua_gettable(L: *mut State, index: c_int);
+
     // https://www.lua.org/manual/5.1/manual.html#lua_gettop
     pub fn lua_gettop(L: *mut State) -> c_int;
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_insert
+    pub fn lua_insert(L: *mut State, index: c_int);
+
     // https://www.lua.org/manual/5.1/manual.html#lua_newuserdata
     pub fn lua_newuserdata(L: *mut State, size: usize) -> *mut c_void;
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_newthread
+    pub fn lua_newthread(L: *mut State) -> *mut State;
+
     // https://www.lua.org/manual/5.1/manual.html#lua_next
     pub fn lua_next(L: *mut State, index: c_int) -> c_int;
 
@@ -123,9 +166,18 @@ unsafe extern "C" {
     // https://www.lua.org/manual/5.1/manual.html#lua_pushstring
     pub fn lua_pushstring(L: *mut State, s: *const c_char);
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_pushthread
+    pub fn lua_pushthread(L: *mut State) -> c_int;
+
     // https://www.lua.org/manual/5.1/manual.html#lua_pushvalue
     pub fn lua_pushvalue(L: *mut State, index: c_int);
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_rawequal
+    pub fn lua_rawequal(L: *mut State, index1: c_int, index2: c_int) -> c_int;
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_rawget
+    pub fn lua_rawget(L: *mut State, index: c_int);
+
     // https://www.lua.org/manual/5.1/manual.html#lua_rawgeti
     pub fn lua_rawgeti(L: *mut State, index: c_int, n: c_int);
 
@@ -135,9 +187,27 @@ unsafe extern "C" {
     // https://www.lua.org/manual/5.1/manual.html#lua_rawseti
     pub fn lua_rawseti(L: *mut State, index: c_int, n: c_int);
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_remove
+    pub fn lua_remove(L: *mut State, index: c_int);
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_resume
+    pub fn lua_resume(L: *mut State, narg: c_int) -> c_int;
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_setfield
+    pub fn lua_setfield(L: *mut State, index: c_int, k: *const c_char);
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_setmetatable
+    pub fn lua_setmetatable(L: *mut State, index: c_int) -> c_int;
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_settable
+    pub fn lua_settable(L: *mut State, index: c_int);
+
     // https://www.lua.org/manual/5.1/manual.html#lua_settop
     pub fn lua_settop(L: *mut State, index: c_int);
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_status
+    pub fn lua_status(L: *mut State) -> c_int;
+
     // https://www.lua.org/manual/5.1/manual.html#lua_toboolean
     pub fn lua_toboolean(L: *mut State, index: c_int) -> c_int;
 
@@ -154,6 +224,9 @@ unsafe extern "C" {
     // https://www.lua.org/manual/5.1/manual.html#lua_tonumber
     pub fn lua_tonumber(L: *mut State, index: c_int) -> Number;
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_tothread
+    pub fn lua_tothread(L: *mut State, index: c_int) -> *mut State;
+
     // https://www.lua.org/manual/5.1/manual.html#lua_touserdata
     pub fn lua_touserdata(L: *mut State, index: c_int) -> *mut c_void;
 
@@ -163,14 +236,36 @@ unsafe extern "C" {
     // https://www.lua.org/manual/5.1/manual.html#lua_typename
     pub fn lua_typename(L: *mut State, tp: c_int) -> *const c_char;
 
+    // https://www.lua.org/manual/5.1/manual.html#lua_xmove
+    pub fn lua_xmove(from: *mut State, to: *mut State, n: c_int);
+
+    // https://www.lua.org/manual/5.1/manual.html#lua_yield
+    pub fn lua_yield(L: *mut State, nresults: c_int) -> c_int;
+
     // Lua auxiliary library.
 
     // https://www.lua.org/manual/5.1/manual.html#luaL_error
     pub fn luaL_error(L: *mut State, fmt: *const c_char, ...) -> !;
 
+    // https://www.lua.org/manual/5.1/manual.html#luaL_loadbuffer
+    pub fn luaL_loadbuffer(
+        L: *mut State,
+        buff: *const c_char,
+        sz: usize,
+        name: *const c_char,
+    ) -> c_int;
+
     // https://www.lua.org/manual/5.1/manual.html#luaL_ref
     pub fn luaL_ref(L: *mut State, t: c_int) -> c_int;
 
+    // https://www.lua.org/manual/5.2/manual.html#luaL_traceback
+    pub fn luaL_traceback(
+        L: *mut State,
+        L1: *mut State,
+        msg: *const c_char,
+        level: c_int,
+    );
+
     // https://www.lua.org/manual/5.1/manual.html#luaL_unref
     pub fn luaL_unref(L: *mut State, t: c_int, r#ref: c_int);
 }
@@ -1,5 +1,5 @@
 use core::error::Error;
-use core::ffi::{CStr, c_int};
+use core::ffi::c_int;
 use core::mem;
 use core::ptr;
 
@@ -15,38 +15,64 @@ where
     O: Pushable,
     R::Error: Error + 'static,
 {
-    type Callback =
-        Box<dyn Fn(*mut State) -> Result<c_int, crate::Error> + 'static>;
-
-    unsafe extern "C" fn c_fun(lstate: *mut State) -> c_int {
-        let fun = {
-            let idx = ffi::lua_upvalueindex(1);
-            let upv = ffi::lua_touserdata(lstate, idx) as *mut Callback;
-            &**upv
-        };
-
-        fun(lstate).unwrap_or_else(|err| utils::push_error(&err, lstate))
-    }
-
     unsafe {
         crate::with_state(move |lstate| {
             let fun = move |lstate| {
-                let args = A::pop(lstate)?;
+                let args = A::pop_above(lstate, 0)?;
                 let ret = fun(args)
                     .into_result()
                     .map_err(crate::Error::push_error_from_err::<R, _>)?;
                 ret.push(lstate)
             };
 
-            let ud = ffi::lua_newuserdata(lstate, mem::size_of::<Callback>());
-            ptr::write(ud as *mut Callback, Box::new(fun));
-
-            ffi::lua_pushcclosure(lstate, c_fun, 1);
+            push_callback(lstate, Box::new(fun));
             ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX)
         })
     }
 }
 
+/// Loads a chunk of Lua code as a function stored in the Lua registry,
+/// returning its ref.
+///
+/// The chunk is only compiled, not executed. Syntax errors are returned as
+/// [`Error::SyntaxError`](crate::Error::SyntaxError)s.
+pub fn load(source: &str, chunkname: &str) -> Result<c_int, crate::Error> {
+    unsafe {
+        crate::with_state(|lstate| {
+            crate::chunk::load_chunk(lstate, source, chunkname)?;
+            Ok(ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX))
+        })
+    }
+}
+
+/// A type-erased Rust function callable from Lua, which receives the Lua
+/// state and returns the number of values it pushed on the stack.
+pub(crate) type Callback =
+    Box<dyn Fn(*mut State) -> Result<c_int, crate::Error> + 'static>;
+
+/// Pushes a Lua function calling the given callback on the stack.
+///
+/// If the callback returns an error it's raised as a Lua error.
+pub(crate) unsafe fn push_callback(lstate: *mut State, callback: Callback) {
+    unsafe extern "C" fn c_fun(lstate: *mut State) -> c_int {
+        let fun = {
+            let idx = ffi::lua_upvalueindex(1);
+            let upv = ffi::lua_touserdata(lstate, idx) as *mut Callback;
+            &**upv
+        };
+
+        match fun(lstate) {
+            Ok(nresults) => crate::thread::return_or_yield(lstate, nresults),
+            Err(err) => utils::push_error(&err, lstate),
+        }
+    }
+
+    let ud = ffi::lua_newuserdata(lstate, mem::size_of::<Callback>());
+    ptr::write(ud as *mut Callback, callback);
+
+    ffi::lua_pushcclosure(lstate, c_fun, 1);
+}
+
 /// Calls a function previously stored in the Lua registry via [store].
 pub fn call<A, R>(lua_ref: c_int, args: A) -> Result<R, crate::Error>
 where
@@ -55,34 +81,12 @@ where
 {
     unsafe {
         crate::with_state(move |lstate| {
+            let base = ffi::lua_gettop(lstate);
             ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
             let nargs = args.push(lstate)?;
 
-            match ffi::lua_pcall(lstate, nargs, -1, 0 /* <- errorfunc */) {
-                ffi::LUA_OK => R::pop(lstate),
-
-                err_code => {
-                    let msg = CStr::from_ptr(ffi::lua_tostring(lstate, -1))
-                        .to_string_lossy()
-                        .to_string();
-
-                    ffi::lua_pop(lstate, 1);
-
-                    match err_code {
-                        ffi::LUA_ERRRUN => {
-                            Err(crate::Error::RuntimeError(msg))
-                        },
-
-                        ffi::LUA_ERRMEM => Err(crate::Error::MemoryError(msg)),
-
-                        ffi::LUA_ERRERR => {
-                            unreachable!("errorfunc is 0, this never happens!")
-                        },
-
-                        _ => unreachable!(),
-                    }
-                },
-            }
+            crate::traceback::pcall(lstate, nargs, -1)?;
+            R::pop_above(lstate, base)
         })
     }
 }
@@ -0,0 +1,39 @@
+use crate::ffi;
+use crate::macros::cstr;
+use crate::{Error, Poppable, Pushable, Table, traceback};
+
+/// Returns a handle to the table of global variables, i.e. Lua's `_G`.
+pub fn globals() -> Table {
+    unsafe {
+        crate::with_state(|lstate| {
+            ffi::lua_pushvalue(lstate, ffi::LUA_GLOBALSINDEX);
+            Table::pop_unchecked(lstate)
+        })
+    }
+}
+
+/// Binding to Lua's `require()`.
+///
+/// Loads the given module and pops the value it returns as a `T`, which is
+/// usually a [`Table`] or a function.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::{Function, lua};
+///
+/// let builtin = lua::require::<lua::Table>("telescope.builtin")?;
+/// let find_files = builtin.get::<_, Function<(), ()>>("find_files")?;
+/// find_files.call(())?;
+/// ```
+pub fn require<T: Poppable>(module: &str) -> Result<T, Error> {
+    unsafe {
+        crate::with_state(|lstate| {
+            let base = ffi::lua_gettop(lstate);
+            ffi::lua_getglobal(lstate, cstr!("require"));
+            module.push(lstate)?;
+            traceback::pcall(lstate, 1, 1)?;
+            T::pop_above(lstate, base)
+        })
+    }
+}
@@ -1,19 +1,46 @@
 #![allow(clippy::missing_safety_doc)]
 
+mod chunk;
+#[doc(hidden)]
+pub mod derive;
 mod error;
 pub mod ffi;
 pub mod function;
+mod globals;
 mod into_result;
 pub mod macros;
 mod poppable;
 mod pushable;
+#[cfg(feature = "serde")]
+pub mod serde;
 mod state;
+mod table;
+mod thread;
+mod traceback;
+mod userdata;
 pub mod utils;
+mod variadic;
 
+pub use chunk::{eval, exec};
 pub use error::Error;
+pub use globals::{globals, require};
 pub use into_result::IntoResult;
 #[doc(hidden)]
 pub use macros::__print;
 pub use poppable::Poppable;
 pub use pushable::Pushable;
 pub use state::{init, with_state};
+pub use table::{Ipairs, Pairs, Table};
+pub use thread::{Run, Thread, ThreadStatus, Yield};
+pub use traceback::{Traceback, TracebackFrame};
+pub use userdata::{
+    MetaMethod,
+    UserData,
+    UserDataRef,
+    UserDataRefMut,
+    UserDataRegistry,
+};
+pub use variadic::{MultiValue, Variadic};
+
+#[cfg(feature = "serde")]
+pub use self::serde::LuaSerde;
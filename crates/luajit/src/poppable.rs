@@ -1,3 +1,4 @@
+use core::ffi::c_int;
 use core::hash::Hash;
 use std::collections::HashMap;
 
@@ -7,8 +8,28 @@ use crate::macros::count;
 
 /// Trait implemented for types that can be popped off the Lua stack.
 pub trait Poppable: Sized {
+    /// Whether the type pops all the values at the top of the stack instead
+    /// of a single one, like [`Variadic`](crate::Variadic).
+    #[doc(hidden)]
+    const IS_VARIADIC: bool = false;
+
     /// Pops the value at the top of the stack.
     unsafe fn pop(lua_state: *mut State) -> Result<Self, Error>;
+
+    /// Pops the values above the stack index `base`, following Lua's
+    /// conventions for arguments and return values: missing values are
+    /// `nil`s and extra values are discarded.
+    ///
+    /// Only variadic types need to override this, since all the other types
+    /// take up a single stack slot.
+    #[doc(hidden)]
+    unsafe fn pop_above(
+        lua_state: *mut State,
+        base: c_int,
+    ) -> Result<Self, Error> {
+        lua_settop(lua_state, base + 1);
+        Self::pop(lua_state)
+    }
 }
 
 impl Poppable for () {
@@ -23,6 +44,15 @@ impl Poppable for () {
             Err(Error::pop_wrong_type::<Self>(LUA_TNIL, lua_type(state, -1)))
         }
     }
+    /// Discards all the values above `base`, just like a Lua function
+    /// without parameters ignores its arguments.
+    unsafe fn pop_above(
+        state: *mut State,
+        base: c_int,
+    ) -> Result<Self, Error> {
+        lua_settop(state, base);
+        Ok(())
+    }
 }
 
 impl Poppable for bool {
@@ -230,29 +260,62 @@ where
 
 /// Implements `Poppable` for a tuple `(a, b, c, ..)` where all the elements
 /// in the tuple implement `Poppable`.
+///
+/// Every element takes up a single stack slot except for the last one, which
+/// can be variadic.
 macro_rules! pop_tuple {
     ($($name:ident)*) => (
         impl<$($name,)*> Poppable for ($($name,)*)
         where
             $($name: Poppable,)*
         {
-            #[allow(non_snake_case)]
+            const IS_VARIADIC: bool = {
+                let is_variadic = [$($name::IS_VARIADIC,)*];
+                is_variadic[is_variadic.len() - 1]
+            };
+
             unsafe fn pop(state: *mut State) -> Result<Self, crate::Error> {
-                crate::utils::grow_stack(state, count!($($name)*));
-                pop_reverse!(state, $($name)*);
+                // Only variadic tuples pop all the values on the stack, the
+                // other ones pop the values at the top.
+                let base = if Self::IS_VARIADIC {
+                    0
+                } else {
+                    (lua_gettop(state) - count!($($name)*)).max(0)
+                };
+
+                Self::pop_above(state, base)
+            }
+
+            #[allow(non_snake_case)]
+            unsafe fn pop_above(
+                state: *mut State,
+                base: c_int,
+            ) -> Result<Self, crate::Error> {
+                let count = count!($($name)*);
+
+                if Self::IS_VARIADIC {
+                    crate::utils::grow_stack(state, base + count - 1);
+                } else {
+                    lua_settop(state, base + count);
+                }
+
+                pop_reverse!(state, base, $($name)*);
                 Ok(($($name,)*))
             }
         }
     );
 }
 
+/// Pops the elements of a tuple starting from the last one, where the first
+/// element is the value right above the stack index `$base`, the second one
+/// is the value above it, and so on.
 macro_rules! pop_reverse {
-    ($lua_state:expr, $x:ident $($xs:ident)*) => {
-        pop_reverse!($lua_state, $($xs)*);
-        let $x = $x::pop($lua_state)?;
+    ($lua_state:expr, $base:expr, $x:ident $($xs:ident)*) => {
+        pop_reverse!($lua_state, $base + 1, $($xs)*);
+        let $x = $x::pop_above($lua_state, $base)?;
     };
 
-    ($lstate:expr,) => ();
+    ($lstate:expr, $base:expr,) => ();
 }
 
 pop_tuple!(A);
@@ -1,7 +1,6 @@
 use core::ffi::{c_char, c_int};
 
 use crate::ffi::{self, Integer, Number, State};
-use crate::macros::count;
 use crate::utils;
 
 /// Trait implemented for types that can be pushed onto the Lua stack.
@@ -94,7 +93,7 @@ impl Pushable for f32 {
     }
 }
 
-impl Pushable for String {
+impl Pushable for &str {
     unsafe fn push(self, lstate: *mut State) -> Result<c_int, crate::Error> {
         ffi::lua_pushlstring(
             lstate,
@@ -105,6 +104,12 @@ impl Pushable for String {
     }
 }
 
+impl Pushable for String {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, crate::Error> {
+        self.as_str().push(lstate)
+    }
+}
+
 impl<T> Pushable for Option<T>
 where
     T: Pushable,
@@ -148,6 +153,9 @@ where
 }
 /// Implements `LuaPushable` for a tuple `(a, b, c, ..)` where all the elements
 /// in the tuple implement `LuaPushable`.
+///
+/// Returns the total number of values pushed, since an element can push more
+/// than one value, like [`Variadic`](crate::Variadic).
 macro_rules! push_tuple {
     ($($name:ident)*) => {
         impl<$($name,)*> Pushable for ($($name,)*)
@@ -160,8 +168,9 @@ macro_rules! push_tuple {
                 lstate: *mut State,
             ) -> Result<c_int, crate::Error> {
                 let ($($name,)*) = self;
-                $($name.push(lstate)?;)*
-                Ok(count!($($name)*))
+                let mut pushed = 0;
+                $(pushed += $name.push(lstate)?;)*
+                Ok(pushed)
             }
         }
     }
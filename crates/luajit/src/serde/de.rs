@@ -0,0 +1,545 @@
+use core::ffi::c_int;
+
+use serde::de::{self, Error as _, IntoDeserializer};
+
+use super::Error;
+use crate::ffi::{self, State};
+use crate::utils;
+
+/// A struct for deserializing Lua values on the Lua stack into Rust values.
+///
+/// The value is read in place, without being popped from the stack. Tables
+/// are deserialized as sequences if they're array-like, and as maps
+/// otherwise. Enums can be deserialized from strings containing the name of
+/// a unit variant, or from `{ [variant] = value }` tables.
+#[derive(Debug)]
+pub struct Deserializer {
+    lstate: *mut State,
+
+    /// The absolute stack index of the value to deserialize.
+    idx: c_int,
+}
+
+impl Deserializer {
+    /// Creates a new `Deserializer` reading the value at index `idx` of the
+    /// stack of `lstate`.
+    ///
+    /// # Safety
+    ///
+    /// `lstate` must be a valid pointer to a Lua state, and `idx` must be a
+    /// valid index on its stack.
+    pub unsafe fn new(lstate: *mut State, idx: c_int) -> Self {
+        let idx = match idx {
+            // Pseudo-indices like `LUA_GLOBALSINDEX` are left as they are.
+            ..0 if idx > ffi::LUA_REGISTRYINDEX => {
+                ffi::lua_gettop(lstate) + idx + 1
+            },
+            _ => idx,
+        };
+
+        Self { lstate, idx }
+    }
+
+    fn ty(&self) -> c_int {
+        unsafe { ffi::lua_type(self.lstate, self.idx) }
+    }
+
+    fn invalid_type(&self, expected: &str) -> Error {
+        let found = unsafe { utils::debug_type(self.lstate, self.idx) };
+        Error::custom(format!("expected {expected}, found a {found}"))
+    }
+
+    /// Returns the bytes of the string at the deserializer's index.
+    ///
+    /// # Safety
+    ///
+    /// The value must be a string, and the returned slice must not outlive
+    /// it.
+    unsafe fn bytes<'a>(&self) -> &'a [u8] {
+        let mut len = 0;
+        let ptr = ffi::lua_tolstring(self.lstate, self.idx, &mut len);
+        core::slice::from_raw_parts(ptr as *const u8, len)
+    }
+
+    fn check_stack(&self) -> Result<(), Error> {
+        // A key and a value.
+        match unsafe { ffi::lua_checkstack(self.lstate, 2) } {
+            0 => Err(Error::custom("can't grow the Lua stack")),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            unit unit_struct identifier
+    }
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.ty() {
+            ffi::LUA_TNIL | ffi::LUA_TNONE => visitor.visit_unit(),
+
+            ffi::LUA_TBOOLEAN => visitor.visit_bool(unsafe {
+                ffi::lua_toboolean(self.lstate, self.idx) != 0
+            }),
+
+            ffi::LUA_TNUMBER => {
+                let number =
+                    unsafe { ffi::lua_tonumber(self.lstate, self.idx) };
+
+                // `i64::MAX as f64` rounds up to 2^63, which doesn't fit.
+                if number.fract() == 0.0
+                    && number >= i64::MIN as f64
+                    && number < i64::MAX as f64
+                {
+                    visitor.visit_i64(number as i64)
+                } else {
+                    visitor.visit_f64(number)
+                }
+            },
+
+            ffi::LUA_TSTRING => {
+                let bytes = unsafe { self.bytes() };
+                match core::str::from_utf8(bytes) {
+                    Ok(str) => visitor.visit_str(str),
+                    Err(_) => visitor.visit_bytes(bytes),
+                }
+            },
+
+            ffi::LUA_TTABLE => {
+                let is_array = unsafe {
+                    let relative_idx =
+                        self.idx - ffi::lua_gettop(self.lstate) - 1;
+                    utils::is_table_array(self.lstate, relative_idx)
+                };
+
+                if is_array {
+                    self.deserialize_seq(visitor)
+                } else {
+                    self.deserialize_map(visitor)
+                }
+            },
+
+            _ => Err(self.invalid_type("a serializable value")),
+        }
+    }
+
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.ty() {
+            ffi::LUA_TSTRING => visitor.visit_bytes(unsafe { self.bytes() }),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.ty() {
+            ffi::LUA_TNIL | ffi::LUA_TNONE => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.ty() {
+            ffi::LUA_TSTRING => {
+                let variant = core::str::from_utf8(unsafe { self.bytes() })
+                    .map_err(Error::custom)?;
+
+                visitor.visit_enum(EnumDeserializer {
+                    variant: variant.to_owned(),
+                    value: None,
+                })
+            },
+
+            ffi::LUA_TTABLE => {
+                self.check_stack()?;
+
+                let lstate = self.lstate;
+
+                let (variant, value) = unsafe {
+                    ffi::lua_pushnil(lstate);
+
+                    if ffi::lua_next(lstate, self.idx) == 0 {
+                        return Err(self.invalid_type("a non-empty table"));
+                    }
+
+                    // Check that the table has a single key-value pair,
+                    // leaving the value on the stack.
+                    ffi::lua_pushvalue(lstate, -2);
+                    if ffi::lua_next(lstate, self.idx) != 0 {
+                        return Err(Error::custom(
+                            "expected a table with a single key-value pair",
+                        ));
+                    }
+
+                    let key = Deserializer::new(lstate, -2);
+
+                    if key.ty() != ffi::LUA_TSTRING {
+                        return Err(key.invalid_type("a string variant"));
+                    }
+
+                    let variant = core::str::from_utf8(key.bytes())
+                        .map_err(Error::custom)?
+                        .to_owned();
+
+                    (variant, Deserializer::new(lstate, -1))
+                };
+
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            },
+
+            _ => Err(self.invalid_type("a string or a table")),
+        }
+    }
+
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.ty() != ffi::LUA_TTABLE {
+            return Err(self.invalid_type("a table"));
+        }
+
+        self.check_stack()?;
+
+        let len = unsafe { ffi::lua_objlen(self.lstate, self.idx) };
+
+        visitor.visit_seq(SeqDeserializer {
+            lstate: self.lstate,
+            table_idx: self.idx,
+            len: len as c_int,
+            next: 1,
+        })
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.ty() != ffi::LUA_TTABLE {
+            return Err(self.invalid_type("a table"));
+        }
+
+        self.check_stack()?;
+
+        let top = unsafe { ffi::lua_gettop(self.lstate) };
+
+        visitor.visit_map(MapDeserializer {
+            lstate: self.lstate,
+            table_idx: self.idx,
+            top,
+            has_key: false,
+            is_done: false,
+        })
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_ignored_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // No need to walk the value if it's going to be ignored.
+        visitor.visit_unit()
+    }
+}
+
+/// Deserializes the elements of an array-like table, pushing them on the
+/// stack one at a time.
+struct SeqDeserializer {
+    lstate: *mut State,
+    table_idx: c_int,
+    len: c_int,
+    next: c_int,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.next > self.len {
+            return Ok(None);
+        }
+
+        let value = unsafe {
+            let top = ffi::lua_gettop(self.lstate);
+            ffi::lua_rawgeti(self.lstate, self.table_idx, self.next);
+            let value = seed.deserialize(Deserializer::new(self.lstate, -1));
+            ffi::lua_settop(self.lstate, top);
+            value
+        };
+
+        self.next += 1;
+
+        value.map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some((self.len - self.next + 1) as usize)
+    }
+}
+
+/// Deserializes the key-value pairs of a table using `lua_next`.
+///
+/// While iterating, the current key is kept at index `top + 1` and its value
+/// at index `top + 2`.
+struct MapDeserializer {
+    lstate: *mut State,
+    table_idx: c_int,
+    top: c_int,
+    has_key: bool,
+    is_done: bool,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.is_done {
+            return Ok(None);
+        }
+
+        unsafe {
+            if self.has_key {
+                // Leave only the previous key on the stack.
+                ffi::lua_settop(self.lstate, self.top + 1);
+            } else {
+                ffi::lua_settop(self.lstate, self.top);
+                ffi::lua_pushnil(self.lstate);
+            }
+
+            if ffi::lua_next(self.lstate, self.table_idx) == 0 {
+                self.is_done = true;
+                self.has_key = false;
+                return Ok(None);
+            }
+
+            self.has_key = true;
+
+            // Numeric keys are converted to strings by `lua_tolstring`,
+            // which would confuse `lua_next`, so we deserialize a copy.
+            ffi::lua_pushvalue(self.lstate, self.top + 1);
+            let key = seed.deserialize(Deserializer::new(self.lstate, -1));
+            ffi::lua_settop(self.lstate, self.top + 2);
+            key.map(Some)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        if !self.has_key {
+            return Err(Error::custom("value deserialized before key"));
+        }
+
+        unsafe {
+            let value =
+                seed.deserialize(Deserializer::new(self.lstate, self.top + 2));
+            ffi::lua_settop(self.lstate, self.top + 1);
+            value
+        }
+    }
+}
+
+impl Drop for MapDeserializer {
+    fn drop(&mut self) {
+        // Pop the key-value pair left by an iteration that stopped early.
+        unsafe { ffi::lua_settop(self.lstate, self.top) };
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Deserializer>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant: de::value::StringDeserializer<Error> =
+            self.variant.into_deserializer();
+        let deserializer = VariantDeserializer { value: self.value };
+        seed.deserialize(variant).map(|v| (v, deserializer))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<Deserializer>,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(value) => seed.deserialize(value),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => de::Deserializer::deserialize_map(value, visitor),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => de::Deserializer::deserialize_seq(value, visitor),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            None => Ok(()),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::NewtypeVariant,
+                &"unit variant",
+            )),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use core::fmt;
+use std::error::Error as StdError;
+
+use serde::{de, ser};
+
+/// The error type of both the [`Serializer`](super::Serializer) and the
+/// [`Deserializer`](super::Deserializer).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Error {
+    pub msg: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl StdError for Error {}
+
+impl ser::Error for Error {
+    #[inline]
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self { msg: msg.to_string() }
+    }
+}
+
+impl de::Error for Error {
+    #[inline]
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self { msg: msg.to_string() }
+    }
+}
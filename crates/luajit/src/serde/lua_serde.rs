@@ -0,0 +1,88 @@
+use core::ffi::c_int;
+use core::ops::{Deref, DerefMut};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::{Deserializer, Serializer};
+use crate::ffi::{self, State};
+use crate::{Error, Poppable, Pushable};
+
+/// A wrapper which pushes and pops any `T` implementing serde's
+/// [`Serialize`] and [`Deserialize`](serde::Deserialize) traits, converting
+/// it directly to and from a Lua value.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::lua::LuaSerde;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     line_width: u32,
+/// }
+///
+/// fn setup(LuaSerde(config): LuaSerde<Config>) {
+///     // ...
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LuaSerde<T>(pub T);
+
+impl<T> LuaSerde<T> {
+    /// Consumes the `LuaSerde`, returning the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for LuaSerde<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for LuaSerde<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for LuaSerde<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize> Pushable for LuaSerde<T> {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        let top = ffi::lua_gettop(lstate);
+
+        match self.0.serialize(Serializer::new(lstate)) {
+            Ok(()) => Ok(1),
+            Err(err) => {
+                // Discard any partially built value.
+                ffi::lua_settop(lstate, top);
+                Err(Error::push_error_from_err::<T, _>(err))
+            },
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Poppable for LuaSerde<T> {
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        let top = ffi::lua_gettop(lstate);
+
+        if top == 0 {
+            return Err(Error::PopEmptyStack);
+        }
+
+        let value = T::deserialize(Deserializer::new(lstate, top));
+
+        ffi::lua_settop(lstate, top - 1);
+
+        value.map(Self).map_err(Error::pop_error_from_err::<T, _>)
+    }
+}
@@ -0,0 +1,19 @@
+//! (De)Serialization support for Lua values using [Serde], reading from and
+//! writing to the Lua stack directly.
+//!
+//! Unlike going through an intermediate [`Object`], this doesn't copy the
+//! values more than once, which makes it well suited for large tables like
+//! the ones usually passed to a plugin's `setup()` function.
+//!
+//! [Serde]: https://serde.rs/
+//! [`Object`]: https://docs.rs/nvim-oxi/latest/nvim_oxi/struct.Object.html
+
+mod de;
+mod error;
+mod lua_serde;
+mod ser;
+
+pub use de::Deserializer;
+pub use error::Error;
+pub use lua_serde::LuaSerde;
+pub use ser::Serializer;
@@ -0,0 +1,405 @@
+use core::ffi::{c_char, c_int};
+
+use serde::ser::{self, Error as _};
+
+use super::Error;
+use crate::ffi::{self, State};
+
+/// A struct for serializing Rust values into Lua values, pushing them on the
+/// Lua stack.
+///
+/// Every value is pushed as exactly one Lua value:
+///
+/// - `None`, `()` and unit structs are pushed as `nil`;
+/// - sequences, tuples and tuple structs as array-like tables;
+/// - maps and structs as tables;
+/// - unit variants as strings containing the variant's name, and all the
+///   other variants as `{ [variant] = value }` tables.
+///
+/// If the serialization fails the stack may contain a partially built value,
+/// which should be discarded by the caller.
+#[derive(Debug)]
+pub struct Serializer {
+    lstate: *mut State,
+}
+
+impl Serializer {
+    /// Creates a new `Serializer` pushing values on the stack of `lstate`.
+    ///
+    /// # Safety
+    ///
+    /// `lstate` must be a valid pointer to a Lua state.
+    pub unsafe fn new(lstate: *mut State) -> Self {
+        Self { lstate }
+    }
+
+    /// Pushes a new table, leaving room on the stack for its keys and values.
+    fn push_table(&self, narr: usize, nrec: usize) -> Result<(), Error> {
+        unsafe {
+            // The table, a key and a value.
+            if ffi::lua_checkstack(self.lstate, 3) == 0 {
+                return Err(Error::custom("can't grow the Lua stack"));
+            }
+            ffi::lua_createtable(self.lstate, narr as c_int, nrec as c_int);
+        }
+        Ok(())
+    }
+
+    /// Pushes the `{ [variant] = .. }` table wrapping the value of a variant,
+    /// followed by the variant's name.
+    fn push_variant(&self, variant: &str) -> Result<(), Error> {
+        self.push_table(0, 1)?;
+        push_str(self.lstate, variant);
+        Ok(())
+    }
+}
+
+/// Implements a `serialize_*` method for an integer type, pushing integers
+/// which don't fit in a `lua_Integer` as floats.
+macro_rules! serialize_int {
+    ($name:ident, $type:ty) => {
+        #[inline]
+        fn $name(self, value: $type) -> Result<(), Self::Error> {
+            unsafe {
+                match ffi::Integer::try_from(value) {
+                    Ok(n) => ffi::lua_pushinteger(self.lstate, n),
+                    Err(_) => ffi::lua_pushnumber(self.lstate, value as _),
+                }
+            }
+            Ok(())
+        }
+    };
+}
+
+macro_rules! serialize_nil {
+    ($name:ident) => {
+        #[inline]
+        fn $name(self) -> Result<(), Self::Error> {
+            unsafe { ffi::lua_pushnil(self.lstate) };
+            Ok(())
+        }
+    };
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeSeq;
+
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    serialize_int!(serialize_i8, i8);
+    serialize_int!(serialize_u8, u8);
+    serialize_int!(serialize_i16, i16);
+    serialize_int!(serialize_u16, u16);
+    serialize_int!(serialize_i32, i32);
+    serialize_int!(serialize_u32, u32);
+    serialize_int!(serialize_i64, i64);
+    serialize_int!(serialize_u64, u64);
+    serialize_int!(serialize_i128, i128);
+    serialize_int!(serialize_u128, u128);
+
+    serialize_nil!(serialize_none);
+    serialize_nil!(serialize_unit);
+
+    #[inline]
+    fn serialize_bool(self, value: bool) -> Result<(), Self::Error> {
+        unsafe { ffi::lua_pushboolean(self.lstate, value as _) };
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_f32(self, value: f32) -> Result<(), Self::Error> {
+        self.serialize_f64(value.into())
+    }
+
+    #[inline]
+    fn serialize_f64(self, value: f64) -> Result<(), Self::Error> {
+        unsafe { ffi::lua_pushnumber(self.lstate, value) };
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_char(self, value: char) -> Result<(), Self::Error> {
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    #[inline]
+    fn serialize_str(self, value: &str) -> Result<(), Self::Error> {
+        self.serialize_bytes(value.as_bytes())
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Self::Error> {
+        unsafe {
+            ffi::lua_pushlstring(
+                self.lstate,
+                value.as_ptr() as *const c_char,
+                value.len(),
+            )
+        };
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<(), Self::Error> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        self.push_variant(variant)?;
+        value.serialize(Serializer { lstate: self.lstate })?;
+        unsafe { ffi::lua_rawset(self.lstate, -3) };
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_seq(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, Self::Error> {
+        self.push_table(len.unwrap_or_default(), 0)?;
+        Ok(SerializeSeq { lstate: self.lstate, len: 0, is_variant: false })
+    }
+
+    #[inline]
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.push_variant(variant)?;
+        self.push_table(len, 0)?;
+        Ok(SerializeSeq { lstate: self.lstate, len: 0, is_variant: true })
+    }
+
+    #[inline]
+    fn serialize_map(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeMap, Self::Error> {
+        self.push_table(0, len.unwrap_or_default())?;
+        Ok(SerializeMap { lstate: self.lstate, is_variant: false })
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.push_variant(variant)?;
+        self.push_table(0, len)?;
+        Ok(SerializeMap { lstate: self.lstate, is_variant: true })
+    }
+}
+
+pub struct SerializeSeq {
+    lstate: *mut State,
+
+    /// The number of elements serialized so far.
+    len: c_int,
+
+    /// Whether the table is the value of a `{ [variant] = .. }` table.
+    is_variant: bool,
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        value.serialize(Serializer { lstate: self.lstate })?;
+        self.len += 1;
+        unsafe { ffi::lua_rawseti(self.lstate, -2, self.len) };
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        if self.is_variant {
+            unsafe { ffi::lua_rawset(self.lstate, -3) };
+        }
+        Ok(())
+    }
+}
+
+macro_rules! serialize_seq {
+    ($trait:ident, $fn:ident) => {
+        impl ser::$trait for SerializeSeq {
+            type Ok = ();
+            type Error = Error;
+
+            fn $fn<T>(&mut self, value: &T) -> Result<(), Self::Error>
+            where
+                T: ser::Serialize + ?Sized,
+            {
+                ser::SerializeSeq::serialize_element(self, value)
+            }
+
+            fn end(self) -> Result<(), Self::Error> {
+                ser::SerializeSeq::end(self)
+            }
+        }
+    };
+}
+
+serialize_seq!(SerializeTuple, serialize_element);
+serialize_seq!(SerializeTupleStruct, serialize_field);
+serialize_seq!(SerializeTupleVariant, serialize_field);
+
+pub struct SerializeMap {
+    lstate: *mut State,
+
+    /// Whether the table is the value of a `{ [variant] = .. }` table.
+    is_variant: bool,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        key.serialize(Serializer { lstate: self.lstate })?;
+
+        if unsafe { ffi::lua_type(self.lstate, -1) } == ffi::LUA_TNIL {
+            return Err(Error::custom("table keys can't be nil"));
+        }
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        value.serialize(Serializer { lstate: self.lstate })?;
+        unsafe { ffi::lua_rawset(self.lstate, -3) };
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        if self.is_variant {
+            unsafe { ffi::lua_rawset(self.lstate, -3) };
+        }
+        Ok(())
+    }
+}
+
+macro_rules! serialize_map {
+    ($trait:ident) => {
+        impl ser::$trait for SerializeMap {
+            type Ok = ();
+            type Error = Error;
+
+            fn serialize_field<T>(
+                &mut self,
+                key: &'static str,
+                value: &T,
+            ) -> Result<(), Self::Error>
+            where
+                T: ser::Serialize + ?Sized,
+            {
+                push_str(self.lstate, key);
+                ser::SerializeMap::serialize_value(self, value)
+            }
+
+            fn end(self) -> Result<(), Self::Error> {
+                ser::SerializeMap::end(self)
+            }
+        }
+    };
+}
+
+serialize_map!(SerializeStruct);
+serialize_map!(SerializeStructVariant);
+
+fn push_str(lstate: *mut State, s: &str) {
+    unsafe {
+        ffi::lua_pushlstring(lstate, s.as_ptr() as *const c_char, s.len())
+    };
+}
@@ -0,0 +1,357 @@
+use core::ffi::c_int;
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::ffi::{self, State};
+use crate::{Error, Poppable, Pushable, traceback};
+
+/// A handle to a Lua table.
+///
+/// The table is kept alive by a reference stored in the Lua registry, which
+/// is released when the `Table` is dropped. Cloning a `Table` creates a new
+/// reference to the same table, it doesn't copy its contents.
+///
+/// Unlike [`raw_get`](Table::raw_get) and [`raw_set`](Table::raw_set),
+/// [`get`](Table::get) and [`set`](Table::set) may invoke the `__index` and
+/// `__newindex` metamethods. Any error raised by the metamethods is returned
+/// as an [`Error::RuntimeError`].
+pub struct Table {
+    lua_ref: c_int,
+}
+
+/// An iterator over the key-value pairs of a [`Table`], returned by
+/// [`Table::pairs`].
+///
+/// Just like Lua's `pairs()`, the iteration order is unspecified.
+pub struct Pairs<K, V> {
+    table: Table,
+
+    /// A reference to the last key returned by the iterator, or `None` if
+    /// the iteration hasn't started yet.
+    last_key: Option<c_int>,
+
+    is_done: bool,
+    types: PhantomData<(K, V)>,
+}
+
+/// An iterator over the array part of a [`Table`], returned by
+/// [`Table::ipairs`].
+pub struct Ipairs<V> {
+    table: Table,
+    index: usize,
+    is_done: bool,
+    ty: PhantomData<V>,
+}
+
+impl Table {
+    /// Creates a new empty table.
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    /// Creates a new empty table, with space pre-allocated for `narr` array
+    /// elements and `nrec` non-array elements.
+    pub fn with_capacity(narr: usize, nrec: usize) -> Self {
+        unsafe {
+            crate::with_state(|lstate| {
+                ffi::lua_createtable(lstate, narr as _, nrec as _);
+                Self::pop_unchecked(lstate)
+            })
+        }
+    }
+
+    /// Returns the value associated to `key`, possibly invoking the `__index`
+    /// metamethod.
+    pub fn get<K, V>(&self, key: K) -> Result<V, Error>
+    where
+        K: Pushable,
+        V: Poppable,
+    {
+        self.call(get, |lstate| unsafe { key.push(lstate) })
+    }
+
+    /// Associates `value` to `key`, possibly invoking the `__newindex`
+    /// metamethod.
+    pub fn set<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: Pushable,
+        V: Pushable,
+    {
+        self.call(set, |lstate| unsafe {
+            Ok(key.push(lstate)? + value.push(lstate)?)
+        })
+    }
+
+    /// Same as [`get`](Table::get), but without invoking any metamethod.
+    pub fn raw_get<K, V>(&self, key: K) -> Result<V, Error>
+    where
+        K: Pushable,
+        V: Poppable,
+    {
+        self.call(raw_get, |lstate| unsafe { key.push(lstate) })
+    }
+
+    /// Same as [`set`](Table::set), but without invoking any metamethod.
+    pub fn raw_set<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: Pushable,
+        V: Pushable,
+    {
+        self.call(raw_set, |lstate| unsafe {
+            Ok(key.push(lstate)? + value.push(lstate)?)
+        })
+    }
+
+    /// Returns the length of the table as returned by the `#` operator,
+    /// without invoking the `__len` metamethod.
+    pub fn len(&self) -> usize {
+        self.with_table(|lstate| unsafe { ffi::lua_objlen(lstate, -1) })
+    }
+
+    /// Returns whether the table's array part is empty, i.e. whether
+    /// [`len`](Table::len) is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the key-value pairs of the table.
+    ///
+    /// If a key or a value can't be popped as a `K` or a `V` the iterator
+    /// yields an error for that pair and moves on to the next one.
+    pub fn pairs<K, V>(&self) -> Pairs<K, V>
+    where
+        K: Poppable,
+        V: Poppable,
+    {
+        Pairs {
+            table: self.clone(),
+            last_key: None,
+            is_done: false,
+            types: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the `(index, value)` pairs of the table,
+    /// starting from index 1 and stopping at the first `nil` value.
+    pub fn ipairs<V: Poppable>(&self) -> Ipairs<V> {
+        Ipairs {
+            table: self.clone(),
+            index: 0,
+            is_done: false,
+            ty: PhantomData,
+        }
+    }
+
+    /// Returns the table's metatable, if it has one.
+    pub fn metatable(&self) -> Option<Table> {
+        self.with_table(|lstate| unsafe {
+            (ffi::lua_getmetatable(lstate, -1) != 0)
+                .then(|| Self::pop_unchecked(lstate))
+        })
+    }
+
+    /// Sets the table's metatable, or removes it if `metatable` is `None`.
+    pub fn set_metatable(&self, metatable: Option<&Table>) {
+        self.with_table(|lstate| unsafe {
+            match metatable {
+                Some(metatable) => metatable.push_ref(lstate),
+                None => ffi::lua_pushnil(lstate),
+            }
+            ffi::lua_setmetatable(lstate, -2);
+        })
+    }
+
+    /// Pushes the table on the stack and calls `fun`, restoring the stack to
+    /// its previous height once `fun` returns.
+    fn with_table<F, R>(&self, fun: F) -> R
+    where
+        F: FnOnce(*mut State) -> R,
+    {
+        unsafe {
+            crate::with_state(|lstate| {
+                let top = ffi::lua_gettop(lstate);
+                self.push_ref(lstate);
+                let ret = fun(lstate);
+                ffi::lua_settop(lstate, top);
+                ret
+            })
+        }
+    }
+
+    /// Calls `cfun` in protected mode with the table as its first argument,
+    /// followed by the arguments pushed by `push_args`, and pops its only
+    /// return value as an `R`.
+    fn call<A, R>(
+        &self,
+        cfun: ffi::CFunction,
+        push_args: A,
+    ) -> Result<R, Error>
+    where
+        A: FnOnce(*mut State) -> Result<c_int, Error>,
+        R: Poppable,
+    {
+        self.with_table(|lstate| unsafe {
+            ffi::lua_pushcfunction(lstate, cfun);
+            ffi::lua_pushvalue(lstate, -2);
+            let nargs = push_args(lstate)?;
+
+            traceback::pcall(lstate, nargs + 1, 1)?;
+            R::pop(lstate)
+        })
+    }
+
+    unsafe fn push_ref(&self, lstate: *mut State) {
+        ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, self.lua_ref);
+    }
+
+    /// Pops the table at the top of the stack without checking its type.
+    pub(crate) unsafe fn pop_unchecked(lstate: *mut State) -> Self {
+        Self { lua_ref: ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX) }
+    }
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Table {
+    fn clone(&self) -> Self {
+        unsafe {
+            crate::with_state(|lstate| {
+                self.push_ref(lstate);
+                Self::pop_unchecked(lstate)
+            })
+        }
+    }
+}
+
+impl Drop for Table {
+    fn drop(&mut self) {
+        unsafe {
+            crate::with_state(|lstate| {
+                ffi::luaL_unref(lstate, ffi::LUA_REGISTRYINDEX, self.lua_ref)
+            })
+        }
+    }
+}
+
+impl fmt::Debug for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Table").field("len", &self.len()).finish()
+    }
+}
+
+impl Pushable for Table {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        self.push_ref(lstate);
+        Ok(1)
+    }
+}
+
+impl Poppable for Table {
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        if ffi::lua_gettop(lstate) == 0 {
+            return Err(Error::PopEmptyStack);
+        }
+
+        match ffi::lua_type(lstate, -1) {
+            ffi::LUA_TTABLE => Ok(Self::pop_unchecked(lstate)),
+            other => {
+                Err(Error::pop_wrong_type::<Self>(ffi::LUA_TTABLE, other))
+            },
+        }
+    }
+}
+
+impl<K: Poppable, V: Poppable> Iterator for Pairs<K, V> {
+    type Item = Result<(K, V), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        let last_key = self.last_key.take();
+
+        self.table.with_table(|lstate| unsafe {
+            match last_key {
+                Some(lua_ref) => {
+                    ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
+                    ffi::luaL_unref(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
+                },
+                None => ffi::lua_pushnil(lstate),
+            }
+
+            if ffi::lua_next(lstate, -2) == 0 {
+                self.is_done = true;
+                return None;
+            }
+
+            // Store a copy of the key to resume the iteration from it on the
+            // next call.
+            ffi::lua_pushvalue(lstate, -2);
+            self.last_key =
+                Some(ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX));
+
+            let value = V::pop(lstate);
+            let key = K::pop(lstate);
+
+            Some(key.and_then(|key| Ok((key, value?))))
+        })
+    }
+}
+
+impl<K, V> Drop for Pairs<K, V> {
+    fn drop(&mut self) {
+        if let Some(lua_ref) = self.last_key.take() {
+            crate::function::remove(lua_ref);
+        }
+    }
+}
+
+impl<V: Poppable> Iterator for Ipairs<V> {
+    type Item = Result<(usize, V), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        let index = self.index + 1;
+
+        self.table.with_table(|lstate| unsafe {
+            ffi::lua_rawgeti(lstate, -1, index as _);
+
+            if ffi::lua_type(lstate, -1) == ffi::LUA_TNIL {
+                self.is_done = true;
+                return None;
+            }
+
+            self.index = index;
+
+            Some(V::pop(lstate).map(|value| (index, value)))
+        })
+    }
+}
+
+unsafe extern "C" fn get(lstate: *mut State) -> c_int {
+    ffi::lua_gettable(lstate, 1);
+    1
+}
+
+unsafe extern "C" fn set(lstate: *mut State) -> c_int {
+    ffi::lua_settable(lstate, 1);
+    0
+}
+
+unsafe extern "C" fn raw_get(lstate: *mut State) -> c_int {
+    ffi::lua_rawget(lstate, 1);
+    1
+}
+
+unsafe extern "C" fn raw_set(lstate: *mut State) -> c_int {
+    ffi::lua_rawset(lstate, 1);
+    0
+}
@@ -0,0 +1,368 @@
+use core::ffi::{c_int, c_void};
+use core::fmt;
+use core::future::Future;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::ffi::{self, State};
+use crate::{Error, Poppable, Pushable, Traceback, traceback, utils};
+
+/// A handle to a Lua thread, i.e. a coroutine.
+///
+/// Just like a [`Table`](crate::Table), the thread is kept alive by a
+/// reference stored in the Lua registry, which is released when the `Thread`
+/// is dropped.
+pub struct Thread {
+    lua_ref: c_int,
+
+    /// The thread's state, which stays valid for as long as the registry
+    /// holds a reference to the thread.
+    state: *mut State,
+}
+
+/// The status of a [`Thread`], as returned by [`Thread::status`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ThreadStatus {
+    /// The thread can be resumed, either because it hasn't started yet or
+    /// because it yielded.
+    Suspended,
+
+    /// The thread is currently running, or it resumed another thread and is
+    /// waiting for it to yield or return.
+    Running,
+
+    /// The thread either returned or raised an error, and can't be resumed
+    /// anymore.
+    Dead,
+}
+
+/// A wrapper around the return value of a Rust function which yields the
+/// coroutine the function was called from instead of returning.
+///
+/// The wrapped values are passed to the `resume()` call which resumed the
+/// coroutine. The arguments of the next `resume()` become the return values
+/// of the Rust function.
+///
+/// Yielding is only supported when the `Yield` is the whole return value of
+/// a Rust function called from Lua code running in a coroutine. Yielding
+/// from the main thread raises an error.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::Function;
+/// use nvim_oxi::lua::Yield;
+///
+/// // Yields the number it's called with, then returns whatever is passed to
+/// // the next `coroutine.resume()`.
+/// let ping = Function::from_fn(|n: i32| Yield(n));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Yield<T>(pub T);
+
+/// The future returned by [`Thread::run`].
+///
+/// Every time it's polled it resumes the coroutine, and if the coroutine
+/// yields it wakes itself up to resume it again on the next poll. It
+/// resolves to the coroutine's return values once it finishes.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Run<A, R> {
+    thread: Thread,
+
+    /// The arguments of the first resume, `None` once it has happened.
+    args: Option<A>,
+
+    _results: PhantomData<fn() -> R>,
+}
+
+/// The address of this static is pushed after the values of a [`Yield`] to
+/// signal the callback's caller that it should yield.
+static YIELD_MARKER: u8 = 0;
+
+impl Thread {
+    /// Creates a new coroutine which will execute the given function when
+    /// first resumed.
+    pub fn new<F: Pushable>(fun: F) -> Result<Self, Error> {
+        unsafe {
+            crate::with_state(|lstate| {
+                let top = ffi::lua_gettop(lstate);
+
+                let thread_state = ffi::lua_newthread(lstate);
+
+                if let Err(err) = fun.push(lstate) {
+                    ffi::lua_settop(lstate, top);
+                    return Err(err);
+                }
+
+                match ffi::lua_type(lstate, -1) {
+                    ffi::LUA_TFUNCTION => {
+                        ffi::lua_xmove(lstate, thread_state, 1);
+                        Ok(Self::pop_unchecked(lstate))
+                    },
+                    other => {
+                        ffi::lua_settop(lstate, top);
+                        Err(Error::push_error(
+                            core::any::type_name::<F>(),
+                            format!(
+                                "expected a function, found a {} instead",
+                                utils::type_name(other)
+                            ),
+                        ))
+                    },
+                }
+            })
+        }
+    }
+
+    /// Starts or resumes the execution of the coroutine, passing it the
+    /// given arguments.
+    ///
+    /// Returns the values passed to `coroutine.yield()` (or to a [`Yield`])
+    /// if the coroutine yielded, or its return values if it finished. Errors
+    /// raised by the coroutine are returned as [`Error::RuntimeError`]s.
+    pub fn resume<A, R>(&self, args: A) -> Result<R, Error>
+    where
+        A: Pushable,
+        R: Poppable,
+    {
+        unsafe {
+            self.resume_raw(args)?;
+            self.pop_results()
+        }
+    }
+
+    /// Returns a future which runs the coroutine to completion, passing it
+    /// the given arguments the first time it's resumed.
+    ///
+    /// The coroutine is resumed every time the future is polled. When it
+    /// yields, the yielded values are discarded and the future wakes itself
+    /// up, so that the executor it's spawned on (e.g. Neovim's event loop)
+    /// resumes it again on its next iteration. The future resolves to the
+    /// coroutine's return values, or to the error it raised.
+    pub fn run<A, R>(self, args: A) -> Run<A, R>
+    where
+        A: Pushable + Unpin,
+        R: Poppable,
+    {
+        Run { thread: self, args: Some(args), _results: PhantomData }
+    }
+
+    /// Resumes the coroutine, leaving the values it yielded or returned on
+    /// its stack. Returns whether the coroutine yielded.
+    unsafe fn resume_raw<A: Pushable>(&self, args: A) -> Result<bool, Error> {
+        if self.status() != ThreadStatus::Suspended {
+            return Err(Error::RuntimeError {
+                message: "cannot resume non-suspended coroutine".to_owned(),
+                traceback: None,
+            });
+        }
+
+        // The coroutine's stack isn't guaranteed to have room for the
+        // arguments, so we give them the same space Lua gives to C functions.
+        if ffi::lua_checkstack(self.state, ffi::LUA_MINSTACK) == 0 {
+            return Err(Error::push_error(
+                core::any::type_name::<A>(),
+                "can't grow the coroutine's stack".to_owned(),
+            ));
+        }
+
+        let top = ffi::lua_gettop(self.state);
+
+        let nargs = match args.push(self.state) {
+            Ok(nargs) => nargs,
+            Err(err) => {
+                // Don't leave half of the arguments on the stack, or they
+                // would be passed to the next resume.
+                ffi::lua_settop(self.state, top);
+                return Err(err);
+            },
+        };
+
+        match ffi::lua_resume(self.state, nargs) {
+            ffi::LUA_OK => Ok(false),
+            ffi::LUA_YIELD => Ok(true),
+            ffi::LUA_ERRMEM => {
+                Err(Error::MemoryError(traceback::pop_message(self.state)))
+            },
+            _ => Err(self.pop_error()),
+        }
+    }
+
+    /// Pops all the values the coroutine yielded or returned.
+    unsafe fn pop_results<R: Poppable>(&self) -> Result<R, Error> {
+        R::pop_above(self.state, 0).inspect_err(|_| {
+            // Leftover values would be passed to the next resume, and would
+            // make a finished coroutine look suspended.
+            ffi::lua_settop(self.state, 0);
+        })
+    }
+
+    /// Returns the status of the coroutine.
+    pub fn status(&self) -> ThreadStatus {
+        unsafe {
+            match ffi::lua_status(self.state) {
+                ffi::LUA_YIELD => ThreadStatus::Suspended,
+
+                ffi::LUA_OK => {
+                    let mut ar = MaybeUninit::<ffi::lua_Debug>::uninit();
+
+                    if ffi::lua_getstack(self.state, 0, ar.as_mut_ptr()) > 0 {
+                        ThreadStatus::Running
+                    } else if ffi::lua_gettop(self.state) == 0 {
+                        ThreadStatus::Dead
+                    } else {
+                        // The function hasn't been called yet.
+                        ThreadStatus::Suspended
+                    }
+                },
+
+                // The coroutine raised an error.
+                _ => ThreadStatus::Dead,
+            }
+        }
+    }
+
+    /// Returns whether the coroutine can be resumed.
+    pub fn is_resumable(&self) -> bool {
+        self.status() == ThreadStatus::Suspended
+    }
+
+    /// Pops the error raised by the coroutine, capturing the coroutine's
+    /// stack traceback.
+    unsafe fn pop_error(&self) -> Error {
+        let traceback = crate::with_state(|lstate| {
+            ffi::luaL_traceback(lstate, self.state, core::ptr::null(), 0);
+            Traceback::parse(&traceback::pop_message(lstate))
+        });
+
+        let message = traceback::pop_message(self.state);
+
+        Error::RuntimeError { message, traceback: Some(traceback) }
+    }
+
+    /// Pops the thread at the top of the stack without checking its type.
+    unsafe fn pop_unchecked(lstate: *mut State) -> Self {
+        let state = ffi::lua_tothread(lstate, -1);
+        let lua_ref = ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX);
+        Self { lua_ref, state }
+    }
+}
+
+impl<A, R> Future for Run<A, R>
+where
+    A: Pushable + Unpin,
+    R: Poppable,
+{
+    type Output = Result<R, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let yielded = unsafe {
+            match this.args.take() {
+                Some(args) => this.thread.resume_raw(args),
+                None => this.thread.resume_raw(()),
+            }
+        };
+
+        match yielded {
+            Ok(true) => {
+                unsafe { ffi::lua_settop(this.thread.state, 0) };
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            },
+            Ok(false) => Poll::Ready(unsafe { this.thread.pop_results() }),
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl Clone for Thread {
+    fn clone(&self) -> Self {
+        unsafe {
+            crate::with_state(|lstate| {
+                ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, self.lua_ref);
+                Self::pop_unchecked(lstate)
+            })
+        }
+    }
+}
+
+impl Drop for Thread {
+    fn drop(&mut self) {
+        unsafe {
+            crate::with_state(|lstate| {
+                ffi::luaL_unref(lstate, ffi::LUA_REGISTRYINDEX, self.lua_ref)
+            })
+        }
+    }
+}
+
+impl fmt::Debug for Thread {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Thread").field("status", &self.status()).finish()
+    }
+}
+
+impl Pushable for Thread {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, self.lua_ref);
+        Ok(1)
+    }
+}
+
+impl Poppable for Thread {
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        if ffi::lua_gettop(lstate) == 0 {
+            return Err(Error::PopEmptyStack);
+        }
+
+        match ffi::lua_type(lstate, -1) {
+            ffi::LUA_TTHREAD => Ok(Self::pop_unchecked(lstate)),
+            other => {
+                Err(Error::pop_wrong_type::<Self>(ffi::LUA_TTHREAD, other))
+            },
+        }
+    }
+}
+
+impl<T: Pushable> Pushable for Yield<T> {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        let nvalues = self.0.push(lstate)?;
+        ffi::lua_pushlightuserdata(lstate, yield_marker());
+        Ok(nvalues + 1)
+    }
+}
+
+fn yield_marker() -> *mut c_void {
+    &YIELD_MARKER as *const u8 as *mut c_void
+}
+
+/// Called with the `nresults` values returned by a Rust callback at the top
+/// of the stack. If they were pushed by a [`Yield`] the current coroutine is
+/// yielded, otherwise the values are returned as is.
+pub(crate) unsafe fn return_or_yield(
+    lstate: *mut State,
+    nresults: c_int,
+) -> c_int {
+    let is_yield = nresults > 0
+        && ffi::lua_type(lstate, -1) == ffi::LUA_TLIGHTUSERDATA
+        && ffi::lua_touserdata(lstate, -1) == yield_marker();
+
+    if !is_yield {
+        return nresults;
+    }
+
+    ffi::lua_pop(lstate, 1);
+
+    let is_main_thread = ffi::lua_pushthread(lstate) == 1;
+    ffi::lua_pop(lstate, 1);
+
+    if is_main_thread {
+        utils::push_error("attempt to yield from outside a coroutine", lstate);
+    }
+
+    ffi::lua_yield(lstate, nresults - 1)
+}
@@ -0,0 +1,199 @@
+use core::ffi::{CStr, c_int};
+use core::fmt;
+
+use crate::ffi::{self, State};
+use crate::{Error, macros::cstr, utils};
+
+/// The stack traceback of a Lua error, as returned by Lua's
+/// `debug.traceback()`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Traceback {
+    frames: Vec<TracebackFrame>,
+}
+
+/// A single frame of a [`Traceback`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TracebackFrame {
+    /// The chunk the function was defined in, e.g. `init.lua`, `[C]` or
+    /// `[string "..."]`.
+    pub source: String,
+
+    /// The line being executed, or `None` for C functions and for the
+    /// frames elided from long tracebacks.
+    pub line: Option<u32>,
+
+    /// What was executing at that level, e.g. `function 'foo'` or `main
+    /// chunk`.
+    pub what: String,
+}
+
+impl Traceback {
+    /// Returns the frames of the traceback, starting from the innermost one.
+    pub fn frames(&self) -> &[TracebackFrame] {
+        &self.frames
+    }
+
+    /// Parses the output of `debug.traceback()`.
+    pub(crate) fn parse(traceback: &str) -> Self {
+        let frames = traceback
+            .lines()
+            .skip_while(|line| !line.starts_with("stack traceback:"))
+            .skip(1)
+            .map(TracebackFrame::parse)
+            .collect();
+
+        Self { frames }
+    }
+}
+
+impl TracebackFrame {
+    fn parse(line: &str) -> Self {
+        let line = line.trim_start();
+
+        let (location, what) = match line.split_once(": in ") {
+            Some((location, what)) => (location, what),
+            None => (line, ""),
+        };
+
+        let (source, line) = match location.rsplit_once(':') {
+            Some((source, line)) if line.parse::<u32>().is_ok() => {
+                (source, line.parse().ok())
+            },
+            _ => (location, None),
+        };
+
+        Self { source: source.to_owned(), line, what: what.to_owned() }
+    }
+}
+
+impl fmt::Display for Traceback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("stack traceback:")?;
+        for frame in &self.frames {
+            write!(f, "\n\t{frame}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TracebackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)?;
+
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+
+        if !self.what.is_empty() {
+            write!(f, ": in {}", self.what)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Calls the function below the `nargs` arguments at the top of the stack
+/// in protected mode, using a message handler which captures the stack
+/// traceback of the error, if any.
+///
+/// Just like [`lua_pcall`](ffi::lua_pcall), the function and its arguments
+/// are replaced by its `nresults` return values if the call succeeds. On
+/// failure they're popped and the error is returned.
+pub(crate) unsafe fn pcall(
+    lstate: *mut State,
+    nargs: c_int,
+    nresults: c_int,
+) -> Result<(), Error> {
+    // Insert the message handler below the function.
+    let handler_idx = ffi::lua_gettop(lstate) - nargs;
+    ffi::lua_pushcfunction(lstate, message_handler);
+    ffi::lua_insert(lstate, handler_idx);
+
+    let retv = ffi::lua_pcall(lstate, nargs, nresults, handler_idx);
+
+    let result = match retv {
+        ffi::LUA_OK => Ok(()),
+        err_code => Err(pop_error(lstate, err_code)),
+    };
+
+    ffi::lua_remove(lstate, handler_idx);
+
+    result
+}
+
+/// The message handler passed to `lua_pcall`, which replaces the error
+/// object with a `{ message, traceback }` table.
+unsafe extern "C" fn message_handler(lstate: *mut State) -> c_int {
+    ffi::lua_createtable(lstate, 0, 2);
+
+    ffi::lua_pushvalue(lstate, 1);
+    ffi::lua_setfield(lstate, -2, cstr!("message"));
+
+    ffi::lua_getglobal(lstate, cstr!("debug"));
+
+    if ffi::lua_type(lstate, -1) == ffi::LUA_TTABLE {
+        ffi::lua_getfield(lstate, -1, cstr!("traceback"));
+
+        if ffi::lua_type(lstate, -1) == ffi::LUA_TFUNCTION {
+            // Skip the message handler's own frame.
+            ffi::lua_pushstring(lstate, cstr!(""));
+            ffi::lua_pushinteger(lstate, 2);
+            ffi::lua_call(lstate, 2, 1);
+            ffi::lua_setfield(lstate, -3, cstr!("traceback"));
+        } else {
+            ffi::lua_pop(lstate, 1);
+        }
+    }
+
+    ffi::lua_pop(lstate, 1);
+
+    1
+}
+
+/// Pops the error object left on the stack by a call to [`pcall`] that
+/// returned the error code `code`, converting it into an [`Error`].
+unsafe fn pop_error(lstate: *mut State, code: c_int) -> Error {
+    // The message handler isn't called for memory errors.
+    if code == ffi::LUA_ERRMEM {
+        let message = pop_message(lstate);
+        return Error::MemoryError(message);
+    }
+
+    if ffi::lua_type(lstate, -1) != ffi::LUA_TTABLE {
+        let message = pop_message(lstate);
+        return Error::RuntimeError { message, traceback: None };
+    }
+
+    ffi::lua_getfield(lstate, -1, cstr!("traceback"));
+
+    let traceback = match ffi::lua_type(lstate, -1) {
+        ffi::LUA_TSTRING => Some(Traceback::parse(&pop_message(lstate))),
+        _ => {
+            ffi::lua_pop(lstate, 1);
+            None
+        },
+    };
+
+    ffi::lua_getfield(lstate, -1, cstr!("message"));
+    let message = pop_message(lstate);
+
+    // Pop the table.
+    ffi::lua_pop(lstate, 1);
+
+    Error::RuntimeError { message, traceback }
+}
+
+/// Pops the error message at the top of the stack.
+pub(crate) unsafe fn pop_message(lstate: *mut State) -> String {
+    let ptr = ffi::lua_tostring(lstate, -1);
+
+    let message = if ptr.is_null() {
+        format!("(error object is a {} value)", utils::debug_type(lstate, -1))
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    };
+
+    ffi::lua_pop(lstate, 1);
+
+    message
+}
@@ -0,0 +1,559 @@
+use core::any::TypeId;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::ffi::c_int;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::{fmt, mem, ptr};
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use crate::ffi::{self, State};
+use crate::function::{Callback, push_callback};
+use crate::{Error, IntoResult, Poppable, Pushable, utils};
+
+thread_local! {
+    /// The registry refs of the metatables of every `UserData` type that has
+    /// been pushed on the stack at least once.
+    static METATABLES: RefCell<HashMap<TypeId, c_int>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Trait implemented by Rust types that can be exposed to Lua as userdata.
+///
+/// Every `T: UserData` implements [`Pushable`], and pushing it on the stack
+/// moves it into a new Lua userdata whose metatable is built from the
+/// methods, fields and metamethods registered in [`UserData::register`].
+/// The value is dropped when the userdata is garbage collected.
+///
+/// A userdata can be borrowed back from the stack as a [`UserDataRef`] or a
+/// [`UserDataRefMut`], which enforce Rust's borrowing rules at runtime.
+///
+/// # Examples
+///
+/// ```ignore
+/// struct Counter(u32);
+///
+/// impl UserData for Counter {
+///     fn register(registry: &mut UserDataRegistry<Self>) {
+///         registry.add_method("get", |this, ()| this.0);
+///         registry.add_method_mut("incr", |this, by: u32| this.0 += by);
+///     }
+/// }
+/// ```
+///
+/// From Lua the counter can then be used as `counter:incr(1)`.
+pub trait UserData: Sized + 'static {
+    /// Registers the methods, fields and metamethods of the type.
+    fn register(registry: &mut UserDataRegistry<Self>) {
+        let _ = registry;
+    }
+}
+
+/// The metamethods that can be registered via
+/// [`UserDataRegistry::add_meta_method`].
+///
+/// `__index`, `__newindex` and `__gc` are managed by the registry itself, and
+/// can't be overridden.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum MetaMethod {
+    /// `__call`, called when the userdata is called like a function.
+    Call,
+
+    /// `__concat`, the `..` operator.
+    Concat,
+
+    /// `__eq`, the `==` operator. Only called when both operands are
+    /// userdata of the same type.
+    Eq,
+
+    /// `__le`, the `<=` operator.
+    Le,
+
+    /// `__len`, the `#` operator.
+    Len,
+
+    /// `__lt`, the `<` operator.
+    Lt,
+
+    /// `__tostring`, used by Lua's `tostring()` and `print()`.
+    ToString,
+}
+
+/// The methods, fields and metamethods of a [`UserData`] type.
+pub struct UserDataRegistry<T> {
+    methods: Vec<(String, Callback)>,
+    getters: Vec<(String, Callback)>,
+    setters: Vec<(String, Callback)>,
+    meta_methods: Vec<(MetaMethod, Callback)>,
+    ty: PhantomData<T>,
+}
+
+/// A shared borrow of a [`UserData`] value stored in Lua.
+///
+/// The userdata is kept alive for as long as the borrow exists.
+pub struct UserDataRef<T: UserData> {
+    cell: *const UserDataCell<T>,
+    lua_ref: c_int,
+}
+
+/// An exclusive borrow of a [`UserData`] value stored in Lua.
+///
+/// The userdata is kept alive for as long as the borrow exists.
+pub struct UserDataRefMut<T: UserData> {
+    cell: *const UserDataCell<T>,
+    lua_ref: c_int,
+}
+
+/// The value pointed to by a userdata.
+struct UserDataCell<T> {
+    /// The number of shared borrows, or `-1` if the value is mutably
+    /// borrowed.
+    borrow: Cell<isize>,
+    value: UnsafeCell<T>,
+}
+
+impl MetaMethod {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Call => "__call",
+            Self::Concat => "__concat",
+            Self::Eq => "__eq",
+            Self::Le => "__le",
+            Self::Len => "__len",
+            Self::Lt => "__lt",
+            Self::ToString => "__tostring",
+        }
+    }
+}
+
+impl<T: UserData> UserDataRegistry<T> {
+    fn new() -> Self {
+        Self {
+            methods: Vec::new(),
+            getters: Vec::new(),
+            setters: Vec::new(),
+            meta_methods: Vec::new(),
+            ty: PhantomData,
+        }
+    }
+
+    /// Registers a method which can be called from Lua as
+    /// `userdata:name(args)`.
+    pub fn add_method<F, A, R, O>(&mut self, name: &str, fun: F)
+    where
+        F: Fn(&T, A) -> R + 'static,
+        A: Poppable,
+        R: IntoResult<O>,
+        O: Pushable,
+        R::Error: core::error::Error + 'static,
+    {
+        self.methods.push((name.to_owned(), method(fun)));
+    }
+
+    /// Same as [`add_method`](Self::add_method), but the method gets a
+    /// mutable reference to the value.
+    pub fn add_method_mut<F, A, R, O>(&mut self, name: &str, fun: F)
+    where
+        F: Fn(&mut T, A) -> R + 'static,
+        A: Poppable,
+        R: IntoResult<O>,
+        O: Pushable,
+        R::Error: core::error::Error + 'static,
+    {
+        self.methods.push((name.to_owned(), method_mut(fun)));
+    }
+
+    /// Registers a function which doesn't take the userdata as its first
+    /// argument, and which can be called from Lua as `userdata.name(args)`.
+    pub fn add_function<F, A, R, O>(&mut self, name: &str, fun: F)
+    where
+        F: Fn(A) -> R + 'static,
+        A: Poppable,
+        R: IntoResult<O>,
+        O: Pushable,
+        R::Error: core::error::Error + 'static,
+    {
+        let callback: Callback = Box::new(move |lstate| unsafe {
+            let args = A::pop_above(lstate, 0)?;
+            let ret = fun(args)
+                .into_result()
+                .map_err(Error::push_error_from_err::<R, _>)?;
+            ret.push(lstate)
+        });
+
+        self.methods.push((name.to_owned(), callback));
+    }
+
+    /// Registers a field which can be read from Lua as `userdata.name`.
+    pub fn add_field<F, R, O>(&mut self, name: &str, getter: F)
+    where
+        F: Fn(&T) -> R + 'static,
+        R: IntoResult<O>,
+        O: Pushable,
+        R::Error: core::error::Error + 'static,
+    {
+        let getter = method(move |this: &T, ()| getter(this));
+        self.getters.push((name.to_owned(), getter));
+    }
+
+    /// Registers a field which can be assigned from Lua as
+    /// `userdata.name = value`.
+    pub fn add_field_setter<F, V, R>(&mut self, name: &str, setter: F)
+    where
+        F: Fn(&mut T, V) -> R + 'static,
+        V: Poppable,
+        R: IntoResult<()>,
+        R::Error: core::error::Error + 'static,
+    {
+        let setter =
+            method_mut::<T, _, V, _, ()>(move |this: &mut T, value: V| {
+                setter(this, value).into_result()
+            });
+        self.setters.push((name.to_owned(), setter));
+    }
+
+    /// Registers a metamethod. The function gets a reference to the value
+    /// followed by the other arguments Lua passes to the metamethod.
+    pub fn add_meta_method<F, A, R, O>(&mut self, meta: MetaMethod, fun: F)
+    where
+        F: Fn(&T, A) -> R + 'static,
+        A: Poppable,
+        R: IntoResult<O>,
+        O: Pushable,
+        R::Error: core::error::Error + 'static,
+    {
+        self.meta_methods.push((meta, method(fun)));
+    }
+
+    /// Pushes the metatable built from the registry on the stack.
+    unsafe fn push_metatable(self, lstate: *mut State) {
+        ffi::lua_createtable(lstate, 0, self.meta_methods.len() as c_int + 3);
+
+        for (meta, callback) in self.meta_methods {
+            push_callback(lstate, callback);
+            set_field(lstate, meta.name());
+        }
+
+        push_table(lstate, self.methods);
+        push_table(lstate, self.getters);
+        ffi::lua_pushcclosure(lstate, index, 2);
+        set_field(lstate, "__index");
+
+        push_table(lstate, self.setters);
+        ffi::lua_pushcclosure(lstate, newindex, 1);
+        set_field(lstate, "__newindex");
+
+        ffi::lua_pushcfunction(lstate, gc::<T>);
+        set_field(lstate, "__gc");
+
+        // Hide the metatable from Lua's `getmetatable()`.
+        ffi::lua_pushboolean(lstate, 0);
+        set_field(lstate, "__metatable");
+    }
+}
+
+impl<T: UserData> Pushable for T {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        let cell = Box::new(UserDataCell {
+            borrow: Cell::new(0),
+            value: UnsafeCell::new(self),
+        });
+
+        let ud = ffi::lua_newuserdata(lstate, mem::size_of::<*mut ()>());
+        ptr::write(ud as *mut *mut UserDataCell<T>, Box::into_raw(cell));
+
+        push_metatable::<T>(lstate);
+        ffi::lua_setmetatable(lstate, -2);
+
+        Ok(1)
+    }
+}
+
+impl<T: UserData> UserDataRef<T> {
+    unsafe fn from_cell(
+        lstate: *mut State,
+        cell: *const UserDataCell<T>,
+    ) -> Result<Self, Error> {
+        let borrow = &(*cell).borrow;
+
+        if borrow.get() < 0 {
+            return Err(Error::pop_error(
+                core::any::type_name::<T>(),
+                "already mutably borrowed",
+            ));
+        }
+
+        borrow.set(borrow.get() + 1);
+
+        let lua_ref = ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX);
+
+        Ok(Self { cell, lua_ref })
+    }
+}
+
+impl<T: UserData> UserDataRefMut<T> {
+    unsafe fn from_cell(
+        lstate: *mut State,
+        cell: *const UserDataCell<T>,
+    ) -> Result<Self, Error> {
+        let borrow = &(*cell).borrow;
+
+        if borrow.get() != 0 {
+            return Err(Error::pop_error(
+                core::any::type_name::<T>(),
+                "already borrowed",
+            ));
+        }
+
+        borrow.set(-1);
+
+        let lua_ref = ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX);
+
+        Ok(Self { cell, lua_ref })
+    }
+}
+
+impl<T: UserData> Poppable for UserDataRef<T> {
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        let cell = get_cell::<T>(lstate)?;
+        Self::from_cell(lstate, cell)
+    }
+}
+
+impl<T: UserData> Poppable for UserDataRefMut<T> {
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        let cell = get_cell::<T>(lstate)?;
+        Self::from_cell(lstate, cell)
+    }
+}
+
+impl<T: UserData> Deref for UserDataRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*(*self.cell).value.get() }
+    }
+}
+
+impl<T: UserData> Deref for UserDataRefMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*(*self.cell).value.get() }
+    }
+}
+
+impl<T: UserData> DerefMut for UserDataRefMut<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *(*self.cell).value.get() }
+    }
+}
+
+impl<T: UserData> Drop for UserDataRef<T> {
+    fn drop(&mut self) {
+        let borrow = unsafe { &(*self.cell).borrow };
+        borrow.set(borrow.get() - 1);
+        crate::function::remove(self.lua_ref);
+    }
+}
+
+impl<T: UserData> Drop for UserDataRefMut<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.cell).borrow.set(0) };
+        crate::function::remove(self.lua_ref);
+    }
+}
+
+impl<T: UserData + fmt::Debug> fmt::Debug for UserDataRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: UserData + fmt::Debug> fmt::Debug for UserDataRefMut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Pushes the metatable of `T` on the stack, creating it if this is the
+/// first time a `T` is pushed.
+unsafe fn push_metatable<T: UserData>(lstate: *mut State) {
+    let id = TypeId::of::<T>();
+
+    let lua_ref = METATABLES.with(|tables| tables.borrow().get(&id).copied());
+
+    if let Some(lua_ref) = lua_ref {
+        ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
+        return;
+    }
+
+    let mut registry = UserDataRegistry::<T>::new();
+    T::register(&mut registry);
+    registry.push_metatable(lstate);
+
+    ffi::lua_pushvalue(lstate, -1);
+    let lua_ref = ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX);
+    METATABLES.with(|tables| tables.borrow_mut().insert(id, lua_ref));
+}
+
+/// Returns a pointer to the `T` stored in the userdata at the top of the
+/// stack, without popping it.
+unsafe fn get_cell<T: UserData>(
+    lstate: *mut State,
+) -> Result<*const UserDataCell<T>, Error> {
+    if ffi::lua_gettop(lstate) == 0 {
+        return Err(Error::PopEmptyStack);
+    }
+
+    let ty = ffi::lua_type(lstate, -1);
+
+    if ty != ffi::LUA_TUSERDATA {
+        return Err(Error::pop_wrong_type::<T>(ffi::LUA_TUSERDATA, ty));
+    }
+
+    if ffi::lua_getmetatable(lstate, -1) == 0 {
+        return Err(Error::pop_wrong_type_at_idx::<T>(lstate, -1));
+    }
+
+    push_metatable::<T>(lstate);
+    let is_same_type = ffi::lua_rawequal(lstate, -1, -2) == 1;
+    ffi::lua_pop(lstate, 2);
+
+    if !is_same_type {
+        return Err(Error::pop_error(
+            core::any::type_name::<T>(),
+            "userdata is of a different type",
+        ));
+    }
+
+    let ud = ffi::lua_touserdata(lstate, -1) as *const *const UserDataCell<T>;
+
+    Ok(*ud)
+}
+
+/// Returns a callback which borrows the userdata passed as the first
+/// argument before calling `fun`.
+fn method<T, F, A, R, O>(fun: F) -> Callback
+where
+    T: UserData,
+    F: Fn(&T, A) -> R + 'static,
+    A: Poppable,
+    R: IntoResult<O>,
+    O: Pushable,
+    R::Error: core::error::Error + 'static,
+{
+    Box::new(move |lstate| unsafe {
+        let this = pop_self::<UserDataRef<T>>(lstate)?;
+        let args = A::pop_above(lstate, 0)?;
+        let ret = fun(&this, args)
+            .into_result()
+            .map_err(Error::push_error_from_err::<R, _>)?;
+        ret.push(lstate)
+    })
+}
+
+/// Same as [`method`], but borrows the userdata mutably.
+fn method_mut<T, F, A, R, O>(fun: F) -> Callback
+where
+    T: UserData,
+    F: Fn(&mut T, A) -> R + 'static,
+    A: Poppable,
+    R: IntoResult<O>,
+    O: Pushable,
+    R::Error: core::error::Error + 'static,
+{
+    Box::new(move |lstate| unsafe {
+        let mut this = pop_self::<UserDataRefMut<T>>(lstate)?;
+        let args = A::pop_above(lstate, 0)?;
+        let ret = fun(&mut this, args)
+            .into_result()
+            .map_err(Error::push_error_from_err::<R, _>)?;
+        ret.push(lstate)
+    })
+}
+
+/// Pops the first argument of a method, leaving the rest of the arguments on
+/// the stack.
+unsafe fn pop_self<S: Poppable>(lstate: *mut State) -> Result<S, Error> {
+    if ffi::lua_gettop(lstate) == 0 {
+        return Err(Error::PopEmptyStack);
+    }
+
+    ffi::lua_pushvalue(lstate, 1);
+    let this = S::pop(lstate)?;
+    ffi::lua_remove(lstate, 1);
+    Ok(this)
+}
+
+/// Pushes a table mapping the given names to their callbacks.
+unsafe fn push_table(lstate: *mut State, callbacks: Vec<(String, Callback)>) {
+    ffi::lua_createtable(lstate, 0, callbacks.len() as c_int);
+
+    for (name, callback) in callbacks {
+        push_callback(lstate, callback);
+        set_field(lstate, &name);
+    }
+}
+
+/// Pops the value at the top of the stack and assigns it to the `name` field
+/// of the table right below it.
+unsafe fn set_field(lstate: *mut State, name: &str) {
+    let name = CString::new(name).expect("field names can't contain NUL");
+    ffi::lua_setfield(lstate, -2, name.as_ptr());
+}
+
+/// The `__index` metamethod, whose upvalues are the tables of methods and
+/// getters.
+unsafe extern "C" fn index(lstate: *mut State) -> c_int {
+    ffi::lua_pushvalue(lstate, 2);
+    ffi::lua_rawget(lstate, ffi::lua_upvalueindex(2));
+
+    if ffi::lua_type(lstate, -1) == ffi::LUA_TFUNCTION {
+        ffi::lua_pushvalue(lstate, 1);
+        ffi::lua_call(lstate, 1, 1);
+        return 1;
+    }
+
+    ffi::lua_pop(lstate, 1);
+    ffi::lua_pushvalue(lstate, 2);
+    ffi::lua_rawget(lstate, ffi::lua_upvalueindex(1));
+    1
+}
+
+/// The `__newindex` metamethod, whose only upvalue is the table of setters.
+unsafe extern "C" fn newindex(lstate: *mut State) -> c_int {
+    ffi::lua_pushvalue(lstate, 2);
+    ffi::lua_rawget(lstate, ffi::lua_upvalueindex(1));
+
+    if ffi::lua_type(lstate, -1) != ffi::LUA_TFUNCTION {
+        // `lua_error` longjmps out of this function, so the message has to
+        // be dropped before calling it.
+        {
+            let key = utils::debug_value(lstate, 2).to_string();
+            let msg = format!("no settable field named {key:?}");
+            ffi::lua_pushlstring(lstate, msg.as_ptr() as *const _, msg.len());
+        }
+        ffi::lua_error(lstate);
+    }
+
+    ffi::lua_pushvalue(lstate, 1);
+    ffi::lua_pushvalue(lstate, 3);
+    ffi::lua_call(lstate, 2, 0);
+    0
+}
+
+/// The `__gc` metamethod, which drops the value stored in the userdata.
+unsafe extern "C" fn gc<T: UserData>(lstate: *mut State) -> c_int {
+    let ud = ffi::lua_touserdata(lstate, 1) as *mut *mut UserDataCell<T>;
+
+    // Borrows keep the userdata alive, so there can't be any at this point.
+    let cell = mem::replace(&mut *ud, ptr::null_mut());
+
+    if !cell.is_null() {
+        drop(Box::from_raw(cell));
+    }
+
+    0
+}
@@ -0,0 +1,266 @@
+use core::ffi::c_int;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+use crate::ffi::{self, State};
+use crate::{Error, Poppable, Pushable};
+
+/// A variable number of values of the same type.
+///
+/// When used as the arguments of a Rust function called from Lua it collects
+/// all the arguments, and when used as its return value it pushes each
+/// element as a separate return value, just like Lua's `...`.
+///
+/// It can also be used as the last element of a tuple to collect the
+/// remaining arguments, e.g. `(String, Variadic<i32>)` for a function called
+/// as `f("foo", 1, 2, 3)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Variadic<T> {
+    values: Vec<T>,
+}
+
+/// A variable number of values of possibly different types.
+///
+/// The values are kept alive by references stored in the Lua registry, and
+/// can be converted to concrete Rust types via [`get`](MultiValue::get).
+#[derive(Default)]
+pub struct MultiValue {
+    /// The registry references to the values. `nil`s are stored as
+    /// `LUA_REFNIL`, which `lua_rawgeti` maps back to `nil`.
+    refs: Vec<c_int>,
+}
+
+impl<T> Variadic<T> {
+    /// Creates a new empty `Variadic`.
+    pub fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    /// Consumes the `Variadic`, returning its values.
+    pub fn into_inner(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T> Deref for Variadic<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl<T> DerefMut for Variadic<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.values
+    }
+}
+
+impl<T> From<Vec<T>> for Variadic<T> {
+    fn from(values: Vec<T>) -> Self {
+        Self { values }
+    }
+}
+
+impl<T> FromIterator<T> for Variadic<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self { values: iter.into_iter().collect() }
+    }
+}
+
+impl<T> IntoIterator for Variadic<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl<T: Pushable> Pushable for Variadic<T> {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        check_stack::<Self>(lstate, self.values.len())?;
+
+        let mut count = 0;
+        for value in self.values {
+            count += value.push(lstate)?;
+        }
+        Ok(count)
+    }
+}
+
+impl<T: Poppable> Poppable for Variadic<T> {
+    const IS_VARIADIC: bool = true;
+
+    /// Pops all the values on the stack.
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        Self::pop_above(lstate, 0)
+    }
+
+    unsafe fn pop_above(
+        lstate: *mut State,
+        base: c_int,
+    ) -> Result<Self, Error> {
+        let len = (ffi::lua_gettop(lstate) - base).max(0) as usize;
+
+        let values =
+            (0..len).map(|_| T::pop(lstate)).collect::<Result<Vec<_>, _>>();
+
+        let mut values = match values {
+            Ok(values) => values,
+            Err(err) => {
+                // Discard the values that haven't been popped yet.
+                ffi::lua_settop(lstate, base);
+                return Err(err);
+            },
+        };
+
+        values.reverse();
+
+        Ok(Self { values })
+    }
+}
+
+impl MultiValue {
+    /// Creates a new empty `MultiValue`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.refs.len()
+    }
+
+    /// Returns whether there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.refs.is_empty()
+    }
+
+    /// Appends a value to the end.
+    pub fn push_value<T: Pushable>(&mut self, value: T) -> Result<(), Error> {
+        unsafe {
+            crate::with_state(|lstate| {
+                let top = ffi::lua_gettop(lstate);
+
+                if let Err(err) = value.push(lstate) {
+                    ffi::lua_settop(lstate, top);
+                    return Err(err);
+                }
+
+                // Values pushing more than one value on the stack are
+                // flattened.
+                let mut refs = (top..ffi::lua_gettop(lstate))
+                    .map(|_| ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX))
+                    .collect::<Vec<_>>();
+
+                refs.reverse();
+                self.refs.extend(refs);
+
+                Ok(())
+            })
+        }
+    }
+
+    /// Returns the value at the given index as a `T`, or `None` if the index
+    /// is out of bounds.
+    pub fn get<T: Poppable>(&self, idx: usize) -> Option<Result<T, Error>> {
+        let lua_ref = *self.refs.get(idx)?;
+
+        let value = unsafe {
+            crate::with_state(|lstate| {
+                ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
+                T::pop_above(lstate, ffi::lua_gettop(lstate) - 1)
+            })
+        };
+
+        Some(value)
+    }
+}
+
+impl Clone for MultiValue {
+    fn clone(&self) -> Self {
+        let refs = unsafe {
+            crate::with_state(|lstate| {
+                self.refs
+                    .iter()
+                    .map(|&lua_ref| {
+                        ffi::lua_rawgeti(
+                            lstate,
+                            ffi::LUA_REGISTRYINDEX,
+                            lua_ref,
+                        );
+                        ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX)
+                    })
+                    .collect()
+            })
+        };
+
+        Self { refs }
+    }
+}
+
+impl Drop for MultiValue {
+    fn drop(&mut self) {
+        unsafe {
+            crate::with_state(|lstate| {
+                for &lua_ref in &self.refs {
+                    ffi::luaL_unref(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
+                }
+            })
+        }
+    }
+}
+
+impl fmt::Debug for MultiValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MultiValue").field("len", &self.len()).finish()
+    }
+}
+
+impl Pushable for MultiValue {
+    unsafe fn push(self, lstate: *mut State) -> Result<c_int, Error> {
+        check_stack::<Self>(lstate, self.refs.len())?;
+
+        for &lua_ref in &self.refs {
+            ffi::lua_rawgeti(lstate, ffi::LUA_REGISTRYINDEX, lua_ref);
+        }
+
+        Ok(self.refs.len() as c_int)
+    }
+}
+
+impl Poppable for MultiValue {
+    const IS_VARIADIC: bool = true;
+
+    /// Pops all the values on the stack.
+    unsafe fn pop(lstate: *mut State) -> Result<Self, Error> {
+        Self::pop_above(lstate, 0)
+    }
+
+    unsafe fn pop_above(
+        lstate: *mut State,
+        base: c_int,
+    ) -> Result<Self, Error> {
+        let len = (ffi::lua_gettop(lstate) - base).max(0) as usize;
+
+        let mut refs = (0..len)
+            .map(|_| ffi::luaL_ref(lstate, ffi::LUA_REGISTRYINDEX))
+            .collect::<Vec<_>>();
+
+        refs.reverse();
+
+        Ok(Self { refs })
+    }
+}
+
+/// Makes sure the stack has room for `n` more values.
+unsafe fn check_stack<T>(lstate: *mut State, n: usize) -> Result<(), Error> {
+    match ffi::lua_checkstack(lstate, n as c_int) {
+        0 => Err(Error::push_error(
+            core::any::type_name::<T>(),
+            format!("can't grow the stack to fit {n} values"),
+        )),
+        _ => Ok(()),
+    }
+}
@@ -1,5 +1,7 @@
 use proc_macro2::Span;
-use syn::parse::Parse;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
+use syn::{Path, parse_quote};
 
 /// A trait implemented by key-value macro attributes.
 pub(crate) trait KeyedAttribute: Parse {
@@ -57,3 +59,52 @@ impl<T: KeyedAttribute> From<DuplicateError<T>> for syn::Error {
         syn::Error::new(attr.key_span(), ErrorMsg(T::KEY))
     }
 }
+
+/// The `nvim_oxi = path` attribute, used to specify the path to the
+/// `nvim-oxi` crate in the generated code.
+pub(crate) struct NvimOxi {
+    key_span: Span,
+    value: Path,
+}
+
+impl Default for NvimOxi {
+    #[inline]
+    fn default() -> Self {
+        Self { key_span: Span::call_site(), value: parse_quote!(::nvim_oxi) }
+    }
+}
+
+impl From<Path> for NvimOxi {
+    #[inline]
+    fn from(value: Path) -> Self {
+        Self { key_span: Span::call_site(), value }
+    }
+}
+
+impl Parse for NvimOxi {
+    #[inline]
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            key_span: Span::call_site(),
+            value: input.parse::<Keyed<Self>>()?.value,
+        })
+    }
+}
+
+impl KeyedAttribute for NvimOxi {
+    const KEY: &'static str = "nvim_oxi";
+
+    type Value = Path;
+
+    #[inline]
+    fn key_span(&self) -> Span {
+        self.key_span
+    }
+}
+
+impl ToTokens for NvimOxi {
+    #[inline]
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.value.to_tokens(tokens);
+    }
+}
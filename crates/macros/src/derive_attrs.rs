@@ -0,0 +1,400 @@
+//! Serde-like attributes and field parsing shared by the derive macros which
+//! convert Rust types to and from Lua values and Neovim objects.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    Attribute,
+    Fields,
+    Generics,
+    Ident,
+    LitStr,
+    Member,
+    Path,
+    Result,
+    Type,
+    parse_quote,
+};
+
+use crate::common::NvimOxi;
+
+/// The tag key used by default to store the name of an enum variant.
+const DEFAULT_TAG: &str = "type";
+
+/// The attributes on the struct or enum being derived.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) nvim_oxi: NvimOxi,
+    pub(crate) rename_all: Option<RenameRule>,
+    tag: Option<String>,
+}
+
+/// The attributes on a field of a struct or of an enum variant.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) skip: bool,
+    pub(crate) default: Option<FieldDefault>,
+}
+
+/// How to get the value of a field that's missing.
+pub(crate) enum FieldDefault {
+    /// `#[attr(default)]`, i.e. use the field type's `Default` impl.
+    Trait,
+
+    /// `#[attr(default = "path")]`, i.e. call the function at `path`.
+    Path(Path),
+}
+
+/// The attributes on an enum variant.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    pub(crate) rename: Option<String>,
+}
+
+/// A `rename_all` rule, one of the rules supported by serde.
+#[derive(Copy, Clone)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl ContainerAttrs {
+    /// Parses the `#[<name>(..)]` attributes on a struct or enum.
+    pub(crate) fn parse(attrs: &[Attribute], name: &str) -> Result<Self> {
+        Self::parse_with(attrs, name, |_| Ok(false))
+    }
+
+    /// Same as [`parse`](Self::parse), but first gives `extra` a chance to
+    /// parse each attribute. `extra` should return `true` if it recognized
+    /// the attribute.
+    pub(crate) fn parse_with(
+        attrs: &[Attribute],
+        name: &str,
+        mut extra: impl FnMut(&ParseNestedMeta) -> Result<bool>,
+    ) -> Result<Self> {
+        let mut this = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+            attr.parse_nested_meta(|meta| {
+                if extra(&meta)? {
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("nvim_oxi") {
+                    this.nvim_oxi =
+                        NvimOxi::from(meta.value()?.parse::<Path>()?);
+                } else if meta.path.is_ident("rename_all") {
+                    let rule = meta.value()?.parse::<LitStr>()?;
+                    this.rename_all = Some(RenameRule::parse(&rule)?);
+                } else if meta.path.is_ident("tag") {
+                    this.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error("unknown attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the key of the table field holding the name of an enum
+    /// variant.
+    pub(crate) fn tag(&self) -> &str {
+        self.tag.as_deref().unwrap_or(DEFAULT_TAG)
+    }
+}
+
+impl FieldAttrs {
+    /// Parses the `#[<name>(..)]` attributes on a field.
+    pub(crate) fn parse(attrs: &[Attribute], name: &str) -> Result<Self> {
+        let mut this = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    this.rename =
+                        Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("skip") {
+                    this.skip = true;
+                } else if meta.path.is_ident("default") {
+                    this.default = Some(if meta.input.peek(syn::Token![=]) {
+                        let path = meta.value()?.parse::<LitStr>()?;
+                        FieldDefault::Path(path.parse()?)
+                    } else {
+                        FieldDefault::Trait
+                    });
+                } else {
+                    return Err(meta.error("unknown attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the key of a named field, taking into account its `rename`
+    /// attribute and the `rename_all` rule of its struct, if any.
+    pub(crate) fn key(
+        &self,
+        field: &Ident,
+        rename_all: Option<RenameRule>,
+    ) -> String {
+        if let Some(rename) = &self.rename {
+            return rename.clone();
+        }
+
+        let name = field.unraw().to_string();
+
+        match rename_all {
+            Some(rule) => rule.apply_to_field(&name),
+            None => name,
+        }
+    }
+}
+
+impl VariantAttrs {
+    /// Parses the `#[<name>(..)]` attributes on an enum variant.
+    pub(crate) fn parse(attrs: &[Attribute], name: &str) -> Result<Self> {
+        let mut this = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    this.rename =
+                        Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error("unknown attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the name of a variant, taking into account its `rename`
+    /// attribute and the container's `rename_all` rule.
+    pub(crate) fn name(
+        &self,
+        variant: &Ident,
+        container: &ContainerAttrs,
+    ) -> String {
+        if let Some(rename) = &self.rename {
+            return rename.clone();
+        }
+
+        let name = variant.unraw().to_string();
+
+        match container.rename_all {
+            Some(rule) => rule.apply_to_variant(&name),
+            None => name,
+        }
+    }
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> Result<Self> {
+        Ok(match &*rule.value() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "Pascal" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    rule,
+                    "unknown rename rule",
+                ));
+            },
+        })
+    }
+
+    /// Applies the rule to a variant name, which is assumed to be in
+    /// `Pascal`.
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_owned(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => {
+                let mut chars = variant.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase())
+                    .into_iter()
+                    .chain(chars)
+                    .collect()
+            },
+            _ => {
+                let mut snake = String::with_capacity(variant.len() + 4);
+                for (idx, char) in variant.char_indices() {
+                    if idx > 0 && char.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(char.to_ascii_lowercase());
+                }
+                self.apply_to_field(&snake)
+            },
+        }
+    }
+
+    /// Applies the rule to a field name, which is assumed to be in
+    /// `snake_case`.
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => {
+                field.replace('_', "-").to_ascii_uppercase()
+            },
+            Self::Pascal | Self::Camel => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = matches!(self, Self::Pascal);
+                for char in field.chars() {
+                    if char == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(char.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(char);
+                    }
+                }
+                pascal
+            },
+        }
+    }
+}
+
+/// Returns the pattern binding the fields of an enum variant.
+pub(crate) fn destructure(
+    path: TokenStream,
+    fields: &ParsedFields,
+) -> TokenStream {
+    let bindings = fields.iter().map(|field| {
+        let binding = &field.binding;
+        if field.attrs.skip {
+            quote! { _ }
+        } else {
+            quote! { #binding }
+        }
+    });
+
+    match fields.kind {
+        FieldsKind::Named => {
+            let members = fields.iter().map(|field| &field.member);
+            quote! { #path { #(#members: #bindings),* } }
+        },
+        _ => quote! { #path(#(#bindings),*) },
+    }
+}
+
+pub(crate) struct ParsedFields<'a> {
+    pub(crate) kind: FieldsKind,
+    pub(crate) fields: Vec<ParsedField<'a>>,
+}
+
+pub(crate) enum FieldsKind {
+    Named,
+    Unnamed,
+    Unit,
+}
+
+pub(crate) struct ParsedField<'a> {
+    pub(crate) attrs: FieldAttrs,
+    pub(crate) member: Member,
+    pub(crate) binding: Ident,
+    pub(crate) key: Key,
+    pub(crate) ty: &'a Type,
+}
+
+pub(crate) enum Key {
+    Named(String),
+    Index,
+}
+
+impl<'a> ParsedFields<'a> {
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &ParsedField<'a>> {
+        self.fields.iter()
+    }
+}
+
+/// Parses the fields of a struct or of an enum variant, together with their
+/// `#[<name>(..)]` attributes.
+pub(crate) fn parse_fields<'a>(
+    fields: &'a Fields,
+    rename_all: Option<RenameRule>,
+    name: &str,
+) -> Result<ParsedFields<'a>> {
+    let kind = match fields {
+        Fields::Named(_) => FieldsKind::Named,
+        Fields::Unnamed(_) => FieldsKind::Unnamed,
+        Fields::Unit => FieldsKind::Unit,
+    };
+
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let attrs = FieldAttrs::parse(&field.attrs, name)?;
+
+            let (member, binding, key) = match &field.ident {
+                Some(ident) => (
+                    Member::Named(ident.clone()),
+                    format_ident!("__{}", ident),
+                    Key::Named(attrs.key(ident, rename_all)),
+                ),
+                None => (
+                    Member::Unnamed(idx.into()),
+                    format_ident!("__{}", idx),
+                    Key::Index,
+                ),
+            };
+
+            Ok(ParsedField { attrs, member, binding, key, ty: &field.ty })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ParsedFields { kind, fields })
+}
+
+/// Returns whether the fields are those of a newtype struct, i.e. a tuple
+/// struct with a single field, which is converted transparently.
+pub(crate) fn is_newtype(fields: &ParsedFields) -> bool {
+    matches!(fields.kind, FieldsKind::Unnamed)
+        && fields.fields.len() == 1
+        && !fields.fields[0].attrs.skip
+}
+
+/// Adds `bound` to all the type parameters of `generics`.
+pub(crate) fn add_bound(generics: &Generics, bound: TokenStream) -> Generics {
+    let mut generics = generics.clone();
+
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+
+    let where_clause = generics.make_where_clause();
+
+    for param in params {
+        where_clause.predicates.push(parse_quote! { #param: #bound });
+    }
+
+    generics
+}
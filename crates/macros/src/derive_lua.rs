@@ -0,0 +1,326 @@
+use std::ffi::CString;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::*;
+
+use crate::derive_attrs::{
+    ContainerAttrs,
+    FieldDefault,
+    FieldsKind,
+    Key,
+    ParsedFields,
+    VariantAttrs,
+    add_bound,
+    destructure,
+    is_newtype,
+    parse_fields,
+};
+
+/// The name of the attribute used to configure the derives.
+const ATTR: &str = "lua";
+
+pub fn expand_derive_pushable(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = ContainerAttrs::parse(&input.attrs, ATTR)?;
+    let nvim_oxi = &attrs.nvim_oxi;
+    let lua = quote! { #nvim_oxi::lua };
+
+    let body = match &input.data {
+        Data::Struct(data) => push_struct(&data.fields, &attrs, &lua)?,
+        Data::Enum(data) => push_enum(data, &attrs, &lua)?,
+        Data::Union(_) => {
+            let msg = "unions are not supported";
+            return Err(Error::new_spanned(input, msg));
+        },
+    };
+
+    let name = &input.ident;
+    let generics = add_bound(&input.generics, quote! { #lua::Pushable });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #lua::Pushable for #name #ty_generics
+            #where_clause
+        {
+            unsafe fn push(
+                self,
+                lstate: *mut #lua::ffi::State,
+            ) -> ::core::result::Result<::core::ffi::c_int, #lua::Error> {
+                unsafe { #body }
+            }
+        }
+    })
+}
+
+pub fn expand_derive_poppable(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = ContainerAttrs::parse(&input.attrs, ATTR)?;
+    let nvim_oxi = &attrs.nvim_oxi;
+    let lua = quote! { #nvim_oxi::lua };
+
+    let body = match &input.data {
+        Data::Struct(data) => pop_struct(&data.fields, &attrs, &lua)?,
+        Data::Enum(data) => pop_enum(data, &attrs, &lua)?,
+        Data::Union(_) => {
+            let msg = "unions are not supported";
+            return Err(Error::new_spanned(input, msg));
+        },
+    };
+
+    let name = &input.ident;
+    let generics = add_bound(&input.generics, quote! { #lua::Poppable });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #lua::Poppable for #name #ty_generics
+            #where_clause
+        {
+            unsafe fn pop(
+                lstate: *mut #lua::ffi::State,
+            ) -> ::core::result::Result<Self, #lua::Error> {
+                unsafe { #body }
+            }
+        }
+    })
+}
+
+/// Structs with named fields are pushed as tables, newtype structs as their
+/// inner value, tuple structs as arrays and unit structs as `nil`.
+fn push_struct(
+    fields: &Fields,
+    attrs: &ContainerAttrs,
+    lua: &TokenStream,
+) -> Result<TokenStream> {
+    let fields = parse_fields(fields, attrs.rename_all, ATTR)?;
+
+    if is_newtype(&fields) {
+        return Ok(quote! { #lua::Pushable::push(self.0, lstate) });
+    }
+
+    let bindings = fields.iter().map(|field| {
+        let member = &field.member;
+        quote! { self.#member }
+    });
+
+    match fields.kind {
+        FieldsKind::Unit => Ok(quote! { #lua::Pushable::push((), lstate) }),
+        _ => Ok(push_table(&fields, bindings, None, lua)),
+    }
+}
+
+fn pop_struct(
+    fields: &Fields,
+    attrs: &ContainerAttrs,
+    lua: &TokenStream,
+) -> Result<TokenStream> {
+    let fields = parse_fields(fields, attrs.rename_all, ATTR)?;
+
+    if is_newtype(&fields) {
+        return Ok(quote! {
+            #lua::Poppable::pop(lstate).map(Self)
+        });
+    }
+
+    if let FieldsKind::Unit = fields.kind {
+        return Ok(quote! {
+            <() as #lua::Poppable>::pop(lstate).map(|()| Self)
+        });
+    }
+
+    let constructor = construct(quote! { Self }, &fields, lua);
+
+    Ok(quote! {
+        let _table = #lua::derive::pop_table::<Self>(lstate)?;
+        ::core::result::Result::Ok(#constructor)
+    })
+}
+
+/// Unit variants are pushed as strings, all the other variants as tables
+/// whose tag field holds the variant's name.
+fn push_enum(
+    data: &DataEnum,
+    attrs: &ContainerAttrs,
+    lua: &TokenStream,
+) -> Result<TokenStream> {
+    let tag = c_str(attrs.tag());
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let name =
+                VariantAttrs::parse(&variant.attrs, ATTR)?.name(ident, attrs);
+            // The `rename_all` rule only applies to the variants' names.
+            let fields = parse_fields(&variant.fields, None, ATTR)?;
+
+            if let FieldsKind::Unit = fields.kind {
+                return Ok(quote! {
+                    Self::#ident => #lua::Pushable::push(#name, lstate),
+                });
+            }
+
+            let bindings = fields
+                .iter()
+                .map(|field| {
+                    let binding = &field.binding;
+                    quote! { #binding }
+                })
+                .collect::<Vec<_>>();
+
+            let pattern = destructure(quote! { Self::#ident }, &fields);
+            let push = push_table(
+                &fields,
+                bindings,
+                Some(quote! {
+                    #lua::derive::set_field(lstate, #tag, #name)?;
+                }),
+                lua,
+            );
+
+            Ok(quote! { #pattern => { #push }, })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+    })
+}
+
+fn pop_enum(
+    data: &DataEnum,
+    attrs: &ContainerAttrs,
+    lua: &TokenStream,
+) -> Result<TokenStream> {
+    let tag = c_str(attrs.tag());
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let name =
+                VariantAttrs::parse(&variant.attrs, ATTR)?.name(ident, attrs);
+            // The `rename_all` rule only applies to the variants' names.
+            let fields = parse_fields(&variant.fields, None, ATTR)?;
+
+            if let FieldsKind::Unit = fields.kind {
+                return Ok(quote! {
+                    (#name, _) => ::core::result::Result::Ok(Self::#ident),
+                });
+            }
+
+            let constructor = construct(quote! { Self::#ident }, &fields, lua);
+
+            Ok(quote! {
+                (#name, true) => ::core::result::Result::Ok(#constructor),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        let (variant, table) =
+            #lua::derive::pop_variant::<Self>(lstate, #tag)?;
+
+        match (&*variant, table.is_some()) {
+            #(#arms)*
+            (other, _) => ::core::result::Result::Err(
+                #lua::derive::unknown_variant::<Self>(other)
+            ),
+        }
+    })
+}
+
+/// Returns the code pushing a table with the given fields, whose values are
+/// given by `values`.
+fn push_table(
+    fields: &ParsedFields,
+    values: impl IntoIterator<Item = TokenStream>,
+    prelude: Option<TokenStream>,
+    lua: &TokenStream,
+) -> TokenStream {
+    let mut narr = 0;
+    let mut nrec = prelude.is_some() as i32;
+
+    let setters = fields
+        .iter()
+        .zip(values)
+        .filter(|(field, _)| !field.attrs.skip)
+        .map(|(field, value)| match &field.key {
+            Key::Named(key) => {
+                nrec += 1;
+                let key = c_str(key);
+                quote! { #lua::derive::set_field(lstate, #key, #value)?; }
+            },
+            Key::Index => {
+                narr += 1;
+                quote! { #lua::derive::set_index(lstate, #narr, #value)?; }
+            },
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        #lua::derive::new_table(lstate, #narr, #nrec);
+        #prelude
+        #(#setters)*
+        ::core::result::Result::Ok(1)
+    }
+}
+
+/// Returns the expression building `path` from the fields of the table at
+/// the top of the stack.
+fn construct(
+    path: TokenStream,
+    fields: &ParsedFields,
+    lua: &TokenStream,
+) -> TokenStream {
+    let mut index = 0;
+
+    let values = fields.iter().map(|field| {
+        let ty = &field.ty;
+
+        if field.attrs.skip {
+            return quote! { <#ty as ::core::default::Default>::default() };
+        }
+
+        match &field.key {
+            Key::Named(key) => {
+                let key = c_str(key);
+                let default = match &field.attrs.default {
+                    Some(FieldDefault::Trait) => quote! {
+                        ::core::option::Option::Some(
+                            <#ty as ::core::default::Default>::default
+                        )
+                    },
+                    Some(FieldDefault::Path(path)) => quote! {
+                        ::core::option::Option::Some(#path)
+                    },
+                    None => quote! { ::core::option::Option::None },
+                };
+                quote! {
+                    #lua::derive::get_field::<Self, #ty>(lstate, #key, #default)?
+                }
+            },
+            Key::Index => {
+                index += 1;
+                quote! {
+                    #lua::derive::get_index::<Self, #ty>(lstate, #index)?
+                }
+            },
+        }
+    });
+
+    match fields.kind {
+        FieldsKind::Named => {
+            let members = fields.iter().map(|field| &field.member);
+            quote! { #path { #(#members: #values),* } }
+        },
+        _ => quote! { #path(#(#values),*) },
+    }
+}
+
+/// Returns a C string literal with the given contents.
+fn c_str(s: &str) -> Literal {
+    Literal::c_string(&CString::new(s).expect("no NUL bytes"))
+}
@@ -0,0 +1,458 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::*;
+
+use crate::derive_attrs::{
+    ContainerAttrs,
+    FieldDefault,
+    FieldsKind,
+    Key,
+    ParsedFields,
+    VariantAttrs,
+    add_bound,
+    destructure,
+    is_newtype,
+    parse_fields,
+};
+
+/// The name of the attribute used to configure the derives.
+const ATTR: &str = "object";
+
+/// The attributes on the struct or enum being derived.
+struct Attrs {
+    container: ContainerAttrs,
+
+    /// Whether to delegate the conversion to the type's serde impls.
+    serde: bool,
+}
+
+impl Attrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut serde = false;
+
+        let container = ContainerAttrs::parse_with(attrs, ATTR, |meta| {
+            let is_serde = meta.path.is_ident("serde");
+            serde |= is_serde;
+            Ok(is_serde)
+        })?;
+
+        Ok(Self { container, serde })
+    }
+}
+
+pub fn expand_derive_from_object(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = Attrs::parse(&input.attrs)?;
+    let nvim_oxi = &attrs.container.nvim_oxi;
+    let nvim_oxi = &quote! { #nvim_oxi };
+    let conversion = quote! { #nvim_oxi::conversion };
+
+    let (body, generics) = if attrs.serde {
+        let body = quote! {
+            <Self as ::serde::Deserialize>::deserialize(
+                #nvim_oxi::serde::Deserializer::new(obj),
+            )
+            .map_err(::core::convert::Into::into)
+        };
+        let mut generics = input.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { Self: for<'de> ::serde::Deserialize<'de> });
+        (body, generics)
+    } else {
+        let body = match &input.data {
+            Data::Struct(data) => {
+                from_struct(&data.fields, &attrs.container, nvim_oxi)?
+            },
+            Data::Enum(data) => from_enum(data, &attrs.container, nvim_oxi)?,
+            Data::Union(_) => {
+                let msg = "unions are not supported";
+                return Err(Error::new_spanned(input, msg));
+            },
+        };
+        let generics =
+            add_bound(&input.generics, quote! { #conversion::FromObject });
+        (body, generics)
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #conversion::FromObject for #name #ty_generics
+            #where_clause
+        {
+            #[inline]
+            fn from_object(
+                obj: #nvim_oxi::Object,
+            ) -> ::core::result::Result<Self, #conversion::Error> {
+                #body
+            }
+        }
+    })
+}
+
+pub fn expand_derive_to_object(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = Attrs::parse(&input.attrs)?;
+    let nvim_oxi = &attrs.container.nvim_oxi;
+    let nvim_oxi = &quote! { #nvim_oxi };
+    let conversion = quote! { #nvim_oxi::conversion };
+
+    let (body, generics) = if attrs.serde {
+        let body = quote! {
+            ::serde::Serialize::serialize(
+                &self,
+                #nvim_oxi::serde::Serializer::new(),
+            )
+            .map_err(::core::convert::Into::into)
+        };
+        let mut generics = input.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { Self: ::serde::Serialize });
+        (body, generics)
+    } else {
+        let body = match &input.data {
+            Data::Struct(data) => {
+                to_struct(&data.fields, &attrs.container, nvim_oxi)?
+            },
+            Data::Enum(data) => to_enum(data, &attrs.container, nvim_oxi)?,
+            Data::Union(_) => {
+                let msg = "unions are not supported";
+                return Err(Error::new_spanned(input, msg));
+            },
+        };
+        let generics =
+            add_bound(&input.generics, quote! { #conversion::ToObject });
+        (body, generics)
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #conversion::ToObject for #name #ty_generics
+            #where_clause
+        {
+            #[inline]
+            fn to_object(
+                self,
+            ) -> ::core::result::Result<#nvim_oxi::Object, #conversion::Error>
+            {
+                #body
+            }
+        }
+    })
+}
+
+/// Structs with named fields are converted to dictionaries, newtype structs
+/// to their inner value, tuple structs to arrays and unit structs to `nil`.
+fn to_struct(
+    fields: &Fields,
+    attrs: &ContainerAttrs,
+    nvim_oxi: &TokenStream,
+) -> Result<TokenStream> {
+    let fields = parse_fields(fields, attrs.rename_all, ATTR)?;
+    let conversion = quote! { #nvim_oxi::conversion };
+
+    if is_newtype(&fields) {
+        return Ok(quote! { #conversion::ToObject::to_object(self.0) });
+    }
+
+    let values = fields.iter().map(|field| {
+        let member = &field.member;
+        quote! { self.#member }
+    });
+
+    Ok(match fields.kind {
+        FieldsKind::Named => to_dictionary(&fields, values, None, nvim_oxi),
+        FieldsKind::Unnamed => to_array(&fields, values, nvim_oxi),
+        FieldsKind::Unit => {
+            quote! { ::core::result::Result::Ok(#nvim_oxi::Object::nil()) }
+        },
+    })
+}
+
+fn from_struct(
+    fields: &Fields,
+    attrs: &ContainerAttrs,
+    nvim_oxi: &TokenStream,
+) -> Result<TokenStream> {
+    let fields = parse_fields(fields, attrs.rename_all, ATTR)?;
+    let conversion = quote! { #nvim_oxi::conversion };
+
+    if is_newtype(&fields) {
+        return Ok(quote! {
+            #conversion::FromObject::from_object(obj).map(Self)
+        });
+    }
+
+    Ok(match fields.kind {
+        FieldsKind::Named => {
+            let constructor = construct(quote! { Self }, &fields, &conversion);
+            quote! {
+                let mut dict = <#nvim_oxi::Dictionary as
+                    #conversion::FromObject>::from_object(obj)?;
+                ::core::result::Result::Ok(#constructor)
+            }
+        },
+        FieldsKind::Unnamed => {
+            let constructor = construct(quote! { Self }, &fields, &conversion);
+            quote! {
+                let mut elements = ::core::iter::IntoIterator::into_iter(
+                    <#nvim_oxi::Array as #conversion::FromObject>
+                        ::from_object(obj)?,
+                );
+                ::core::result::Result::Ok(#constructor)
+            }
+        },
+        FieldsKind::Unit => quote! {
+            <() as #conversion::FromObject>::from_object(obj).map(|()| Self)
+        },
+    })
+}
+
+/// Unit variants are converted to strings, variants with named fields to
+/// dictionaries whose tag field holds the variant's name.
+fn to_enum(
+    data: &DataEnum,
+    attrs: &ContainerAttrs,
+    nvim_oxi: &TokenStream,
+) -> Result<TokenStream> {
+    let tag = attrs.tag();
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let name =
+                VariantAttrs::parse(&variant.attrs, ATTR)?.name(ident, attrs);
+            // The `rename_all` rule only applies to the variants' names.
+            let fields = parse_fields(&variant.fields, None, ATTR)?;
+
+            match fields.kind {
+                FieldsKind::Unit => {
+                    return Ok(quote! {
+                        Self::#ident => ::core::result::Result::Ok(
+                            #nvim_oxi::Object::from(#name)
+                        ),
+                    });
+                },
+                FieldsKind::Unnamed => return Err(tuple_variant(variant)),
+                FieldsKind::Named => {},
+            }
+
+            let values = fields
+                .iter()
+                .map(|field| {
+                    let binding = &field.binding;
+                    quote! { #binding }
+                })
+                .collect::<Vec<_>>();
+
+            let pattern = destructure(quote! { Self::#ident }, &fields);
+            let dict = to_dictionary(
+                &fields,
+                values,
+                Some(quote! { (#tag, #nvim_oxi::Object::from(#name)) }),
+                nvim_oxi,
+            );
+
+            Ok(quote! { #pattern => { #dict }, })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+    })
+}
+
+fn from_enum(
+    data: &DataEnum,
+    attrs: &ContainerAttrs,
+    nvim_oxi: &TokenStream,
+) -> Result<TokenStream> {
+    let tag = attrs.tag();
+    let conversion = quote! { #nvim_oxi::conversion };
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let name =
+                VariantAttrs::parse(&variant.attrs, ATTR)?.name(ident, attrs);
+            // The `rename_all` rule only applies to the variants' names.
+            let fields = parse_fields(&variant.fields, None, ATTR)?;
+
+            match fields.kind {
+                FieldsKind::Unit => {
+                    return Ok(quote! {
+                        (#name, _) => ::core::result::Result::Ok(Self::#ident),
+                    });
+                },
+                FieldsKind::Unnamed => return Err(tuple_variant(variant)),
+                FieldsKind::Named => {},
+            }
+
+            let constructor =
+                construct(quote! { Self::#ident }, &fields, &conversion);
+
+            Ok(quote! {
+                (#name, ::core::option::Option::Some(mut dict)) => {
+                    ::core::result::Result::Ok(#constructor)
+                },
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        let (variant, dict) =
+            #conversion::derive::variant::<Self>(obj, #tag)?;
+
+        match (&*variant, dict) {
+            #(#arms)*
+            (other, _) => ::core::result::Result::Err(
+                #conversion::derive::unknown_variant::<Self>(other)
+            ),
+        }
+    })
+}
+
+/// Returns the code building a dictionary with the given fields, whose
+/// values are given by `values`.
+fn to_dictionary(
+    fields: &ParsedFields,
+    values: impl IntoIterator<Item = TokenStream>,
+    prelude: Option<TokenStream>,
+    nvim_oxi: &TokenStream,
+) -> TokenStream {
+    let pairs = prelude
+        .into_iter()
+        .chain(
+            fields
+                .iter()
+                .zip(values)
+                .filter(|(field, _)| !field.attrs.skip)
+                .map(|(field, value)| {
+                    let Key::Named(key) = &field.key else {
+                        unreachable!("only named fields are in dictionaries")
+                    };
+                    quote! {
+                        (
+                            #key,
+                            #nvim_oxi::conversion::derive::to_field::<Self, _>(
+                                #key, #value,
+                            )?,
+                        )
+                    }
+                }),
+        )
+        .collect::<Vec<_>>();
+
+    let len = pairs.len();
+
+    quote! {
+        let pairs: [(&str, #nvim_oxi::Object); #len] = [#(#pairs),*];
+        ::core::result::Result::Ok(#nvim_oxi::Object::from(
+            <#nvim_oxi::Dictionary as ::core::iter::FromIterator<_>>
+                ::from_iter(pairs)
+        ))
+    }
+}
+
+/// Returns the code building an array with the given fields, whose values
+/// are given by `values`.
+fn to_array(
+    fields: &ParsedFields,
+    values: impl IntoIterator<Item = TokenStream>,
+    nvim_oxi: &TokenStream,
+) -> TokenStream {
+    let elements = fields
+        .iter()
+        .zip(values)
+        .filter(|(field, _)| !field.attrs.skip)
+        .map(|(_, value)| {
+            quote! { #nvim_oxi::conversion::ToObject::to_object(#value)? }
+        })
+        .collect::<Vec<_>>();
+
+    let len = elements.len();
+
+    quote! {
+        let elements: [#nvim_oxi::Object; #len] = [#(#elements),*];
+        ::core::result::Result::Ok(#nvim_oxi::Object::from(
+            <#nvim_oxi::Array as ::core::iter::FromIterator<_>>
+                ::from_iter(elements)
+        ))
+    }
+}
+
+/// Returns the expression building `path` from the fields of the `dict`
+/// dictionary or from the `elements` iterator, depending on whether the
+/// fields are named or not.
+fn construct(
+    path: TokenStream,
+    fields: &ParsedFields,
+    conversion: &TokenStream,
+) -> TokenStream {
+    let mut index = 0usize;
+
+    let values = fields.iter().map(|field| {
+        let ty = &field.ty;
+
+        if field.attrs.skip {
+            return quote! { <#ty as ::core::default::Default>::default() };
+        }
+
+        match &field.key {
+            Key::Named(key) => {
+                let default = match &field.attrs.default {
+                    Some(FieldDefault::Trait) => quote! {
+                        ::core::option::Option::Some(
+                            <#ty as ::core::default::Default>::default
+                        )
+                    },
+                    Some(FieldDefault::Path(path)) => quote! {
+                        ::core::option::Option::Some(#path)
+                    },
+                    None => quote! { ::core::option::Option::None },
+                };
+                quote! {
+                    #conversion::derive::take_field::<Self, #ty>(
+                        &mut dict, #key, #default,
+                    )?
+                }
+            },
+            Key::Index => {
+                let idx = index;
+                index += 1;
+                quote! {
+                    #conversion::derive::take_element::<Self, #ty>(
+                        &mut elements, #idx,
+                    )?
+                }
+            },
+        }
+    });
+
+    match fields.kind {
+        FieldsKind::Named => {
+            let members = fields.iter().map(|field| &field.member);
+            quote! { #path { #(#members: #values),* } }
+        },
+        _ => quote! { #path(#(#values),*) },
+    }
+}
+
+/// Returns the error for tuple variants, which can't be represented as
+/// dictionaries.
+fn tuple_variant(variant: &Variant) -> Error {
+    let msg = "tuple variants are not supported, use named fields or \
+               `#[object(serde)]` instead";
+    Error::new_spanned(variant, msg)
+}
@@ -2,6 +2,9 @@ use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
 mod common;
+mod derive_attrs;
+mod derive_lua;
+mod derive_object;
 mod derive_opts;
 
 #[cfg(feature = "plugin")]
@@ -19,6 +22,149 @@ pub fn derive_opts_builder(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Derives the `Pushable` trait, which pushes a value on the Lua stack.
+///
+/// Structs with named fields are pushed as tables, tuple structs as arrays,
+/// newtype structs as their inner value and unit structs as `nil`. Enum unit
+/// variants are pushed as strings containing the variant's name, while all
+/// the other variants are pushed as tables whose `type` field contains the
+/// variant's name, together with the variant's fields.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::lua::{Poppable, Pushable};
+///
+/// #[derive(Pushable, Poppable)]
+/// #[lua(rename_all = "camelCase")]
+/// struct Config {
+///     // Pushed as `{ lineWidth = .. }`.
+///     line_width: u32,
+///
+///     // Popped as `"auto"` if the table doesn't have a `mode` field.
+///     #[lua(default = "auto")]
+///     mode: Mode,
+///
+///     // Not pushed, and set to `Default::default()` when popped.
+///     #[lua(skip)]
+///     cache: Vec<String>,
+/// }
+///
+/// #[derive(Pushable, Poppable)]
+/// #[lua(rename_all = "lowercase")]
+/// enum Mode {
+///     Auto,
+///     Fixed { width: u32 },
+/// }
+///
+/// fn auto() -> Mode {
+///     Mode::Auto
+/// }
+/// ```
+///
+/// # Attributes
+///
+/// The following attributes are supported via `#[lua(..)]`, with the same
+/// meaning they have in serde:
+///
+/// - on structs and enums: `rename_all = ".."`, and `tag = ".."` to change
+///   the key holding the name of enum variants (`type` by default);
+/// - on fields: `rename = ".."`, `skip`, `default` and `default = "path"`;
+/// - on enum variants: `rename = ".."`.
+///
+/// The `nvim_oxi = path` attribute can be used on structs and enums in the
+/// same way as on the [`macro@plugin`] macro.
+#[proc_macro_derive(Pushable, attributes(lua))]
+pub fn derive_pushable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive_lua::expand_derive_pushable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives the `Poppable` trait, which pops a value from the Lua stack.
+///
+/// This is the inverse of the [`macro@Pushable`] derive, and it supports the
+/// same attributes.
+#[proc_macro_derive(Poppable, attributes(lua))]
+pub fn derive_poppable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive_lua::expand_derive_poppable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives the `FromObject` trait, which converts an `Object` into a Rust
+/// value.
+///
+/// By default the conversion is done field by field: structs with named
+/// fields are converted from dictionaries, tuple structs from arrays,
+/// newtype structs from their inner value and unit structs from `nil`. Enum
+/// unit variants are converted from strings containing the variant's name,
+/// and variants with named fields from dictionaries whose `type` field
+/// contains the variant's name, together with the variant's fields. Missing
+/// fields are treated as `nil`, so they can be converted into `Option`s.
+///
+/// With `#[object(serde)]` the conversion is instead delegated to the type's
+/// `Deserialize` impl, which is more flexible but requires a dependency on
+/// `serde`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nvim_oxi::conversion::{FromObject, ToObject};
+///
+/// #[derive(FromObject, ToObject)]
+/// #[object(rename_all = "camelCase")]
+/// struct Config {
+///     // Converted from `{ lineWidth = .. }`.
+///     line_width: u32,
+///
+///     #[object(default)]
+///     verbose: bool,
+/// }
+///
+/// #[derive(serde::Deserialize, FromObject)]
+/// #[object(serde)]
+/// struct Infos {
+///     #[serde(rename = "type")]
+///     kind: String,
+/// }
+/// ```
+///
+/// # Attributes
+///
+/// The following attributes are supported via `#[object(..)]`:
+///
+/// - on structs and enums: `serde`, and `rename_all = ".."` and `tag = ".."`
+///   with the same meaning as in the [`macro@Pushable`] derive;
+/// - on fields: `rename = ".."`, `skip`, `default` and `default = "path"`;
+/// - on enum variants: `rename = ".."`.
+///
+/// The attributes other than `serde` and `nvim_oxi` are ignored in serde mode,
+/// where serde's own attributes should be used instead.
+#[proc_macro_derive(FromObject, attributes(object))]
+pub fn derive_from_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive_object::expand_derive_from_object(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives the `ToObject` trait, which converts a Rust value into an
+/// `Object`.
+///
+/// This is the inverse of the [`macro@FromObject`] derive, and it supports the
+/// same attributes. In serde mode the conversion is delegated to the type's
+/// `Serialize` impl.
+#[proc_macro_derive(ToObject, attributes(object))]
+pub fn derive_to_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive_object::expand_derive_to_object(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 /// Marks a function as the entrypoint of the plugin.
 ///
 /// The function wrapped by this macro will be called by Neovim when the user
@@ -1,10 +1,10 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
-use quote::{ToTokens, quote};
+use quote::quote;
 use syn::parse::{Parse, ParseStream};
-use syn::{ItemFn, Path, Token, parse_macro_input, parse_quote};
+use syn::{ItemFn, Token, parse_macro_input};
 
-use crate::common::{DuplicateError, Keyed, KeyedAttribute};
+use crate::common::{DuplicateError, NvimOxi};
 
 #[inline]
 pub fn plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -76,43 +76,3 @@ impl Parse for Attribute {
         input.parse::<NvimOxi>().map(Self::NvimOxi)
     }
 }
-
-pub(crate) struct NvimOxi {
-    key_span: Span,
-    value: Path,
-}
-
-impl Default for NvimOxi {
-    #[inline]
-    fn default() -> Self {
-        Self { key_span: Span::call_site(), value: parse_quote!(::nvim_oxi) }
-    }
-}
-
-impl Parse for NvimOxi {
-    #[inline]
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        Ok(Self {
-            key_span: Span::call_site(),
-            value: input.parse::<Keyed<Self>>()?.value,
-        })
-    }
-}
-
-impl KeyedAttribute for NvimOxi {
-    const KEY: &'static str = "nvim_oxi";
-
-    type Value = Path;
-
-    #[inline]
-    fn key_span(&self) -> Span {
-        self.key_span
-    }
-}
-
-impl ToTokens for NvimOxi {
-    #[inline]
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        self.value.to_tokens(tokens);
-    }
-}
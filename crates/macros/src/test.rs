@@ -4,8 +4,7 @@ use quote::{ToTokens, quote};
 use syn::parse::{Parse, ParseStream};
 use syn::{AttrStyle, ItemFn, LitStr, Meta, Token, parse_macro_input};
 
-use crate::common::{DuplicateError, Keyed, KeyedAttribute};
-use crate::plugin::NvimOxi;
+use crate::common::{DuplicateError, Keyed, KeyedAttribute, NvimOxi};
 
 #[inline]
 pub fn test(attrs: TokenStream, item: TokenStream) -> TokenStream {
@@ -16,6 +16,9 @@ use crate::{
     ObjectKind,
 };
 
+#[doc(hidden)]
+pub mod derive;
+
 #[derive(Clone, Debug, Eq, PartialEq, ThisError)]
 pub enum Error {
     #[error("Was expecting a \"{expected}\" but received a \"{actual}\"")]
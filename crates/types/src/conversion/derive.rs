@@ -0,0 +1,196 @@
+//! Helpers used by the code generated by `#[derive(FromObject, ToObject)]`.
+
+use super::{Error, FromObject, ToObject};
+use crate::iter::ArrayIterator;
+use crate::{Dictionary, Object, ObjectKind};
+
+/// Removes the `field` of the dictionary and converts it into a `T`.
+///
+/// If the field is missing or `nil` and a `default` is given, the default is
+/// returned instead. Missing fields are otherwise treated as `nil`, so that
+/// they can be converted into `Option`s.
+pub fn take_field<Owner, T: FromObject>(
+    dict: &mut Dictionary,
+    field: &str,
+    default: Option<fn() -> T>,
+) -> Result<T, Error> {
+    let value = match dict.get_index(field) {
+        Some(idx) => dict.swap_remove(idx).into_value(),
+        None => Object::nil(),
+    };
+
+    from_member::<Owner, T>(value, default, || format!("field `{field}`"))
+}
+
+/// Converts the next element of the array into a `T`, treating missing
+/// elements as `nil`.
+pub fn take_element<Owner, T: FromObject>(
+    elements: &mut ArrayIterator,
+    idx: usize,
+) -> Result<T, Error> {
+    let value = elements.next().unwrap_or_default();
+    from_member::<Owner, T>(value, None, || format!("element {idx}"))
+}
+
+/// Converts the value of `field` into an `Object`.
+pub fn to_field<Owner, T: ToObject>(
+    field: &str,
+    value: T,
+) -> Result<Object, Error> {
+    value.to_object().map_err(|err| {
+        invalid_member::<Owner>(&format!("field `{field}`"), &err)
+    })
+}
+
+/// Returns the name of the enum variant stored in the object.
+///
+/// Unit variants are represented as strings, while all the other variants
+/// are dictionaries whose `tag` field contains the variant's name. In the
+/// latter case the rest of the dictionary is returned together with the
+/// name.
+pub fn variant<Owner>(
+    obj: Object,
+    tag: &str,
+) -> Result<(String, Option<Dictionary>), Error> {
+    match obj.kind() {
+        ObjectKind::String => Ok((String::from_object(obj)?, None)),
+
+        ObjectKind::Dictionary => {
+            let mut dict = Dictionary::from_object(obj)?;
+            let name = take_field::<Owner, String>(&mut dict, tag, None)?;
+            Ok((name, Some(dict)))
+        },
+
+        other => Err(Error::FromWrongType {
+            expected: "string or dictionary",
+            actual: other.as_static(),
+        }),
+    }
+}
+
+/// Returns the error for an enum variant that doesn't match any of the
+/// variants of `T`.
+pub fn unknown_variant<T>(name: &str) -> Error {
+    Error::Other(format!(
+        "unknown variant `{name}` of `{}`",
+        core::any::type_name::<T>()
+    ))
+}
+
+fn from_member<Owner, T: FromObject>(
+    value: Object,
+    default: Option<fn() -> T>,
+    describe: impl FnOnce() -> String,
+) -> Result<T, Error> {
+    if let Some(default) = default {
+        if value.is_nil() {
+            return Ok(default());
+        }
+    }
+
+    T::from_object(value)
+        .map_err(|err| invalid_member::<Owner>(&describe(), &err))
+}
+
+fn invalid_member<Owner>(member: &str, err: &Error) -> Error {
+    Error::Other(format!(
+        "invalid {member} of `{}`: {err}",
+        core::any::type_name::<Owner>()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use macros::{FromObject, ToObject};
+
+    use crate::conversion::{FromObject, ToObject};
+    use crate::{Array, Dictionary, Object};
+
+    #[derive(Debug, PartialEq, FromObject, ToObject)]
+    #[object(nvim_oxi = crate, rename_all = "camelCase")]
+    struct Config {
+        line_width: u32,
+        #[object(rename = "name")]
+        title: Option<String>,
+        #[object(default)]
+        verbose: bool,
+        #[object(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, FromObject, ToObject)]
+    #[object(nvim_oxi = crate)]
+    struct Meters(f64);
+
+    #[derive(Debug, PartialEq, FromObject, ToObject)]
+    #[object(nvim_oxi = crate)]
+    struct Point(i64, i64);
+
+    #[derive(Debug, PartialEq, FromObject, ToObject)]
+    #[object(nvim_oxi = crate, rename_all = "lowercase", tag = "kind")]
+    enum Shape {
+        Empty,
+        Circle { radius: f64 },
+    }
+
+    #[test]
+    fn named_struct_roundtrip() {
+        let config = Config {
+            line_width: 80,
+            title: Some("foo".into()),
+            verbose: true,
+            cache: vec![1, 2, 3],
+        };
+
+        let obj = config.to_object().unwrap();
+
+        let dict = Dictionary::from_object(obj.clone()).unwrap();
+        assert_eq!(dict.len(), 3);
+        assert_eq!(dict.get("lineWidth"), Some(&Object::from(80)));
+        assert_eq!(dict.get("name"), Some(&Object::from("foo")));
+        assert_eq!(dict.get("cache"), None);
+
+        let config = Config::from_object(obj).unwrap();
+        assert_eq!(config.line_width, 80);
+        assert_eq!(config.title.as_deref(), Some("foo"));
+        assert!(config.verbose);
+        assert!(config.cache.is_empty());
+    }
+
+    #[test]
+    fn missing_fields() {
+        let obj = Object::from(Dictionary::from_iter([("lineWidth", 4)]));
+
+        let config = Config::from_object(obj).unwrap();
+        assert_eq!(config.title, None);
+        assert!(!config.verbose);
+
+        let err = Config::from_object(Dictionary::new().into()).unwrap_err();
+        assert!(err.to_string().contains("invalid field `lineWidth`"));
+    }
+
+    #[test]
+    fn tuple_structs() {
+        assert_eq!(Meters(1.5).to_object(), Ok(Object::from(1.5)));
+        assert_eq!(Meters::from_object(Object::from(1.5)), Ok(Meters(1.5)));
+
+        let obj = Point(1, 2).to_object().unwrap();
+        assert_eq!(obj, Object::from(Array::from((1, 2))));
+        assert_eq!(Point::from_object(obj), Ok(Point(1, 2)));
+    }
+
+    #[test]
+    fn enums() {
+        assert_eq!(Shape::Empty.to_object(), Ok(Object::from("empty")));
+        assert_eq!(Shape::from_object("empty".into()), Ok(Shape::Empty));
+
+        let obj = Shape::Circle { radius: 2.0 }.to_object().unwrap();
+        let dict = Dictionary::from_object(obj.clone()).unwrap();
+        assert_eq!(dict.get("kind"), Some(&Object::from("circle")));
+        assert_eq!(dict.get("radius"), Some(&Object::from(2.0)));
+        assert_eq!(Shape::from_object(obj), Ok(Shape::Circle { radius: 2.0 }));
+
+        let err = Shape::from_object("square".into()).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `square`"));
+    }
+}
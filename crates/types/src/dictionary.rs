@@ -155,6 +155,13 @@ impl Dictionary {
 }
 
 impl KeyValuePair {
+    /// Creates a new `KeyValuePair`. Unlike [`Dictionary::insert`] this
+    /// keeps `nil` values.
+    #[inline]
+    pub(crate) fn new(key: crate::String, value: Object) -> Self {
+        Self { key, value }
+    }
+
     /// Consumes the `KeyValuePair` and returns the key.
     #[inline]
     pub fn into_key(self) -> crate::String {
@@ -81,6 +81,13 @@ impl<A, R> Function<A, R> {
         self.lua_ref
     }
 
+    /// Compiles a chunk of Lua code into a function, without running it.
+    ///
+    /// The `chunkname` is used in error messages and stack tracebacks.
+    pub fn load(source: &str, chunkname: &str) -> Result<Self, lua::Error> {
+        lua::function::load(source, chunkname).map(Self::from_ref)
+    }
+
     pub fn from_fn<F, O>(fun: F) -> Self
     where
         F: Fn(A) -> O + 'static,
@@ -10,6 +10,7 @@ mod error;
 mod function;
 mod kvec;
 mod macros;
+mod msgpack;
 mod non_owning;
 mod object;
 #[cfg(feature = "serde")]
@@ -23,6 +24,7 @@ pub use array::{Array, ArrayFromTupleError};
 pub use dictionary::{Dictionary, KeyValuePair};
 pub use error::Error;
 pub use function::Function;
+pub use msgpack::MsgpackError;
 pub use non_owning::NonOwning;
 pub use object::{Object, ObjectKind};
 pub use str::NvimStr;
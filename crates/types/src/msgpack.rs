@@ -0,0 +1,555 @@
+//! Encoding and decoding of [`Object`]s to and from the [MessagePack] format
+//! used by Neovim's RPC API.
+//!
+//! [MessagePack]: https://github.com/msgpack/msgpack/blob/master/spec.md
+
+use crate::kvec::KVec;
+use crate::{
+    Array,
+    Dictionary,
+    Integer,
+    KeyValuePair,
+    Object,
+    ObjectKind,
+    String,
+};
+
+// https://github.com/neovim/neovim/blob/v0.10.0/src/nvim/msgpack_rpc/packer.c
+const EXT_BUFFER: i8 = 0;
+const EXT_WINDOW: i8 = 1;
+const EXT_TABPAGE: i8 = 2;
+
+/// The maximum nesting depth of arrays and maps accepted by
+/// [`Object::from_msgpack`].
+const MAX_DEPTH: usize = 128;
+
+/// The error type returned by [`Object::from_msgpack`].
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum MsgpackError {
+    /// The input ended in the middle of a value.
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+
+    /// The input contains the reserved `0xc1` marker.
+    #[error("invalid marker byte {0:#04x}")]
+    InvalidMarker(u8),
+
+    /// An unsigned integer is too big to fit in an [`Integer`].
+    #[error("integer {0} doesn't fit in a 64-bit signed integer")]
+    IntegerOverflow(u64),
+
+    /// A map key is neither a string nor a binary.
+    #[error("map keys must be strings, got {0}")]
+    InvalidKey(&'static str),
+
+    /// An extension type other than Neovim's buffer, window and tabpage.
+    #[error("unknown extension type {0}")]
+    UnknownExtType(i8),
+
+    /// The payload of a buffer, window or tabpage extension isn't an
+    /// integer.
+    #[error("the payload of extension type {0} is not an integer")]
+    InvalidExtPayload(i8),
+
+    /// Arrays and maps are nested more than the maximum allowed depth.
+    #[error("arrays and maps are nested more than {} levels deep", MAX_DEPTH)]
+    DepthLimitExceeded,
+
+    /// The input contains more bytes after the first value.
+    #[error("{0} trailing bytes after the end of the object")]
+    TrailingBytes(usize),
+}
+
+impl Object {
+    /// Encodes this object in the MessagePack format.
+    ///
+    /// Buffers, windows and tabpages are encoded as the extension types `0`,
+    /// `1` and `2` used by Neovim's RPC API, with the handle as payload.
+    /// Lua references can't be serialized and are encoded as nil.
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode(self, &mut buf);
+        buf
+    }
+
+    /// Decodes an object from a buffer containing a single
+    /// MessagePack-encoded value.
+    ///
+    /// Binaries are decoded as [`String`]s, and the extension types `0`, `1`
+    /// and `2` as buffers, windows and tabpages, respectively.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, MsgpackError> {
+        let mut decoder = Decoder { bytes };
+        let obj = decoder.decode(0)?;
+        match decoder.bytes.len() {
+            0 => Ok(obj),
+            n => Err(MsgpackError::TrailingBytes(n)),
+        }
+    }
+}
+
+fn encode(obj: &Object, buf: &mut Vec<u8>) {
+    match obj.kind() {
+        ObjectKind::Nil | ObjectKind::LuaRef => buf.push(0xc0),
+
+        ObjectKind::Boolean => {
+            let b = unsafe { obj.as_boolean_unchecked() };
+            buf.push(if b { 0xc3 } else { 0xc2 });
+        },
+
+        ObjectKind::Integer => {
+            encode_integer(unsafe { obj.as_integer_unchecked() }, buf)
+        },
+
+        ObjectKind::Float => {
+            buf.push(0xcb);
+            buf.extend(unsafe { obj.as_float_unchecked() }.to_be_bytes());
+        },
+
+        ObjectKind::String => {
+            encode_str(unsafe { obj.as_nvim_str_unchecked() }.as_bytes(), buf)
+        },
+
+        ObjectKind::Array => {
+            let array = unsafe { obj.as_array_unchecked() };
+            encode_len(array.len(), 0x90, 0xdc, buf);
+            for obj in array.iter() {
+                encode(obj, buf);
+            }
+        },
+
+        ObjectKind::Dictionary => {
+            let dict = unsafe { obj.as_dictionary_unchecked() };
+            encode_len(dict.len(), 0x80, 0xde, buf);
+            for (key, value) in dict.iter() {
+                encode_str(key.as_bytes(), buf);
+                encode(value, buf);
+            }
+        },
+
+        ObjectKind::Buffer => encode_handle(obj, EXT_BUFFER, buf),
+        ObjectKind::Window => encode_handle(obj, EXT_WINDOW, buf),
+        ObjectKind::TabPage => encode_handle(obj, EXT_TABPAGE, buf),
+    }
+}
+
+/// Encodes an integer using the smallest possible representation.
+fn encode_integer(n: Integer, buf: &mut Vec<u8>) {
+    if n >= 0 {
+        let n = n as u64;
+        if n < 0x80 {
+            buf.push(n as u8);
+        } else if let Ok(n) = u8::try_from(n) {
+            buf.push(0xcc);
+            buf.push(n);
+        } else if let Ok(n) = u16::try_from(n) {
+            buf.push(0xcd);
+            buf.extend(n.to_be_bytes());
+        } else if let Ok(n) = u32::try_from(n) {
+            buf.push(0xce);
+            buf.extend(n.to_be_bytes());
+        } else {
+            buf.push(0xcf);
+            buf.extend(n.to_be_bytes());
+        }
+    } else if n >= -32 {
+        buf.push(n as u8);
+    } else if let Ok(n) = i8::try_from(n) {
+        buf.push(0xd0);
+        buf.push(n as u8);
+    } else if let Ok(n) = i16::try_from(n) {
+        buf.push(0xd1);
+        buf.extend(n.to_be_bytes());
+    } else if let Ok(n) = i32::try_from(n) {
+        buf.push(0xd2);
+        buf.extend(n.to_be_bytes());
+    } else {
+        buf.push(0xd3);
+        buf.extend(n.to_be_bytes());
+    }
+}
+
+fn encode_str(bytes: &[u8], buf: &mut Vec<u8>) {
+    let len = bytes.len();
+    if len < 32 {
+        buf.push(0xa0 | len as u8);
+    } else if let Ok(len) = u8::try_from(len) {
+        buf.push(0xd9);
+        buf.push(len);
+    } else if let Ok(len) = u16::try_from(len) {
+        buf.push(0xda);
+        buf.extend(len.to_be_bytes());
+    } else {
+        buf.push(0xdb);
+        buf.extend((len as u32).to_be_bytes());
+    }
+    buf.extend_from_slice(bytes);
+}
+
+/// Encodes the length of an array or a map, given the marker of its "fix"
+/// variant and of its 16-bit variant (the 32-bit one always follows it).
+fn encode_len(len: usize, fix_marker: u8, marker16: u8, buf: &mut Vec<u8>) {
+    if len < 16 {
+        buf.push(fix_marker | len as u8);
+    } else if let Ok(len) = u16::try_from(len) {
+        buf.push(marker16);
+        buf.extend(len.to_be_bytes());
+    } else {
+        buf.push(marker16 + 1);
+        buf.extend((len as u32).to_be_bytes());
+    }
+}
+
+fn encode_handle(obj: &Object, ext_type: i8, buf: &mut Vec<u8>) {
+    let mut payload = Vec::with_capacity(9);
+    encode_integer(unsafe { obj.as_integer_unchecked() }, &mut payload);
+
+    match payload.len() {
+        1 => buf.push(0xd4),
+        2 => buf.push(0xd5),
+        len => {
+            buf.push(0xc7);
+            buf.push(len as u8);
+        },
+    }
+
+    buf.push(ext_type as u8);
+    buf.extend(payload);
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], MsgpackError> {
+        if self.bytes.len() < n {
+            return Err(MsgpackError::UnexpectedEof);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], MsgpackError> {
+        Ok(self.take(N)?.try_into().expect("took exactly N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, MsgpackError> {
+        self.take_array::<1>().map(|[b]| b)
+    }
+
+    fn u16(&mut self) -> Result<u16, MsgpackError> {
+        self.take_array().map(u16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, MsgpackError> {
+        self.take_array().map(u32::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, MsgpackError> {
+        self.take_array().map(u64::from_be_bytes)
+    }
+
+    fn decode(&mut self, depth: usize) -> Result<Object, MsgpackError> {
+        let marker = self.u8()?;
+
+        let obj = match marker {
+            0x00..=0x7f => Object::from(marker as Integer),
+            0x80..=0x8f => self.decode_map((marker & 0x0f) as usize, depth)?,
+            0x90..=0x9f => {
+                self.decode_array((marker & 0x0f) as usize, depth)?
+            },
+            0xa0..=0xbf => self.decode_str((marker & 0x1f) as usize)?,
+            0xc0 => Object::nil(),
+            0xc1 => return Err(MsgpackError::InvalidMarker(marker)),
+            0xc2 => Object::from(false),
+            0xc3 => Object::from(true),
+
+            // bin 8, 16, 32.
+            0xc4 | 0xd9 => {
+                let len = self.u8()? as usize;
+                self.decode_str(len)?
+            },
+            0xc5 | 0xda => {
+                let len = self.u16()? as usize;
+                self.decode_str(len)?
+            },
+            0xc6 | 0xdb => {
+                let len = self.u32()? as usize;
+                self.decode_str(len)?
+            },
+
+            // ext 8, 16, 32.
+            0xc7 => {
+                let len = self.u8()? as usize;
+                self.decode_ext(len)?
+            },
+            0xc8 => {
+                let len = self.u16()? as usize;
+                self.decode_ext(len)?
+            },
+            0xc9 => {
+                let len = self.u32()? as usize;
+                self.decode_ext(len)?
+            },
+
+            0xca => Object::from(f32::from_be_bytes(self.take_array()?)),
+            0xcb => Object::from(f64::from_be_bytes(self.take_array()?)),
+
+            0xcc => Object::from(self.u8()? as Integer),
+            0xcd => Object::from(self.u16()? as Integer),
+            0xce => Object::from(self.u32()? as Integer),
+            0xcf => {
+                let n = self.u64()?;
+                let n = Integer::try_from(n)
+                    .map_err(|_| MsgpackError::IntegerOverflow(n))?;
+                Object::from(n)
+            },
+
+            0xd0 => Object::from(self.u8()? as i8 as Integer),
+            0xd1 => Object::from(self.u16()? as i16 as Integer),
+            0xd2 => Object::from(self.u32()? as i32 as Integer),
+            0xd3 => Object::from(self.u64()? as i64),
+
+            // fixext 1, 2, 4, 8, 16.
+            0xd4..=0xd8 => self.decode_ext(1 << (marker - 0xd4))?,
+
+            0xdc => {
+                let len = self.u16()? as usize;
+                self.decode_array(len, depth)?
+            },
+            0xdd => {
+                let len = self.u32()? as usize;
+                self.decode_array(len, depth)?
+            },
+            0xde => {
+                let len = self.u16()? as usize;
+                self.decode_map(len, depth)?
+            },
+            0xdf => {
+                let len = self.u32()? as usize;
+                self.decode_map(len, depth)?
+            },
+
+            0xe0..=0xff => Object::from(marker as i8 as Integer),
+        };
+
+        Ok(obj)
+    }
+
+    fn decode_str(&mut self, len: usize) -> Result<Object, MsgpackError> {
+        self.take(len).map(|bytes| String::from_bytes(bytes).into())
+    }
+
+    fn decode_array(
+        &mut self,
+        len: usize,
+        depth: usize,
+    ) -> Result<Object, MsgpackError> {
+        if depth == MAX_DEPTH {
+            return Err(MsgpackError::DepthLimitExceeded);
+        }
+        (0..len)
+            .map(|_| self.decode(depth + 1))
+            .collect::<Result<Array, _>>()
+            .map(Object::from)
+    }
+
+    fn decode_map(
+        &mut self,
+        len: usize,
+        depth: usize,
+    ) -> Result<Object, MsgpackError> {
+        if depth == MAX_DEPTH {
+            return Err(MsgpackError::DepthLimitExceeded);
+        }
+        // The pairs are pushed directly instead of going through
+        // `Dictionary::insert()`, which would drop `nil` values.
+        //
+        // Every pair takes at least two bytes, so the capacity is capped to
+        // avoid huge allocations when the length is bogus.
+        let mut kvec = KVec::with_capacity(len.min(self.bytes.len() / 2));
+        for _ in 0..len {
+            let key = self.decode(depth + 1)?;
+            if key.kind() != ObjectKind::String {
+                return Err(MsgpackError::InvalidKey(key.kind().as_static()));
+            }
+            let key = unsafe { key.into_string_unchecked() };
+            let value = self.decode(depth + 1)?;
+            kvec.push(KeyValuePair::new(key, value));
+        }
+        Ok(Dictionary(kvec).into())
+    }
+
+    fn decode_ext(&mut self, len: usize) -> Result<Object, MsgpackError> {
+        let ext_type = self.u8()? as i8;
+        let payload = self.take(len)?;
+
+        let kind = match ext_type {
+            EXT_BUFFER => ObjectKind::Buffer,
+            EXT_WINDOW => ObjectKind::Window,
+            EXT_TABPAGE => ObjectKind::TabPage,
+            other => return Err(MsgpackError::UnknownExtType(other)),
+        };
+
+        let handle = match Object::from_msgpack(payload) {
+            Ok(obj) if obj.kind() == ObjectKind::Integer => unsafe {
+                obj.as_integer_unchecked()
+            },
+            _ => return Err(MsgpackError::InvalidExtPayload(ext_type)),
+        };
+
+        Ok(Object::from_handle(kind, handle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Array, Dictionary};
+
+    fn roundtrip(obj: Object) {
+        let bytes = obj.to_msgpack();
+        assert_eq!(Object::from_msgpack(&bytes), Ok(obj));
+    }
+
+    #[test]
+    fn roundtrip_scalars() {
+        roundtrip(Object::nil());
+        roundtrip(Object::from(true));
+        roundtrip(Object::from(false));
+        roundtrip(Object::from(1.5));
+        roundtrip(Object::from("foo"));
+        roundtrip(Object::from("a".repeat(300)));
+    }
+
+    #[test]
+    fn roundtrip_integers() {
+        for n in [
+            0,
+            127,
+            128,
+            255,
+            256,
+            65_535,
+            65_536,
+            u32::MAX as i64,
+            u32::MAX as i64 + 1,
+            i64::MAX,
+            -1,
+            -32,
+            -33,
+            -128,
+            -129,
+            -32_768,
+            -32_769,
+            i32::MIN as i64,
+            i32::MIN as i64 - 1,
+            i64::MIN,
+        ] {
+            roundtrip(Object::from(n));
+        }
+    }
+
+    #[test]
+    fn roundtrip_collections() {
+        let array = Array::from_iter((0..20).map(Object::from));
+        let dict = Dictionary::from_iter([
+            ("foo", Object::from("bar")),
+            ("baz", Object::from(Array::from((1, "two", 3.0)))),
+            ("nested", Object::from(array)),
+        ]);
+        roundtrip(Object::from(dict));
+    }
+
+    #[test]
+    fn roundtrip_nil_map_values() {
+        let dict = Dictionary(KVec::from_iter([
+            KeyValuePair::new("foo".into(), Object::nil()),
+            KeyValuePair::new("bar".into(), Object::from(1)),
+        ]));
+        let obj = Object::from(dict);
+        assert_eq!(
+            obj.to_msgpack(),
+            [0x82, 0xa3, b'f', b'o', b'o', 0xc0, 0xa3, b'b', b'a', b'r', 0x01]
+        );
+        roundtrip(obj);
+    }
+
+    #[test]
+    fn handles_are_ext_types() {
+        let buffer = Object::from_handle(ObjectKind::Buffer, 1);
+        assert_eq!(buffer.to_msgpack(), [0xd4, 0x00, 0x01]);
+        roundtrip(buffer);
+
+        let window = Object::from_handle(ObjectKind::Window, 1000);
+        assert_eq!(window.to_msgpack(), [0xc7, 0x03, 0x01, 0xcd, 0x03, 0xe8]);
+        roundtrip(window);
+
+        let tabpage = Object::from_handle(ObjectKind::TabPage, 2);
+        assert_eq!(tabpage.to_msgpack(), [0xd4, 0x02, 0x02]);
+        roundtrip(tabpage);
+    }
+
+    #[test]
+    fn decode_other_encodings() {
+        // bin 8.
+        assert_eq!(
+            Object::from_msgpack(&[0xc4, 0x02, b'h', b'i']),
+            Ok(Object::from("hi"))
+        );
+
+        // float 32.
+        let mut bytes = vec![0xca];
+        bytes.extend(2.5f32.to_be_bytes());
+        assert_eq!(Object::from_msgpack(&bytes), Ok(Object::from(2.5)));
+
+        // fixext 2 with a non-minimal payload.
+        assert_eq!(
+            Object::from_msgpack(&[0xd5, 0x00, 0xd0, 0x07]),
+            Ok(Object::from_handle(ObjectKind::Buffer, 7))
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Object::from_msgpack(&[]),
+            Err(MsgpackError::UnexpectedEof)
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0xa3, b'f']),
+            Err(MsgpackError::UnexpectedEof)
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0xc1]),
+            Err(MsgpackError::InvalidMarker(0xc1))
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0xc0, 0xc0]),
+            Err(MsgpackError::TrailingBytes(1))
+        );
+        assert_eq!(
+            Object::from_msgpack(&[
+                0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff
+            ]),
+            Err(MsgpackError::IntegerOverflow(u64::MAX))
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0x81, 0x01, 0xc0]),
+            Err(MsgpackError::InvalidKey("integer"))
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0xd4, 0x05, 0x01]),
+            Err(MsgpackError::UnknownExtType(5))
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0xd4, 0x00, 0xc0]),
+            Err(MsgpackError::InvalidExtPayload(0))
+        );
+        assert_eq!(
+            Object::from_msgpack(&[0x91; MAX_DEPTH + 1]),
+            Err(MsgpackError::DepthLimitExceeded)
+        );
+    }
+}
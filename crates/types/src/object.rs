@@ -137,6 +137,17 @@ impl Object {
         Self { ty: ObjectKind::LuaRef, data: ObjectData { luaref } }
     }
 
+    /// Returns a new object of the given kind wrapping a buffer, window or
+    /// tabpage handle.
+    #[inline]
+    pub(crate) fn from_handle(kind: ObjectKind, handle: Integer) -> Self {
+        debug_assert!(matches!(
+            kind,
+            ObjectKind::Buffer | ObjectKind::Window | ObjectKind::TabPage
+        ));
+        Self { ty: kind, data: ObjectData { integer: handle } }
+    }
+
     #[inline]
     pub fn kind(&self) -> ObjectKind {
         self.ty
@@ -6,6 +6,11 @@ use super::DeserializeError;
 use crate::{Array, Dictionary, Object, ObjectKind};
 
 /// A struct used for deserializing Neovim `Object`s into Rust values.
+///
+/// The deserialized values can't borrow from the `Object`, which is consumed.
+/// To deserialize types with `&'de str` or `&'de [u8]` fields, deserialize
+/// from a `&'de Object` instead, which also implements serde's `Deserializer`
+/// trait.
 pub struct Deserializer {
     obj: Object,
 }
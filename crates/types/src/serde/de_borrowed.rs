@@ -0,0 +1,535 @@
+//! Zero-copy deserialization from borrowed `Object`s.
+//!
+//! Deserializing from a `&'de Object` instead of an `Object` lets the
+//! deserialized value borrow strings and byte slices from it, e.g. to fields
+//! of type `&'de str` or `&'de [u8]`.
+
+use serde::de::{
+    self,
+    Error,
+    IntoDeserializer,
+    value::BorrowedStrDeserializer,
+};
+
+use super::DeserializeError;
+use crate::iter::DictIter;
+use crate::{NvimStr, Object, ObjectKind};
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for &'de Object {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializes the borrowed `Object` in the same way as the owned
+/// [`Deserializer`](super::Deserializer), except that strings are passed to
+/// the visitor as borrowed `&'de str`s.
+///
+/// Like with the owned `Deserializer`, strings that aren't valid UTF-8 are
+/// converted lossily, unless they're deserialized as bytes.
+impl<'de> de::Deserializer<'de> for &'de Object {
+    type Error = DeserializeError;
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            unit unit_struct identifier ignored_any
+    }
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        use ObjectKind::*;
+        match self.kind() {
+            Nil => visitor.visit_unit(),
+
+            Boolean => {
+                visitor.visit_bool(unsafe { self.as_boolean_unchecked() })
+            },
+
+            Integer | Buffer | Window | TabPage => {
+                visitor.visit_i64(unsafe { self.as_integer_unchecked() })
+            },
+
+            Float => visitor.visit_f64(unsafe { self.as_float_unchecked() }),
+
+            String => {
+                visit_str(unsafe { self.as_nvim_str_unchecked() }, visitor)
+            },
+
+            Array => self.deserialize_seq(visitor),
+
+            Dictionary => self.deserialize_map(visitor),
+
+            // See the owned `Deserializer`.
+            LuaRef => {
+                visitor.visit_f32(unsafe { self.as_luaref_unchecked() } as f32)
+            },
+        }
+    }
+
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.kind() {
+            ObjectKind::String => visitor.visit_borrowed_bytes(
+                unsafe { self.as_nvim_str_unchecked() }.as_bytes(),
+            ),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.kind() {
+            ObjectKind::Nil => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (variant, value) = match self.kind() {
+            ObjectKind::Dictionary => {
+                let mut iter =
+                    unsafe { self.as_dictionary_unchecked() }.iter();
+
+                let (variant, value) = match iter.len() {
+                    1 => iter.next().expect("checked length"),
+                    _ => {
+                        return Err(Self::Error::invalid_value(
+                            de::Unexpected::Map,
+                            &"dictionary with a single key-value pair",
+                        ));
+                    },
+                };
+
+                (variant.as_nvim_str(), Some(value))
+            },
+
+            ObjectKind::String => {
+                (unsafe { self.as_nvim_str_unchecked() }, None)
+            },
+
+            _ => return Err(Self::Error::custom("bad enum value")),
+        };
+
+        let variant = core::str::from_utf8(variant.as_bytes())
+            .map_err(Self::Error::custom)?;
+
+        visitor.visit_enum(EnumDeserializer { variant, value })
+    }
+
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let elements: &'de [Object] = match self.kind() {
+            ObjectKind::Array => unsafe { self.as_array_unchecked() },
+
+            // Empty dictionaries are also valid arrays.
+            ObjectKind::Dictionary
+                if unsafe { self.as_dictionary_unchecked() }.is_empty() =>
+            {
+                &[]
+            },
+
+            other => {
+                return Err(Self::Error::invalid_type(
+                    de::Unexpected::Other(&format!("{other:?}")),
+                    &"Array",
+                ));
+            },
+        };
+
+        visitor.visit_seq(SeqDeserializer { iter: elements.iter() })
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let iter = match self.kind() {
+            ObjectKind::Dictionary => {
+                Some(unsafe { self.as_dictionary_unchecked() }.iter())
+            },
+
+            // Empty arrays are also valid dictionaries.
+            ObjectKind::Array
+                if unsafe { self.as_array_unchecked() }.is_empty() =>
+            {
+                None
+            },
+
+            other => {
+                return Err(Self::Error::invalid_type(
+                    de::Unexpected::Other(&format!("{other:?}")),
+                    &"Dictionary",
+                ));
+            },
+        };
+
+        visitor.visit_map(MapDeserializer { iter, value: None })
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+}
+
+/// Passes a borrowed string to the visitor, falling back to an owned lossy
+/// copy if it's not valid UTF-8.
+#[inline]
+fn visit_str<'de, V>(
+    str: NvimStr<'de>,
+    visitor: V,
+) -> Result<V::Value, DeserializeError>
+where
+    V: de::Visitor<'de>,
+{
+    let bytes = str.as_bytes();
+
+    match core::str::from_utf8(bytes) {
+        Ok(str) => visitor.visit_borrowed_str(str),
+        Err(_) => visitor.visit_str(&String::from_utf8_lossy(bytes)),
+    }
+}
+
+struct SeqDeserializer<'de> {
+    iter: core::slice::Iter<'de, Object>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        self.iter.next().map(|obj| seed.deserialize(obj)).transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer<'de> {
+    /// `None` if the map was deserialized from an empty array.
+    iter: Option<DictIter<'de>>,
+    value: Option<&'de Object>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let Some((key, value)) = self.iter.as_mut().and_then(Iterator::next)
+        else {
+            return Ok(None);
+        };
+
+        self.value = Some(value);
+
+        seed.deserialize(KeyDeserializer { key: key.as_nvim_str() }).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(obj) => seed.deserialize(obj),
+            _ => Err(Self::Error::custom("object is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.as_ref().map_or(0, ExactSizeIterator::len))
+    }
+}
+
+/// Deserializes the key of a dictionary, which is always a string.
+struct KeyDeserializer<'de> {
+    key: NvimStr<'de>,
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = DeserializeError;
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+    }
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visit_str(self.key, visitor)
+    }
+}
+
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    value: Option<&'de Object>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeserializeError;
+    type Variant = VariantDeserializer<'de>;
+
+    fn variant_seed<V>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = BorrowedStrDeserializer::new(self.variant);
+        let deserializer = VariantDeserializer { value: self.value };
+        seed.deserialize(variant).map(|v| (v, deserializer))
+    }
+}
+
+struct VariantDeserializer<'de> {
+    value: Option<&'de Object>,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(obj) => seed.deserialize(obj),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(obj) => de::Deserializer::deserialize_map(obj, visitor),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(obj) => de::Deserializer::deserialize_seq(obj, visitor),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            None => Ok(()),
+
+            _ => Err(Self::Error::invalid_type(
+                de::Unexpected::NewtypeVariant,
+                &"unit variant",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{Array, Dictionary};
+
+    #[test]
+    fn deserialize_borrowed_str() {
+        let obj = Object::from("foo");
+        assert_eq!(<&str>::deserialize(&obj), Ok("foo"));
+        assert_eq!(<&[u8]>::deserialize(&obj), Ok(&b"foo"[..]));
+    }
+
+    #[test]
+    fn deserialize_borrowed_struct() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Line<'a> {
+            text: &'a str,
+            #[serde(borrow)]
+            words: Vec<&'a str>,
+            number: u32,
+        }
+
+        let obj = Object::from(Dictionary::from_iter([
+            ("text", Object::from("foo bar")),
+            ("words", Object::from(Array::from(("foo", "bar")))),
+            ("number", Object::from(42)),
+        ]));
+
+        let line = Line::deserialize(&obj).unwrap();
+
+        assert_eq!(
+            line,
+            Line { text: "foo bar", words: vec!["foo", "bar"], number: 42 }
+        );
+
+        // The strings are borrowed from the object.
+        let Some(text) = unsafe { obj.as_dictionary_unchecked() }.get("text")
+        else {
+            panic!("missing text");
+        };
+        let text = unsafe { text.as_nvim_str_unchecked() };
+        assert_eq!(line.text.as_ptr(), text.as_bytes().as_ptr());
+    }
+
+    #[test]
+    fn deserialize_borrowed_enum() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Change<'a> {
+            Deleted,
+            Inserted(&'a str),
+        }
+
+        let obj = Object::from("Deleted");
+        assert_eq!(Change::deserialize(&obj), Ok(Change::Deleted));
+
+        let obj = Object::from(Dictionary::from_iter([("Inserted", "foo")]));
+        assert_eq!(Change::deserialize(&obj), Ok(Change::Inserted("foo")));
+    }
+
+    #[test]
+    fn deserialize_borrowed_matches_owned() {
+        let obj = Object::from(Dictionary::from_iter([
+            ("foo", Object::from(Array::from((1, 2.5, true)))),
+            ("bar", Object::from(Dictionary::new())),
+            ("baz", Object::nil()),
+            ("qux", Object::from(crate::String::from_bytes(b"caf\xe9"))),
+        ]));
+
+        let owned =
+            Object::deserialize(super::super::Deserializer::new(obj.clone()));
+
+        assert_eq!(Object::deserialize(&obj), owned);
+    }
+
+    #[test]
+    fn deserialize_invalid_utf8() {
+        let obj = Object::from(crate::String::from_bytes(b"caf\xe9"));
+
+        assert_eq!(String::deserialize(&obj).unwrap(), "caf\u{fffd}");
+
+        assert_eq!(<&[u8]>::deserialize(&obj), Ok(&b"caf\xe9"[..]));
+    }
+}
@@ -4,6 +4,7 @@
 //! [Serde]: https://serde.rs/
 
 mod de;
+mod de_borrowed;
 mod error;
 mod ser;
 
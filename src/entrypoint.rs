@@ -17,7 +17,9 @@ where
         luajit::init(lua_state);
 
         #[cfg(feature = "libuv")]
-        libuv::init(lua_state);
+        if let Err(err) = libuv::init(lua_state) {
+            luajit::utils::push_error(&err, lua_state);
+        }
 
         match body().push(lua_state) {
             Ok(num_pushed) => num_pushed,
@@ -33,3 +33,15 @@ pub enum Error {
     #[error(transparent)]
     Mlua(#[from] mlua::Error),
 }
+
+impl Error {
+    /// Returns the stack traceback of the error, if it was raised by Lua
+    /// code called from Rust, either directly or via an API function.
+    pub fn traceback(&self) -> Option<&luajit::Traceback> {
+        match self {
+            Self::Lua(err) => err.traceback(),
+            Self::Api(err) => err.traceback(),
+            _ => None,
+        }
+    }
+}
@@ -35,12 +35,46 @@ pub mod libuv {
     pub use libuv::*;
 }
 
+pub mod conversion {
+    //! Traits for converting between Neovim [`Object`](crate::Object)s and
+    //! Rust types.
+    pub use ::macros::{FromObject, ToObject};
+    #[doc(inline)]
+    pub use types::conversion::*;
+}
+
 pub mod lua {
     //! Low-level Rust bindings to [LuaJIT], the Lua version used by Neovim.
     //!
     //! [LuaJIT]: https://luajit.org/
+    pub use ::macros::{Poppable, Pushable};
     #[doc(inline)]
     pub use luajit::*;
+
+    /// Compiles a chunk of Lua code into a [`Function`](crate::Function),
+    /// without running it.
+    ///
+    /// The `chunkname` is used in error messages and stack tracebacks.
+    /// Syntax errors are returned as [`Error::SyntaxError`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use nvim_oxi::lua;
+    ///
+    /// let add = lua::load::<(i32, i32), i32>(
+    ///     "local a, b = ...; return a + b",
+    ///     "add",
+    /// )?;
+    ///
+    /// assert_eq!(add.call((1, 2))?, 3);
+    /// ```
+    pub fn load<A, R>(
+        source: &str,
+        chunkname: &str,
+    ) -> Result<crate::Function<A, R>, Error> {
+        crate::Function::load(source, chunkname)
+    }
 }
 
 #[cfg(feature = "mlua")]
@@ -83,6 +117,9 @@ pub mod mlua {
 }
 
 pub use error::{Error, Result};
+#[cfg(feature = "libuv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "libuv")))]
+pub use libuv::spawn;
 pub use luajit::{IntoResult, dbg, print};
 pub use macros::plugin;
 #[cfg(feature = "test")]
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[derive(Debug, thiserror::Error)]
+#[error("oops")]
+struct Oops;
+
+#[nvim_oxi::test]
+fn callback_error_is_reported(terminator: TestTerminator) {
+    set_error_handler(move |err| {
+        reset_error_handler();
+
+        let result = match err {
+            CallbackError::Error { handle: HandleKind::Timer, error }
+                if error.to_string() == "oops" =>
+            {
+                Ok(())
+            },
+            other => Err(TestFailure::Error(other.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+
+    TimerHandle::once(Duration::from_millis(10), || Err::<(), _>(Oops))
+        .unwrap();
+}
+
+#[nvim_oxi::test]
+fn callback_panic_is_caught(terminator: TestTerminator) {
+    set_error_handler(move |err| {
+        reset_error_handler();
+
+        let result = match err {
+            CallbackError::Panic { handle: HandleKind::Timer, message }
+                if message == "boom" =>
+            {
+                Ok(())
+            },
+            other => Err(TestFailure::Error(other.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+
+    TimerHandle::once(Duration::from_millis(10), || -> Result<(), Oops> {
+        panic!("boom")
+    })
+    .unwrap();
+}
+
+#[nvim_oxi::test]
+fn task_panic_is_resumed(terminator: TestTerminator) {
+    let handle = spawn(async {
+        panic!("boom");
+    });
+
+    // Awaiting the handle resumes the panic in this task, whose own handle
+    // is dropped, so the panic is reported to the error handler.
+    spawn(handle);
+
+    set_error_handler(move |err| {
+        reset_error_handler();
+
+        let result = match err {
+            CallbackError::Panic { handle: HandleKind::Task, message }
+                if message == "boom" =>
+            {
+                Ok(())
+            },
+            other => Err(TestFailure::Error(other.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
@@ -0,0 +1,104 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use nvim_oxi::api::{self, opts::BufDeleteOpts};
+use nvim_oxi::libuv::*;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn cancel_token_buffer_wipeout() -> Result<(), api::Error> {
+    let buffer = api::create_buf(true, false)?;
+
+    let token = CancellationToken::for_buffer(&buffer)?;
+    assert!(!token.is_cancelled());
+
+    buffer.delete(&BufDeleteOpts::builder().force(true).build())?;
+    assert!(token.is_cancelled());
+
+    Ok(())
+}
+
+#[nvim_oxi::test]
+fn cancel_token_window_closed() -> Result<(), api::Error> {
+    api::command("split")?;
+
+    let window = api::get_current_win();
+
+    let other = api::list_wins().find(|win| win != &window).unwrap();
+    let other = CancellationToken::for_window(&other)?;
+
+    let token = CancellationToken::for_window(&window)?;
+    assert!(!token.is_cancelled());
+
+    window.close(true)?;
+    assert!(token.is_cancelled());
+    assert!(!other.is_cancelled());
+
+    Ok(())
+}
+
+#[nvim_oxi::test]
+fn cancel_token_child() {
+    let parent = CancellationToken::new();
+    let child = parent.child_token();
+
+    let has_run = Rc::new(Cell::new(false));
+    let also_has_run = has_run.clone();
+    child.on_cancel(move || also_has_run.set(true));
+
+    let timer = TimerHandle::start(
+        Duration::from_secs(10),
+        Duration::from_secs(10),
+        |_| {},
+    )
+    .unwrap();
+    child.own(timer);
+
+    parent.cancel();
+
+    assert!(child.is_cancelled());
+    assert!(has_run.get());
+
+    // Cancelling a child token doesn't cancel its parent.
+    let parent = CancellationToken::new();
+    parent.child_token().cancel();
+    assert!(!parent.is_cancelled());
+}
+
+#[nvim_oxi::test]
+fn cancel_token_remove_on_cancel() {
+    let token = CancellationToken::new();
+
+    let has_run = Rc::new(Cell::new(false));
+    let also_has_run = has_run.clone();
+    let key = token.on_cancel(move || also_has_run.set(true));
+    assert_eq!(Rc::strong_count(&has_run), 2);
+
+    // Removing the callback drops it without executing it.
+    assert!(token.remove_on_cancel(key));
+    assert_eq!(Rc::strong_count(&has_run), 1);
+    assert!(!token.remove_on_cancel(key));
+
+    token.cancel();
+    assert!(!has_run.get());
+}
+
+#[nvim_oxi::test]
+fn cancel_token_spawn(terminator: TestTerminator) {
+    let token = CancellationToken::new();
+
+    let handle = token.spawn(async {
+        let _ = sleep(Duration::from_secs(10)).await;
+    });
+
+    token.cancel();
+
+    nvim_oxi::spawn(async move {
+        let result = match handle.await {
+            None => Ok(()),
+            Some(()) => Err(TestFailure::Error("the task wasn't cancelled")),
+        };
+        terminator.terminate(result);
+    });
+}
@@ -0,0 +1,80 @@
+use std::thread;
+
+use nvim_oxi::api;
+use nvim_oxi::libuv::*;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn dispatch_sync_from_thread(terminator: TestTerminator) {
+    thread::spawn(move || {
+        let lines = vec!["foo".to_owned(), "bar".to_owned()];
+
+        let line_count = dispatch_sync(move || {
+            let mut buf = api::Buffer::current();
+            buf.set_lines(.., true, lines).unwrap();
+            buf.line_count().unwrap()
+        });
+
+        let result = match line_count {
+            Ok(2) => Ok(()),
+            Ok(_) => Err(TestFailure::Error("wrong line count".to_owned())),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn dispatch_sync_on_main_thread() {
+    let main_thread = MainThread::get();
+    assert!(main_thread.is_current());
+    assert_eq!(main_thread.dispatch_sync(|| 42), Ok(42));
+}
+
+#[nvim_oxi::test]
+fn dispatch_many_from_thread(terminator: TestTerminator) {
+    thread::spawn(move || {
+        // Dispatching a bunch of closures in a row triggers the coalescing of
+        // `uv_async_send`, but every closure should still be executed.
+        let futures =
+            (0..100).map(|i| dispatch(move || i)).collect::<Vec<_>>();
+
+        let sum = futures
+            .into_iter()
+            .try_fold(0, |sum, fut| block_on(fut).map(|i| sum + i));
+
+        let result = match sum {
+            Ok(4950) => Ok(()),
+            Ok(_) => Err(TestFailure::Error("wrong sum".to_owned())),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+/// Polls a future to completion by parking the current thread.
+fn block_on<F: core::future::Future>(fut: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = core::pin::pin!(fut);
+
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
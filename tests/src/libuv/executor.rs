@@ -0,0 +1,89 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+use nvim_oxi::{api, lua, spawn};
+
+#[nvim_oxi::test]
+fn spawn_join_handle(terminator: TestTerminator) {
+    let handle = spawn(async { api::get_current_buf() });
+
+    spawn(async move {
+        let buf = handle.await;
+        let result = if buf == api::Buffer::current() {
+            Ok(())
+        } else {
+            Err(TestFailure::Error("wrong buffer"))
+        };
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn spawn_nested(terminator: TestTerminator) {
+    let num_polled = Rc::new(Cell::new(0));
+
+    let also_num_polled = num_polled.clone();
+
+    spawn(async move {
+        let inner = spawn(async move {
+            also_num_polled.set(also_num_polled.get() + 1);
+            42
+        });
+
+        let n = inner.await;
+
+        let result = if n == 42 && num_polled.get() == 1 {
+            Ok(())
+        } else {
+            Err(TestFailure::Error("inner task didn't run"))
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn join_handle_abort(terminator: TestTerminator) {
+    let has_run = Rc::new(Cell::new(false));
+
+    let also_has_run = has_run.clone();
+
+    let handle = spawn(async move { also_has_run.set(true) });
+
+    handle.abort();
+
+    spawn(async move {
+        let result = if has_run.get() {
+            Err(TestFailure::Error("aborted task was polled"))
+        } else {
+            Ok(())
+        };
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn spawn_lua_thread(terminator: TestTerminator) {
+    let fun = lua::load::<i32, i32>(
+        "local sum = 0
+         for i = 1, ... do
+           sum = sum + i
+           coroutine.yield(i)
+         end
+         return sum",
+        "sum",
+    )
+    .unwrap();
+
+    let thread = lua::Thread::new(fun).unwrap();
+
+    spawn(async move {
+        let result = match thread.run::<_, i32>(4).await {
+            Ok(10) => Ok(()),
+            Ok(n) => Err(TestFailure::Error(format!("expected 10, got {n}"))),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+        terminator.terminate(result);
+    });
+}
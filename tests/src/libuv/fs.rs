@@ -0,0 +1,80 @@
+use nvim_oxi::libuv::{Error, fs};
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn fs_roundtrip(terminator: TestTerminator) {
+    let dir = std::env::temp_dir()
+        .join(format!("nvim-oxi-fs-roundtrip-{}", std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+
+    spawn(async move {
+        let result = async {
+            fs::mkdir(&dir).await?;
+
+            let src = dir.join("foo.txt");
+            let dst = dir.join("bar.txt");
+
+            fs::write(&src, "Hello, world!").await?;
+
+            if fs::stat(&src).await?.size() != 13 {
+                return Ok(Err("wrong file size"));
+            }
+
+            fs::rename(&src, &dst).await?;
+
+            if fs::read(&dst).await? != b"Hello, world!" {
+                return Ok(Err("wrong file contents"));
+            }
+
+            let entries = fs::scandir(&dir).await?;
+
+            if entries.len() != 1
+                || entries[0].name() != "bar.txt"
+                || entries[0].file_type() != fs::FileType::File
+            {
+                return Ok(Err("wrong directory entries"));
+            }
+
+            if !fs::realpath(&dst).await?.is_absolute() {
+                return Ok(Err("realpath isn't absolute"));
+            }
+
+            fs::unlink(&dst).await?;
+
+            if !fs::scandir(&dir).await?.is_empty() {
+                return Ok(Err("file wasn't removed"));
+            }
+
+            Ok::<_, Error>(Ok(()))
+        }
+        .await;
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(msg)) => Err(TestFailure::Error(msg.to_owned())),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn fs_not_found(terminator: TestTerminator) {
+    spawn(async move {
+        let result = match fs::read("/this/file/does/not/exist").await {
+            Err(Error::Fs(code)) if code.name() == "ENOENT" => Ok(()),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+            Ok(contents) => Err(TestFailure::Error(format!(
+                "read {} bytes from a missing file",
+                contents.len()
+            ))),
+        };
+
+        terminator.terminate(result);
+    });
+}
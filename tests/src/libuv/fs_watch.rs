@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("nvim-oxi-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[nvim_oxi::test]
+fn fs_event_stream(terminator: TestTerminator) {
+    let dir = temp_dir("fs-event");
+    let file = dir.join("foo.txt");
+
+    let mut events = FsEventHandle::watch(&dir, FsEventOptions::default())
+        .expect("couldn't watch directory");
+
+    spawn({
+        let file = file.clone();
+        async move {
+            sleep(Duration::from_millis(20)).await.unwrap();
+            std::fs::write(file, "foo").unwrap();
+        }
+    });
+
+    spawn(async move {
+        let event = events.next_event();
+
+        let result = match timeout(Duration::from_secs(2), event).await {
+            Ok(Ok(event)) if event.path.ends_with("foo.txt") => Ok(()),
+            Ok(Ok(_)) => Err(TestFailure::Error("unexpected path")),
+            Ok(Err(_)) => Err(TestFailure::Error("couldn't watch directory")),
+            Err(_) => Err(TestFailure::Error("no event received")),
+        };
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn fs_poll_callback(terminator: TestTerminator) {
+    let dir = temp_dir("fs-poll");
+    let file = dir.join("foo.txt");
+    std::fs::write(&file, "foo").unwrap();
+
+    let handle = FsPollHandle::start(
+        &file,
+        Duration::from_millis(10),
+        move |handle, event| {
+            let result = match event {
+                Ok(event) if event.curr.size() == 6 => Ok(()),
+                Ok(_) => Err(TestFailure::Error("unexpected file size")),
+                Err(_) => Err(TestFailure::Error("couldn't stat file")),
+            };
+            let _ = handle.stop();
+            let _ = std::fs::remove_dir_all(&dir);
+            terminator.terminate(result);
+        },
+    );
+
+    assert!(handle.is_ok());
+
+    spawn(async move {
+        sleep(Duration::from_millis(50)).await.unwrap();
+        std::fs::write(file, "foobar").unwrap();
+    });
+}
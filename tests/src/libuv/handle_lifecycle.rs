@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn handle_lifecycle_ref_unref() {
+    let mut handle = TimerHandle::start(
+        Duration::from_secs(10),
+        Duration::from_secs(10),
+        |_| {},
+    )
+    .unwrap();
+
+    assert!(handle.is_active());
+    assert!(handle.has_ref());
+
+    // A repeating timer that's been unreferenced doesn't keep the event loop
+    // alive.
+    handle.unref();
+    assert!(!handle.has_ref());
+
+    handle.ref_();
+    assert!(handle.has_ref());
+
+    handle.stop().unwrap();
+    assert!(!handle.is_active());
+    assert!(!handle.is_closing());
+}
+
+#[nvim_oxi::test]
+fn handle_lifecycle_close(terminator: TestTerminator) {
+    let handle = TimerHandle::start(
+        Duration::from_secs(10),
+        Duration::from_secs(10),
+        |_| -> Result<(), Error> {
+            panic!("the callback of a closed timer was called")
+        },
+    )
+    .unwrap();
+
+    handle.close(move || terminator.terminate::<&str>(Ok(())));
+}
+
+#[nvim_oxi::test]
+fn handle_lifecycle_detached_once(terminator: TestTerminator) {
+    // Dropping an active handle doesn't stop it.
+    let _ = TimerHandle::once(Duration::from_millis(10), move || {
+        terminator.terminate::<&str>(Ok(()));
+    })
+    .unwrap();
+}
+
+#[nvim_oxi::test]
+fn handle_lifecycle_async_close(terminator: TestTerminator) {
+    let handle = AsyncHandle::new(|| {}).unwrap();
+
+    let also_handle = handle.clone();
+
+    assert!(handle.is_active());
+
+    handle.close(move || {
+        let result = match also_handle.send() {
+            Err(Error::HandleClosed) if also_handle.is_closing() => Ok(()),
+            _ => Err(TestFailure::Error("sent to a closed async handle")),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn handle_lifecycle_process(terminator: TestTerminator) {
+    spawn(async move {
+        let result = async {
+            let mut child = Process::new("sleep")
+                .arg("10")
+                .spawn()
+                .map_err(|_| "couldn't spawn process")?;
+
+            if !child.is_active() || !child.has_ref() {
+                return Err("a running process should be active and ref'd");
+            }
+
+            // An unreferenced child doesn't keep the event loop alive.
+            child.unref();
+            if child.has_ref() {
+                return Err("the process is still ref'd after unref()");
+            }
+
+            child.ref_();
+            if !child.has_ref() {
+                return Err("the process isn't ref'd after ref_()");
+            }
+
+            child.kill(15).map_err(|_| "couldn't kill process")?;
+            child.wait().await;
+
+            // The handle is closed as soon as the process exits.
+            if child.is_active() || !child.is_closing() || child.has_ref() {
+                return Err("an exited process should be closed");
+            }
+
+            Ok(child)
+        }
+        .await;
+
+        match result {
+            Ok(child) => {
+                child.close(move || terminator.terminate::<&str>(Ok(())))
+            },
+            Err(err) => terminator.terminate(Err(TestFailure::Error(err))),
+        }
+    });
+}
+
+#[nvim_oxi::test]
+fn handle_lifecycle_process_close(terminator: TestTerminator) {
+    let child = Process::new("sleep").arg("10").spawn().unwrap();
+    let pid = child.pid();
+
+    child.close(move || {
+        // Closing the handle doesn't kill the process.
+        let _ = Process::new("kill").arg(pid.to_string()).spawn();
+        terminator.terminate::<&str>(Ok(()));
+    });
+}
@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn idle_handle_0(terminator: TestTerminator) {
+    let mut count = 0;
+
+    IdleHandle::start(move |idle| {
+        count += 1;
+
+        if count == 3 {
+            idle.stop().unwrap();
+            terminator.terminate::<Error>(Ok(()));
+        }
+    })
+    .unwrap();
+}
+
+#[nvim_oxi::test]
+fn prepare_and_check_handles(terminator: TestTerminator) {
+    let phases = Rc::new(RefCell::new(Vec::new()));
+
+    // Keep the loop spinning so that both handles get called.
+    let mut idle = IdleHandle::start(|_| {}).unwrap();
+
+    let prepare = {
+        let phases = phases.clone();
+        PrepareHandle::start(move |_| phases.borrow_mut().push("prepare"))
+            .unwrap()
+    };
+
+    let mut prepare = Some(prepare);
+
+    CheckHandle::start(move |check| {
+        let phases = &mut *phases.borrow_mut();
+
+        phases.push("check");
+
+        if phases.len() < 4 {
+            return;
+        }
+
+        check.stop().unwrap();
+        prepare.take().unwrap().stop().unwrap();
+        idle.stop().unwrap();
+
+        // The check handle runs right after the prepare handle.
+        let result = if phases.windows(2).any(|w| w == ["prepare", "check"]) {
+            Ok(())
+        } else {
+            Err(TestFailure::Error("check didn't run after prepare"))
+        };
+
+        terminator.terminate(result);
+    })
+    .unwrap();
+}
@@ -1,2 +1,15 @@
 mod async_handle;
+mod callback_error;
+mod cancel;
+mod dispatch;
+mod executor;
+mod fs;
+mod fs_watch;
+mod handle_lifecycle;
+mod loop_hooks;
+mod process;
+mod signal_handle;
+mod socket;
+mod time;
 mod timer_handle;
+mod work;
@@ -0,0 +1,94 @@
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn process_stdout(terminator: TestTerminator) {
+    spawn(async move {
+        let result = async {
+            let mut child = Process::new("echo")
+                .arg("Hello, world!")
+                .stdout(Stdio::Piped)
+                .spawn()?;
+
+            let stdout = child.stdout.take().unwrap().read_to_end().await?;
+            let status = child.wait().await;
+            Ok::<_, Error>((stdout, status))
+        }
+        .await;
+
+        let result = match result {
+            Ok((stdout, status)) if stdout == b"Hello, world!\n" => {
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(TestFailure::Error("process didn't exit successfully"))
+                }
+            },
+            Ok(_) => Err(TestFailure::Error("unexpected stdout")),
+            Err(_) => Err(TestFailure::Error("couldn't run process")),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn process_stdin(terminator: TestTerminator) {
+    spawn(async move {
+        let result = async {
+            let mut child = Process::new("cat")
+                .stdin(Stdio::Piped)
+                .stdout(Stdio::Piped)
+                .spawn()?;
+
+            let mut stdin = child.stdin.take().unwrap();
+            stdin.write("foo").await?;
+            stdin.shutdown().await?;
+
+            child.stdout.take().unwrap().read_to_end().await
+        }
+        .await;
+
+        let result = match result {
+            Ok(stdout) if stdout == b"foo" => Ok(()),
+            Ok(_) => Err(TestFailure::Error("unexpected stdout")),
+            Err(_) => Err(TestFailure::Error("couldn't run process")),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn process_exit_code_and_env(terminator: TestTerminator) {
+    spawn(async move {
+        let result = async {
+            let mut child = Process::new("sh")
+                .args(["-c", "exit $CODE"])
+                .env("CODE", "3")
+                .spawn()?;
+
+            Ok::<_, Error>(child.wait().await)
+        }
+        .await;
+
+        let result = match result {
+            Ok(status) if status.code() == 3 && status.signal().is_none() => {
+                Ok(())
+            },
+            Ok(_) => Err(TestFailure::Error("unexpected exit status")),
+            Err(_) => Err(TestFailure::Error("couldn't run process")),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn process_spawn_error() {
+    let err = Process::new("this-program-does-not-exist").spawn().unwrap_err();
+    assert!(
+        matches!(err, Error::ProcessSpawn(code) if code.name() == "ENOENT")
+    );
+}
@@ -0,0 +1,35 @@
+use nvim_oxi::libuv::*;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+/// The value of `SIGWINCH` on both Linux and macOS.
+const SIGWINCH: i32 = 28;
+
+#[nvim_oxi::test]
+fn signal_handle_oneshot(terminator: TestTerminator) {
+    SignalHandle::start_oneshot(SIGWINCH, move |signum| {
+        let result = if signum == SIGWINCH {
+            Ok(())
+        } else {
+            Err(TestFailure::Error("received the wrong signal"))
+        };
+
+        terminator.terminate(result);
+    })
+    .unwrap();
+
+    std::process::Command::new("kill")
+        .args(["-WINCH", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+}
+
+#[nvim_oxi::test]
+fn signal_handle_invalid_signum() {
+    let Err(err) = SignalHandle::start(-1, |_, _| {}) else {
+        panic!("starting a handle with an invalid signal should fail")
+    };
+
+    assert!(
+        matches!(err, Error::SignalStart(code) if code.name() == "EINVAL")
+    );
+}
@@ -0,0 +1,98 @@
+use std::net::SocketAddr;
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn tcp_echo(terminator: TestTerminator) {
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let mut listener = TcpListener::bind(addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    spawn(async move {
+        let mut conn = listener.accept().await?;
+        let bytes = conn.read_to_end().await?;
+        conn.write(bytes).await?;
+        conn.shutdown().await
+    });
+
+    spawn(async move {
+        let result = async {
+            let mut client = TcpHandle::connect(addr).await?;
+            client.write("Hello, world!").await?;
+            client.shutdown().await?;
+            client.read_to_end().await
+        }
+        .await;
+
+        let result = match result {
+            Ok(bytes) if bytes == b"Hello, world!" => Ok(()),
+            Ok(_) => Err(TestFailure::Error("unexpected reply")),
+            Err(_) => Err(TestFailure::Error("couldn't connect to server")),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn pipe_echo(terminator: TestTerminator) {
+    let path = std::env::temp_dir()
+        .join(format!("nvim-oxi-pipe-echo-{}.sock", std::process::id()));
+
+    let _ = std::fs::remove_file(&path);
+
+    let mut listener = PipeListener::bind(&path).unwrap();
+
+    spawn(async move {
+        let mut conn = listener.accept().await?;
+        let bytes = conn.read_to_end().await?;
+        conn.write(bytes).await?;
+        conn.shutdown().await
+    });
+
+    spawn(async move {
+        let result = async {
+            let mut client = PipeHandle::connect(&path).await?;
+            client.write("Hello, world!").await?;
+            client.shutdown().await?;
+            client.read_to_end().await
+        }
+        .await;
+
+        let _ = std::fs::remove_file(&path);
+
+        let result = match result {
+            Ok(bytes) if bytes == b"Hello, world!" => Ok(()),
+            Ok(_) => Err(TestFailure::Error("unexpected reply")),
+            Err(_) => Err(TestFailure::Error("couldn't connect to server")),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn tcp_connect_refused(terminator: TestTerminator) {
+    // Bind a listener to get a free port, then drop it so that nothing is
+    // listening on that port.
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let addr = TcpListener::bind(addr).unwrap().local_addr().unwrap();
+
+    spawn(async move {
+        let result = match TcpHandle::connect(addr).await {
+            Err(Error::StreamConnect(code))
+                if code.name() == "ECONNREFUSED" =>
+            {
+                Ok(())
+            },
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+            Ok(_) => Err(TestFailure::Error(
+                "connected to a closed port".to_owned(),
+            )),
+        };
+
+        terminator.terminate(result);
+    });
+}
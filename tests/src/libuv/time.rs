@@ -0,0 +1,75 @@
+use std::future::pending;
+use std::time::{Duration, Instant};
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn sleep_0(terminator: TestTerminator) {
+    let start = Instant::now();
+
+    spawn(async move {
+        let result = match sleep(Duration::from_millis(50)).await {
+            Ok(()) if start.elapsed() >= Duration::from_millis(50) => Ok(()),
+            Ok(()) => Err(TestFailure::Error("woke up too early".to_owned())),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn interval_0(terminator: TestTerminator) {
+    let start = Instant::now();
+
+    spawn(async move {
+        let mut interval = interval(Duration::from_millis(20));
+
+        let mut ticks = Ok(());
+
+        for _ in 0..4 {
+            ticks = ticks.and(interval.tick().await);
+        }
+
+        // The first tick completes immediately.
+        let result = match ticks {
+            Ok(()) if start.elapsed() >= Duration::from_millis(60) => Ok(()),
+            Ok(()) => Err(TestFailure::Error("ticked too early".to_owned())),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn timeout_elapsed(terminator: TestTerminator) {
+    spawn(async move {
+        let result =
+            match timeout(Duration::from_millis(10), pending::<()>()).await {
+                Err(Error::Timeout) => Ok(()),
+                Err(err) => Err(TestFailure::Error(err.to_string())),
+                Ok(()) => Err(TestFailure::Error(
+                    "pending future completed".to_owned(),
+                )),
+            };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn timeout_completed(terminator: TestTerminator) {
+    spawn(async move {
+        let future = async { 42 };
+
+        let result = match timeout(Duration::from_secs(1), future).await {
+            Ok(42) => Ok(()),
+            _ => Err(TestFailure::Error("future didn't complete")),
+        };
+
+        terminator.terminate(result);
+    });
+}
@@ -39,3 +39,25 @@ fn timer_handle_0() {
 
     assert_eq!(rx.try_recv().unwrap_err(), mpsc::TryRecvError::Empty);
 }
+
+#[nvim_oxi::test]
+fn timer_handle_repeat() {
+    let mut handle = TimerHandle::start(
+        Duration::from_secs(10),
+        Duration::from_secs(1),
+        |_| {},
+    )
+    .unwrap();
+
+    assert_eq!(handle.get_repeat(), Duration::from_secs(1));
+
+    handle.set_repeat(Duration::from_secs(2));
+    assert_eq!(handle.get_repeat(), Duration::from_secs(2));
+
+    assert!(handle.get_due_in() <= Duration::from_secs(10));
+
+    handle.again().unwrap();
+    assert!(handle.get_due_in() <= Duration::from_secs(2));
+
+    handle.stop().unwrap();
+}
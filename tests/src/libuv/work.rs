@@ -0,0 +1,56 @@
+use std::thread;
+
+use nvim_oxi::libuv::*;
+use nvim_oxi::spawn;
+use nvim_oxi::tests::{TestFailure, TestTerminator};
+
+#[nvim_oxi::test]
+fn queue_work_0(terminator: TestTerminator) {
+    let main_thread = thread::current().id();
+
+    queue_work(
+        move || (thread::current().id(), (1..=100u64).sum::<u64>()),
+        move |(work_thread, sum)| {
+            let result = if work_thread == main_thread {
+                Err(TestFailure::Error("work ran on the main thread"))
+            } else if thread::current().id() != main_thread {
+                Err(TestFailure::Error("after didn't run on the main thread"))
+            } else if sum != 5050 {
+                Err(TestFailure::Error("wrong output"))
+            } else {
+                Ok(())
+            };
+
+            terminator.terminate(result);
+        },
+    )
+    .unwrap();
+}
+
+#[nvim_oxi::test]
+fn queue_work_async_0(terminator: TestTerminator) {
+    spawn(async move {
+        let result = match queue_work_async(|| 42).await {
+            Ok(42) => Ok(()),
+            Ok(n) => Err(TestFailure::Error(format!("expected 42, got {n}"))),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+        };
+
+        terminator.terminate(result);
+    });
+}
+
+#[nvim_oxi::test]
+fn queue_work_async_panic(terminator: TestTerminator) {
+    spawn(async move {
+        let result = match queue_work_async(|| panic!("boom")).await {
+            Err(Error::WorkPanicked(_)) => Ok(()),
+            Err(err) => Err(TestFailure::Error(err.to_string())),
+            Ok(()) => Err(TestFailure::Error(
+                "the panicking closure returned".to_owned(),
+            )),
+        };
+
+        terminator.terminate(result);
+    });
+}
@@ -0,0 +1,57 @@
+use nvim_oxi::lua::{self, Variadic};
+
+#[nvim_oxi::test]
+fn load_and_call() {
+    let add =
+        lua::load::<(i32, i32), i32>("local a, b = ...; return a + b", "add")
+            .unwrap();
+
+    assert_eq!(add.call((1, 2)).unwrap(), 3);
+    assert_eq!(add.call((40, 2)).unwrap(), 42);
+}
+
+#[nvim_oxi::test]
+fn load_syntax_error() {
+    let err =
+        lua::load::<(), ()>("local a = 1\nlocal b = ", "broken").unwrap_err();
+
+    let lua::Error::SyntaxError { chunkname, line, message } = &err else {
+        panic!("expected a syntax error, got {err:?}");
+    };
+
+    assert_eq!(chunkname, "broken");
+    assert_eq!(*line, Some(2));
+    assert!(message.contains("unexpected symbol"), "{message}");
+
+    let err = lua::load::<(), ()>("return 1", "bro\0ken").unwrap_err();
+    assert!(matches!(err, lua::Error::InvalidChunkname(_)), "{err:?}");
+}
+
+#[nvim_oxi::test]
+fn eval_expr() {
+    assert_eq!(lua::eval::<i32>("1 + 2").unwrap(), 3);
+    assert_eq!(lua::eval::<String>("('foo'):upper()").unwrap(), "FOO");
+    assert_eq!(*lua::eval::<Variadic<i32>>("1, 2, 3").unwrap(), [1, 2, 3]);
+
+    let err = lua::eval::<i32>("1 +").unwrap_err();
+    assert!(matches!(err, lua::Error::SyntaxError { line: Some(1), .. }));
+
+    // The source isn't used as the chunkname.
+    let err = lua::eval::<i32>("'a:1: b' +").unwrap_err();
+    let lua::Error::SyntaxError { chunkname, line, .. } = &err else {
+        panic!("expected a syntax error, got {err:?}");
+    };
+    assert_eq!(chunkname, "(eval)");
+    assert_eq!(*line, Some(1));
+
+    let err = lua::eval::<i32>("error('boom')").unwrap_err();
+    assert!(matches!(err, lua::Error::RuntimeError { .. }));
+}
+
+#[nvim_oxi::test]
+fn exec_chunk() {
+    lua::exec("_G.oxi_exec_test = 42").unwrap();
+    assert_eq!(lua::eval::<i32>("_G.oxi_exec_test").unwrap(), 42);
+
+    assert!(lua::exec("local = 1").is_err());
+}
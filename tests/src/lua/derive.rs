@@ -0,0 +1,122 @@
+use nvim_oxi::lua::{self, Poppable, Pushable};
+
+#[derive(Debug, PartialEq, Pushable, Poppable)]
+#[lua(rename_all = "camelCase")]
+struct Config {
+    line_width: u32,
+
+    #[lua(rename = "enabled")]
+    is_enabled: bool,
+
+    #[lua(default = "default_mode")]
+    mode: Mode,
+
+    #[lua(default)]
+    tags: Vec<String>,
+
+    #[lua(skip)]
+    cache: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Pushable, Poppable)]
+#[lua(rename_all = "snake_case")]
+enum Mode {
+    Auto,
+    FixedWidth { width: u32 },
+    Ratio(f64, f64),
+}
+
+#[derive(Debug, PartialEq, Pushable, Poppable)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, Pushable, Poppable)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, Pushable, Poppable)]
+#[lua(tag = "kind")]
+enum Shape<T> {
+    Circle { radius: T },
+    Empty,
+}
+
+fn default_mode() -> Mode {
+    Mode::Auto
+}
+
+#[nvim_oxi::test]
+fn derive_struct_round_trip() {
+    let config = Config {
+        line_width: 80,
+        is_enabled: true,
+        mode: Mode::FixedWidth { width: 100 },
+        tags: vec!["foo".into()],
+        cache: Some("bar".into()),
+    };
+
+    lua::globals().set("oxi_config", config).unwrap();
+
+    assert_eq!(lua::eval::<u32>("oxi_config.lineWidth").unwrap(), 80);
+    assert!(lua::eval::<bool>("oxi_config.enabled").unwrap());
+    assert_eq!(
+        lua::eval::<String>("oxi_config.mode.type").unwrap(),
+        "fixed_width"
+    );
+    assert!(lua::eval::<bool>("oxi_config.cache == nil").unwrap());
+
+    let config = lua::globals().get::<_, Config>("oxi_config").unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            line_width: 80,
+            is_enabled: true,
+            mode: Mode::FixedWidth { width: 100 },
+            tags: vec!["foo".into()],
+            cache: None,
+        }
+    );
+}
+
+#[nvim_oxi::test]
+fn derive_struct_defaults() {
+    let config =
+        lua::eval::<Config>("{ lineWidth = 120, enabled = false }").unwrap();
+
+    assert_eq!(config.mode, Mode::Auto);
+    assert!(config.tags.is_empty());
+
+    let err = lua::eval::<Config>("{ enabled = false }").unwrap_err();
+    assert!(err.to_string().contains("lineWidth"), "{err}");
+}
+
+#[nvim_oxi::test]
+fn derive_enum() {
+    assert_eq!(lua::eval::<Mode>("'auto'").unwrap(), Mode::Auto);
+
+    assert_eq!(
+        lua::eval::<Mode>("{ type = 'ratio', 16, 9 }").unwrap(),
+        Mode::Ratio(16.0, 9.0)
+    );
+
+    assert!(lua::eval::<Mode>("'manual'").is_err());
+
+    lua::globals().set("oxi_shape", Shape::Circle { radius: 2 }).unwrap();
+    assert_eq!(lua::eval::<String>("oxi_shape.kind").unwrap(), "Circle");
+    assert_eq!(
+        lua::globals().get::<_, Shape<i32>>("oxi_shape").unwrap(),
+        Shape::Circle { radius: 2 }
+    );
+
+    lua::globals().set("oxi_shape", Shape::<i32>::Empty).unwrap();
+    assert_eq!(lua::eval::<String>("oxi_shape").unwrap(), "Empty");
+}
+
+#[nvim_oxi::test]
+fn derive_tuple_structs() {
+    lua::globals().set("oxi_meters", Meters(1.5)).unwrap();
+    assert_eq!(lua::eval::<f64>("oxi_meters").unwrap(), 1.5);
+
+    lua::globals().set("oxi_point", Point(1, 2)).unwrap();
+    assert_eq!(lua::eval::<i32>("oxi_point[2]").unwrap(), 2);
+    assert_eq!(lua::eval::<Point>("{ 3, 4 }").unwrap(), Point(3, 4));
+}
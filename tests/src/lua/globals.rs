@@ -0,0 +1,42 @@
+use nvim_oxi::lua::{self, Table};
+use nvim_oxi::{Function, api};
+
+#[nvim_oxi::test]
+fn globals_get_set() {
+    let globals = lua::globals();
+
+    globals.set("oxi_globals_test", 42).unwrap();
+    assert_eq!(lua::eval::<i32>("oxi_globals_test").unwrap(), 42);
+
+    api::command("lua _G.oxi_globals_test = 'foo'").unwrap();
+    assert_eq!(globals.get::<_, String>("oxi_globals_test").unwrap(), "foo");
+
+    let vim = globals.get::<_, Table>("vim").unwrap();
+    assert!(vim.get::<_, Table>("api").is_ok());
+}
+
+#[nvim_oxi::test]
+fn require_module() {
+    lua::exec(
+        "package.preload['oxi_test_mod'] = function() return { answer = 42, \
+         double = function(n) return n * 2 end } end",
+    )
+    .unwrap();
+
+    let module = lua::require::<Table>("oxi_test_mod").unwrap();
+    assert_eq!(module.get::<_, i32>("answer").unwrap(), 42);
+
+    let double = module.get::<_, Function<i32, i32>>("double").unwrap();
+    assert_eq!(double.call(21).unwrap(), 42);
+}
+
+#[nvim_oxi::test]
+fn require_missing_module() {
+    let err = lua::require::<Table>("oxi_does_not_exist").unwrap_err();
+
+    let lua::Error::RuntimeError { message, .. } = &err else {
+        panic!("expected a runtime error, got {err:?}");
+    };
+
+    assert!(message.contains("oxi_does_not_exist"), "{message}");
+}
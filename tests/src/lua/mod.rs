@@ -0,0 +1,9 @@
+mod chunk;
+mod derive;
+mod globals;
+mod serde;
+mod table;
+mod thread;
+mod traceback;
+mod userdata;
+mod variadic;
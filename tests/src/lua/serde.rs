@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use ::serde::{Deserialize, Serialize};
+use nvim_oxi::lua::{self, LuaSerde};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    line_width: u32,
+    enabled: bool,
+    mode: Mode,
+    #[serde(default)]
+    tags: Vec<String>,
+    name: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Mode {
+    Auto,
+    Fixed(u32),
+    Ratio { num: f64, den: f64 },
+}
+
+#[nvim_oxi::test]
+fn lua_serde_deserialize_table() {
+    let LuaSerde(config) = lua::eval::<LuaSerde<Config>>(
+        "{ line_width = 80, enabled = true, mode = { fixed = 100 } }",
+    )
+    .unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            line_width: 80,
+            enabled: true,
+            mode: Mode::Fixed(100),
+            tags: vec![],
+            name: None,
+        }
+    );
+
+    let LuaSerde(mode) = lua::eval::<LuaSerde<Mode>>("'auto'").unwrap();
+    assert_eq!(mode, Mode::Auto);
+}
+
+#[nvim_oxi::test]
+fn lua_serde_round_trip() {
+    let config = Config {
+        line_width: 100,
+        enabled: false,
+        mode: Mode::Ratio { num: 1.0, den: 2.0 },
+        tags: vec!["foo".into(), "bar".into()],
+        name: Some("baz".into()),
+    };
+
+    lua::globals().set("oxi_serde_config", LuaSerde(config)).unwrap();
+
+    assert_eq!(lua::eval::<u32>("oxi_serde_config.line_width").unwrap(), 100);
+    assert_eq!(
+        lua::eval::<f64>("oxi_serde_config.mode.ratio.den").unwrap(),
+        2.0
+    );
+    assert_eq!(
+        lua::eval::<String>("oxi_serde_config.tags[2]").unwrap(),
+        "bar"
+    );
+
+    let LuaSerde(config) =
+        lua::globals().get::<_, LuaSerde<Config>>("oxi_serde_config").unwrap();
+
+    assert_eq!(config.mode, Mode::Ratio { num: 1.0, den: 2.0 });
+    assert_eq!(config.tags, ["foo", "bar"]);
+    assert_eq!(config.name.as_deref(), Some("baz"));
+}
+
+#[nvim_oxi::test]
+fn lua_serde_untyped_values() {
+    let LuaSerde(map) = lua::eval::<LuaSerde<HashMap<String, Vec<i64>>>>(
+        "{ foo = { 1, 2, 3 }, bar = {} }",
+    )
+    .unwrap();
+
+    assert_eq!(map["foo"], [1, 2, 3]);
+    assert!(map["bar"].is_empty());
+}
+
+#[nvim_oxi::test]
+fn lua_serde_error() {
+    let err =
+        lua::eval::<LuaSerde<Config>>("{ line_width = 'foo' }").unwrap_err();
+
+    assert!(matches!(err, lua::Error::PopError { .. }), "{err}");
+
+    // The stack is left untouched by a failed pop.
+    assert_eq!(lua::eval::<i32>("1 + 1").unwrap(), 2);
+}
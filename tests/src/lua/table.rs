@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use nvim_oxi::lua::Table;
+
+#[nvim_oxi::test]
+fn table_get_set() {
+    let table = Table::new();
+
+    table.set("foo", 42).unwrap();
+    table.set(1, "bar".to_owned()).unwrap();
+
+    assert_eq!(table.get::<_, i32>("foo").unwrap(), 42);
+    assert_eq!(table.get::<_, String>(1).unwrap(), "bar");
+    assert_eq!(table.get::<_, Option<i32>>("baz").unwrap(), None);
+    assert!(table.get::<_, i32>("baz").is_err());
+
+    assert_eq!(table.len(), 1);
+}
+
+#[nvim_oxi::test]
+fn table_metatable() {
+    let fallback = Table::new();
+    fallback.set("foo", 42).unwrap();
+
+    let metatable = Table::new();
+    metatable.set("__index", fallback).unwrap();
+
+    let table = Table::new();
+    assert!(table.metatable().is_none());
+
+    table.set_metatable(Some(&metatable));
+    assert!(table.metatable().is_some());
+
+    assert_eq!(table.get::<_, i32>("foo").unwrap(), 42);
+    assert_eq!(table.raw_get::<_, Option<i32>>("foo").unwrap(), None);
+
+    table.set_metatable(None);
+    assert!(table.metatable().is_none());
+}
+
+#[nvim_oxi::test]
+fn table_nil_key_is_an_error() {
+    let table = Table::new();
+    assert!(table.raw_set((), 42).is_err());
+}
+
+#[nvim_oxi::test]
+fn table_iter() {
+    let table = Table::new();
+
+    for (idx, value) in ["a", "b", "c"].into_iter().enumerate() {
+        table.set(idx + 1, value.to_owned()).unwrap();
+    }
+
+    table.set("foo", "bar".to_owned()).unwrap();
+
+    let values = table
+        .ipairs::<String>()
+        .map(|res| res.map(|(_, value)| value))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(values, ["a", "b", "c"]);
+
+    let map = table
+        .pairs::<String, String>()
+        .collect::<Result<HashMap<_, _>, _>>()
+        .unwrap();
+
+    assert_eq!(map.len(), 4);
+    assert_eq!(map["foo"], "bar");
+    assert_eq!(map["1"], "a");
+}
@@ -0,0 +1,96 @@
+use nvim_oxi::Function;
+use nvim_oxi::lua::{self, Thread, ThreadStatus, Yield};
+
+#[nvim_oxi::test]
+fn thread_resume_lua_function() {
+    let fun = lua::load::<i32, i32>(
+        "local n = ...; local m = coroutine.yield(n + 1); return n + m",
+        "counter",
+    )
+    .unwrap();
+
+    let thread = Thread::new(fun).unwrap();
+    assert_eq!(thread.status(), ThreadStatus::Suspended);
+
+    assert_eq!(thread.resume::<_, i32>(1).unwrap(), 2);
+    assert_eq!(thread.status(), ThreadStatus::Suspended);
+
+    assert_eq!(thread.resume::<_, i32>(10).unwrap(), 11);
+    assert_eq!(thread.status(), ThreadStatus::Dead);
+    assert!(!thread.is_resumable());
+
+    assert!(thread.resume::<_, ()>(()).is_err());
+}
+
+#[nvim_oxi::test]
+fn thread_error() {
+    let fun = lua::load::<(), ()>("error('boom')", "boom").unwrap();
+
+    let thread = Thread::new(fun).unwrap();
+    let err = thread.resume::<_, ()>(()).unwrap_err();
+
+    let lua::Error::RuntimeError { message, traceback } = &err else {
+        panic!("expected a runtime error, got {err:?}");
+    };
+
+    assert!(message.contains("boom"), "{message}");
+    assert!(traceback.is_some());
+    assert_eq!(thread.status(), ThreadStatus::Dead);
+}
+
+#[nvim_oxi::test]
+fn thread_yield_from_rust() {
+    let ping = Function::from_fn(|n: i32| Yield(n * 2));
+    lua::globals().set("oxi_ping", ping).unwrap();
+
+    let fun = lua::load::<(), i32>(
+        "local pong = oxi_ping(21); return pong + 1",
+        "ping",
+    )
+    .unwrap();
+
+    let thread = Thread::new(fun).unwrap();
+
+    assert_eq!(thread.resume::<_, i32>(()).unwrap(), 42);
+    assert_eq!(thread.status(), ThreadStatus::Suspended);
+
+    assert_eq!(thread.resume::<_, i32>(1).unwrap(), 2);
+    assert_eq!(thread.status(), ThreadStatus::Dead);
+}
+
+#[nvim_oxi::test]
+fn thread_yield_from_main_thread() {
+    let ping = Function::<i32, Yield<i32>>::from_fn(Yield);
+    lua::globals().set("oxi_yield", ping).unwrap();
+
+    let err = lua::exec("oxi_yield(1)").unwrap_err();
+    assert!(err.to_string().contains("outside a coroutine"), "{err}");
+}
+
+#[nvim_oxi::test]
+fn thread_from_lua() {
+    let thread = lua::eval::<Thread>(
+        "coroutine.create(function(a, b) coroutine.yield(a + b) end)",
+    )
+    .unwrap();
+
+    assert_eq!(thread.resume::<_, i32>((1, 2)).unwrap(), 3);
+
+    lua::globals().set("oxi_thread", thread.clone()).unwrap();
+    assert_eq!(
+        lua::eval::<String>("coroutine.status(oxi_thread)").unwrap(),
+        "suspended"
+    );
+}
+
+#[nvim_oxi::test]
+fn thread_pop_error_clears_stack() {
+    let fun = lua::load::<(), &str>("return 'foo'", "foo").unwrap();
+
+    let thread = Thread::new(fun).unwrap();
+    assert!(thread.resume::<_, Thread>(()).is_err());
+
+    // The returned value was discarded, so the coroutine isn't mistaken for
+    // one that can still be resumed.
+    assert_eq!(thread.status(), ThreadStatus::Dead);
+}
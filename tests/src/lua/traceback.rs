@@ -0,0 +1,40 @@
+use nvim_oxi::lua::{self, Poppable, ffi};
+use nvim_oxi::{Function, api};
+
+#[nvim_oxi::test]
+fn traceback_on_runtime_error() {
+    api::command(
+        "lua function _G.inner() error('boom') end; function _G.outer() \
+         inner() end",
+    )
+    .unwrap();
+
+    let outer = unsafe {
+        lua::with_state(|lstate| {
+            ffi::lua_getglobal(lstate, lua::cstr!("outer"));
+            Function::<(), ()>::pop(lstate)
+        })
+    }
+    .unwrap();
+
+    let err = outer.call(()).unwrap_err();
+
+    let lua::Error::RuntimeError { message, traceback } = &err else {
+        panic!("expected a runtime error, got {err:?}");
+    };
+
+    assert!(message.contains("boom"), "{message}");
+
+    let frames = traceback.as_ref().unwrap().frames();
+    assert!(frames.iter().any(|frame| frame.what.contains("inner")));
+    assert!(frames.iter().any(|frame| frame.what.contains("outer")));
+
+    let oxi_err = nvim_oxi::Error::from(err.clone());
+    assert!(oxi_err.traceback().is_some());
+
+    let err = api::Error::from(err);
+    assert!(err.traceback().is_some());
+
+    let oxi_err = nvim_oxi::Error::from(err);
+    assert!(oxi_err.traceback().is_some());
+}
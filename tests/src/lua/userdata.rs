@@ -0,0 +1,102 @@
+use nvim_oxi::api;
+use nvim_oxi::lua::{
+    self,
+    MetaMethod,
+    UserData,
+    UserDataRef,
+    UserDataRefMut,
+    UserDataRegistry,
+};
+
+#[derive(Debug)]
+struct Counter {
+    count: u32,
+    step: u32,
+}
+
+impl UserData for Counter {
+    fn register(registry: &mut UserDataRegistry<Self>) {
+        registry.add_method("get", |this, ()| this.count);
+
+        registry.add_method_mut("incr", |this, ()| this.count += this.step);
+
+        registry.add_function("new", |step: u32| Counter { count: 0, step });
+
+        registry.add_field("step", |this| this.step);
+
+        registry.add_field_setter("step", |this, step: u32| this.step = step);
+
+        registry.add_meta_method(MetaMethod::ToString, |this, ()| {
+            format!("Counter({})", this.count)
+        });
+
+        registry.add_meta_method(
+            MetaMethod::Eq,
+            |this, other: UserDataRef<Counter>| this.count == other.count,
+        );
+
+        registry
+            .add_meta_method(MetaMethod::Call, |this, n: u32| this.count * n);
+    }
+}
+
+#[nvim_oxi::test]
+fn userdata_methods_and_fields() -> Result<(), api::Error> {
+    lua::globals().set("counter", Counter { count: 0, step: 1 }).unwrap();
+
+    api::command("lua assert(counter:get() == 0)")?;
+    api::command("lua counter:incr()")?;
+    api::command("lua assert(counter:get() == 1)")?;
+
+    api::command("lua assert(counter.step == 1)")?;
+    api::command("lua counter.step = 5; counter:incr()")?;
+    api::command("lua assert(counter:get() == 6)")?;
+
+    api::command("lua assert(tostring(counter) == 'Counter(6)')")?;
+    api::command("lua assert(counter(2) == 12)")?;
+
+    api::command("lua other = counter.new(3)")?;
+    api::command("lua assert(other.step == 3 and other ~= counter)")?;
+
+    // Assigning a field without a setter is an error.
+    assert!(api::command("lua counter.count = 1").is_err());
+
+    Ok(())
+}
+
+#[nvim_oxi::test]
+fn userdata_borrow() {
+    let globals = lua::globals();
+
+    globals.set("counter", Counter { count: 42, step: 1 }).unwrap();
+
+    let counter = globals.get::<_, UserDataRef<Counter>>("counter").unwrap();
+    assert_eq!(counter.count, 42);
+
+    // Shared borrows can coexist, but not with exclusive ones.
+    let also_counter =
+        globals.get::<_, UserDataRef<Counter>>("counter").unwrap();
+    assert!(globals.get::<_, UserDataRefMut<Counter>>("counter").is_err());
+
+    // Methods that need a mutable borrow fail too.
+    assert!(api::command("lua counter:incr()").is_err());
+
+    drop((counter, also_counter));
+
+    let mut counter =
+        globals.get::<_, UserDataRefMut<Counter>>("counter").unwrap();
+    counter.count += 1;
+    drop(counter);
+
+    api::command("lua assert(counter:get() == 43)").unwrap();
+}
+
+#[nvim_oxi::test]
+fn userdata_wrong_type() {
+    lua::globals().set("not_a_counter", 42).unwrap();
+    assert!(
+        lua::globals()
+            .get::<_, UserDataRef<Counter>>("not_a_counter")
+            .is_err()
+    );
+}
@@ -0,0 +1,148 @@
+use nvim_oxi::lua::{self, MultiValue, Poppable, Pushable, Variadic, ffi};
+use nvim_oxi::{Function, api};
+
+#[nvim_oxi::test]
+fn variadic_args_and_returns() {
+    let globals = lua::globals();
+
+    let split =
+        Function::from_fn(|(sep, words): (String, Variadic<String>)| {
+            let joined = words.join(&sep);
+            (joined, words.len())
+        });
+
+    globals.set("split", split).unwrap();
+
+    api::command(
+        "lua local joined, len = split('-', 'a', 'b', 'c'); _G.joined = \
+         joined; _G.len = len",
+    )
+    .unwrap();
+
+    assert_eq!(globals.get::<_, String>("joined").unwrap(), "a-b-c");
+    assert_eq!(globals.get::<_, usize>("len").unwrap(), 3);
+
+    let reverse = Function::from_fn(|nums: Variadic<i32>| {
+        nums.into_iter().rev().collect::<Variadic<_>>()
+    });
+
+    globals.set("reverse", reverse).unwrap();
+
+    api::command("lua _G.reversed = { reverse(1, 2, 3) }").unwrap();
+
+    assert_eq!(globals.get::<_, Vec<i32>>("reversed").unwrap(), [3, 2, 1]);
+}
+
+#[nvim_oxi::test]
+fn variadic_from_lua_function() {
+    let globals = lua::globals();
+
+    api::command("lua function _G.three() return 1, 2, 3 end").unwrap();
+
+    let three =
+        globals.get::<_, Function<(), Variadic<i32>>>("three").unwrap();
+    assert_eq!(*three.call(()).unwrap(), [1, 2, 3]);
+
+    let three =
+        globals.get::<_, Function<(), (i32, Variadic<i32>)>>("three").unwrap();
+    let (first, rest) = three.call(()).unwrap();
+    assert_eq!(first, 1);
+    assert_eq!(*rest, [2, 3]);
+}
+
+#[nvim_oxi::test]
+fn missing_and_extra_args() {
+    let globals = lua::globals();
+
+    let add =
+        Function::from_fn(|(a, b): (i32, Option<i32>)| a + b.unwrap_or(0));
+
+    globals.set("add", add).unwrap();
+
+    api::command("lua _G.one = add(1); _G.three = add(1, 2, 3)").unwrap();
+
+    assert_eq!(globals.get::<_, i32>("one").unwrap(), 1);
+    assert_eq!(globals.get::<_, i32>("three").unwrap(), 3);
+}
+
+#[nvim_oxi::test]
+fn multi_value() {
+    api::command("lua function _G.mixed() return 1, 'foo', nil, true end")
+        .unwrap();
+
+    let mixed =
+        lua::globals().get::<_, Function<(), MultiValue>>("mixed").unwrap();
+    let values = mixed.call(()).unwrap();
+
+    assert_eq!(values.len(), 4);
+    assert_eq!(values.get::<i32>(0).unwrap().unwrap(), 1);
+    assert_eq!(values.get::<String>(1).unwrap().unwrap(), "foo");
+    assert_eq!(values.get::<Option<i32>>(2).unwrap().unwrap(), None);
+    assert!(values.get::<bool>(3).unwrap().unwrap());
+    assert!(values.get::<bool>(4).is_none());
+
+    let count = Function::from_fn(|values: MultiValue| values.len());
+    assert_eq!(count.call(values).unwrap(), 4);
+
+    let mut values = MultiValue::new();
+    values.push_value(42).unwrap();
+    values.push_value(("foo", ())).unwrap();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values.get::<String>(1).unwrap().unwrap(), "foo");
+}
+
+#[nvim_oxi::test]
+fn tuple_with_variadic_returns() {
+    let globals = lua::globals();
+
+    let prefixed = Function::from_fn(|()| {
+        ("a", [1, 2, 3].into_iter().collect::<Variadic<i32>>())
+    });
+
+    globals.set("prefixed", prefixed).unwrap();
+
+    api::command("lua _G.prefixed_all = { prefixed() }").unwrap();
+
+    let all = globals.get::<_, lua::Table>("prefixed_all").unwrap();
+    assert_eq!(all.len(), 4);
+    assert_eq!(all.get::<_, String>(1).unwrap(), "a");
+    assert_eq!(all.get::<_, i32>(4).unwrap(), 3);
+}
+
+#[nvim_oxi::test]
+fn tuple_pop_order() {
+    unsafe {
+        lua::with_state(|lstate| {
+            let top = ffi::lua_gettop(lstate);
+
+            (1, 2).push(lstate).unwrap();
+            assert_eq!(<(i32, i32)>::pop(lstate).unwrap(), (1, 2));
+            assert_eq!(ffi::lua_gettop(lstate), top);
+
+            (1, 2, 3).push(lstate).unwrap();
+            let (first, rest) =
+                <(i32, Variadic<i32>)>::pop_above(lstate, top).unwrap();
+            assert_eq!(first, 1);
+            assert_eq!(*rest, [2, 3]);
+            assert_eq!(ffi::lua_gettop(lstate), top);
+        })
+    }
+}
+
+#[nvim_oxi::test]
+fn tuple_pop_leaves_values_below() {
+    unsafe {
+        lua::with_state(|lstate| {
+            let top = ffi::lua_gettop(lstate);
+
+            ("x", 1, 2).push(lstate).unwrap();
+
+            // Only the two values at the top of the stack are popped.
+            assert_eq!(<(i32, i32)>::pop(lstate).unwrap(), (1, 2));
+            assert_eq!(ffi::lua_gettop(lstate), top + 1);
+
+            assert_eq!(<String as Poppable>::pop(lstate).unwrap(), "x");
+            assert_eq!(ffi::lua_gettop(lstate), top);
+        })
+    }
+}